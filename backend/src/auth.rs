@@ -1,6 +1,7 @@
-use crate::db::Db;
-use crate::models::{LoginRequest, LoginResponse, User, UserResponse};
-use argon2::{Argon2, PasswordHash, PasswordVerifier};
+use crate::store::Store;
+use crate::world::{Command, JwtKey, RefreshToken, Role, Session, User, World};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
 use axum::{
     body::Body,
     extract::State,
@@ -9,53 +10,384 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use bitflags::bitflags;
 use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{decode, decode_header, encode, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, RwLock};
+use time::Duration as CookieDuration;
 use uuid::Uuid;
 
-// JWT secret - in production, load from environment
-const JWT_SECRET: &[u8] = b"your-secret-key-change-in-production";
-const JWT_EXPIRY_HOURS: i64 = 24;
+bitflags! {
+    /// What a role is allowed to do. `require_permission` checks a single
+    /// bit; a role can combine several.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Permissions: u32 {
+        const CREATE_TASK = 1 << 0;
+        const ASSIGN_TASK = 1 << 1;
+        const DELETE_TASK = 1 << 2;
+        const MANAGE_USERS = 1 << 3;
+    }
+}
+
+impl Role {
+    /// The fixed permission set for each role. There's no per-user override
+    /// yet — granting anything finer than a role requires adding a role.
+    pub fn permissions(self) -> Permissions {
+        match self {
+            Role::Admin => Permissions::all(),
+            Role::Member => Permissions::CREATE_TASK | Permissions::ASSIGN_TASK,
+            Role::Viewer => Permissions::empty(),
+        }
+    }
+
+    /// OAuth-style scope strings stamped into `Claims` at `login` time. These
+    /// describe the same access as `permissions()`, just in the vocabulary
+    /// other services expect from `/api/auth/introspect` rather than this
+    /// crate's internal `Permissions` bitflags.
+    pub fn scopes(self) -> Vec<String> {
+        match self {
+            Role::Admin => vec![
+                "tasks:read".to_string(),
+                "tasks:write".to_string(),
+                "users:admin".to_string(),
+            ],
+            Role::Member => vec!["tasks:read".to_string(), "tasks:write".to_string()],
+            Role::Viewer => vec!["tasks:read".to_string()],
+        }
+    }
+}
+
+/// Name of the cookie the opaque refresh token travels in. Scoped to the
+/// auth endpoints via the cookie's own `path`, so it isn't sent on every
+/// request the way a root-scoped cookie would be.
+const REFRESH_COOKIE_NAME: &str = "txxt_refresh";
+
+/// Errors `login`, `logout`, and `auth_middleware` can return, each mapped to
+/// a status code and a JSON body (`{"status": ..., "message": ...}`) instead
+/// of the bare plaintext `(StatusCode, String)` pairs the rest of this file
+/// still uses — this is the one corner of the auth surface a browser client
+/// needs to branch on programmatically (e.g. `TokenExpired` means "call
+/// `/api/auth/refresh`", not "send the user back to the login form").
+#[derive(Debug)]
+pub enum AuthError {
+    InternalError(anyhow::Error),
+    MissingCredentials,
+    InvalidCredentials,
+    MissingToken,
+    InvalidToken,
+    TokenExpired,
+    UserNotFound,
+    Forbidden,
+    /// `register` rejected a password shorter than `MIN_PASSWORD_LEN`.
+    PasswordTooShort,
+    /// `register` rejected a username that's already taken.
+    UsernameTaken,
+}
+
+#[derive(Serialize)]
+struct AuthErrorBody {
+    status: u16,
+    message: String,
+}
 
-#[derive(Debug, Serialize, Deserialize)]
+impl AuthError {
+    fn status_and_message(&self) -> (StatusCode, String) {
+        match self {
+            AuthError::InternalError(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+            AuthError::MissingCredentials => {
+                (StatusCode::BAD_REQUEST, "Missing credentials".to_string())
+            }
+            AuthError::InvalidCredentials => {
+                (StatusCode::UNAUTHORIZED, "Invalid credentials".to_string())
+            }
+            AuthError::MissingToken => {
+                (StatusCode::UNAUTHORIZED, "Missing authorization".to_string())
+            }
+            AuthError::InvalidToken => (StatusCode::UNAUTHORIZED, "Invalid token".to_string()),
+            AuthError::TokenExpired => (StatusCode::UNAUTHORIZED, "Token expired".to_string()),
+            AuthError::UserNotFound => (StatusCode::UNAUTHORIZED, "User not found".to_string()),
+            AuthError::Forbidden => (
+                StatusCode::FORBIDDEN,
+                "You don't have permission to do that".to_string(),
+            ),
+            AuthError::PasswordTooShort => (
+                StatusCode::BAD_REQUEST,
+                format!("Password must be at least {MIN_PASSWORD_LEN} characters"),
+            ),
+            AuthError::UsernameTaken => {
+                (StatusCode::CONFLICT, "Username is already taken".to_string())
+            }
+        }
+    }
+}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        let (status, message) = self.status_and_message();
+        (status, Json(AuthErrorBody { status: status.as_u16(), message })).into_response()
+    }
+}
+
+/// Tell an expired token apart from a malformed/mis-signed one, so callers
+/// can report `TokenExpired` distinctly — that's the signal a frontend uses
+/// to hit `/api/auth/refresh` instead of bouncing the user to login.
+fn classify_jwt_error(e: jsonwebtoken::errors::Error) -> AuthError {
+    match e.kind() {
+        jsonwebtoken::errors::ErrorKind::ExpiredSignature => AuthError::TokenExpired,
+        _ => AuthError::InvalidToken,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
-    pub sub: Uuid,        // user id
+    pub sub: Uuid,   // user id
     pub username: String,
-    pub exp: usize,       // expiry timestamp
-    pub iat: usize,       // issued at
+    pub role: Role,
+    /// Set from `role.scopes()` at mint time, not recomputed per request —
+    /// a role change takes effect for the holder's *next* token, not
+    /// retroactively for ones already issued.
+    pub scopes: Vec<String>,
+    /// Session id. Looked up in the save file's session table on every
+    /// request, so a revoked session is rejected even while the JWT itself
+    /// is still within its signed expiry.
+    pub jti: Uuid,
+    pub exp: usize,  // expiry timestamp
+    pub iat: usize,  // issued at
 }
 
 pub struct AppState {
-    pub db: Db,
-    pub ws_broadcast: tokio::sync::broadcast::Sender<String>,
+    /// Shared with every configured `AuthProvider` so auto-provisioning
+    /// (e.g. `LdapProvider`) can see and insert users the same way `register`
+    /// does, without `login` needing to know which provider did the work.
+    pub world: Arc<RwLock<World>>,
+    pub save_file: Arc<dyn Store>,
+    pub event_bus: Arc<dyn crate::event_bus::EventBus>,
+    /// Cached copy of the save file's JWT signing keyring, so the hot path
+    /// of every request doesn't re-read it from disk. Only changes when
+    /// something calls `Store::rotate_jwt_key` and refreshes this cache.
+    pub jwt_keyring: RwLock<Vec<JwtKey>>,
+    /// How long a freshly issued access token stays valid, from `Settings`.
+    pub access_token_ttl: Duration,
+    /// How long a freshly issued refresh token stays valid, from `Settings`.
+    pub refresh_token_ttl: Duration,
+    /// The providers `login` tries, in the order configured by
+    /// `[auth] providers`.
+    pub providers: Vec<Box<dyn crate::auth_provider::AuthProvider>>,
+    /// Fired once by `begin_shutdown` on SIGTERM/SIGINT. WS handlers hold a
+    /// subscriber alongside their broadcast receiver so they can drain their
+    /// socket with a clean Close frame instead of being killed mid-frame
+    /// when the runtime stops.
+    pub shutdown: tokio::sync::broadcast::Sender<()>,
+    /// Last revision actually sent to each live `/api/game` connection,
+    /// keyed by a per-connection id `game::handle_socket` mints on upgrade
+    /// and removes on disconnect. The periodic compaction task in `main`
+    /// takes the minimum across this map as its compaction boundary, so it
+    /// never folds away revisions a still-connected (merely lagging) client
+    /// hasn't seen yet — doing so would force that client's next broadcast
+    /// lag into a full snapshot resync instead of `catch_up`'s cheap replay.
+    pub client_revisions: Mutex<HashMap<Uuid, u64>>,
 }
 
 pub type SharedState = Arc<AppState>;
 
-pub fn create_token(user: &User) -> Result<String, jsonwebtoken::errors::Error> {
-    let now = Utc::now();
-    let expiry = now + Duration::hours(JWT_EXPIRY_HOURS);
+impl AppState {
+    /// Tell every connected WebSocket to wind down. Safe to call more than
+    /// once; only subscribers present at call time observe it, which is why
+    /// `main` awaits in-flight sockets after calling this rather than
+    /// immediately dropping the listener.
+    pub fn begin_shutdown(&self) {
+        let _ = self.shutdown.send(());
+    }
+
+    /// The lowest revision any currently-connected `/api/game` client has
+    /// actually been sent, or `None` if nobody's connected. The periodic
+    /// compaction task bounds `World::compact`'s `up_to` by this so it never
+    /// folds away a revision a live (if lagging) client hasn't seen yet.
+    pub fn min_client_revision(&self) -> Option<u64> {
+        self.client_revisions.lock().unwrap().values().copied().min()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+}
+
+/// Shortest password `register` will accept. Not a security-grade policy,
+/// just enough to stop one-character passwords.
+const MIN_PASSWORD_LEN: usize = 8;
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterRequest {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserResponse {
+    pub id: Uuid,
+    pub username: String,
+    pub role: Role,
+}
+
+impl From<&User> for UserResponse {
+    fn from(user: &User) -> Self {
+        UserResponse {
+            id: user.id,
+            username: user.username.clone(),
+            role: user.role,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LoginResponse {
+    pub access_token: String,
+    pub user: UserResponse,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RefreshResponse {
+    pub access_token: String,
+}
+
+/// The keyring's single non-retired entry — the key new tokens are signed
+/// with. `None` means the keyring hasn't been bootstrapped yet.
+fn active_key(keyring: &[JwtKey]) -> Option<&JwtKey> {
+    keyring.iter().find(|k| !k.retired)
+}
 
+fn find_key(keyring: &[JwtKey], kid: Uuid) -> Option<&JwtKey> {
+    keyring.iter().find(|k| k.kid == kid)
+}
+
+fn encode_claims(
+    user: &User,
+    jti: Uuid,
+    ttl: Duration,
+    key: &JwtKey,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let now = Utc::now();
     let claims = Claims {
         sub: user.id,
         username: user.username.clone(),
-        exp: expiry.timestamp() as usize,
+        role: user.role,
+        scopes: user.role.scopes(),
+        jti,
         iat: now.timestamp() as usize,
+        exp: (now + ttl).timestamp() as usize,
     };
 
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(JWT_SECRET),
-    )
+    let mut header = Header::default();
+    header.kid = Some(key.kid.to_string());
+
+    encode(&header, &claims, &EncodingKey::from_secret(&key.secret))
+}
+
+/// Mint a fresh access token for `user`, signed with the keyring's active
+/// key, and record the session backing it. The session is what lets
+/// `logout`/`auth_middleware` reject it before it naturally expires.
+pub fn issue_access_token(
+    user: &User,
+    save_file: &dyn Store,
+    keyring: &[JwtKey],
+    ttl: Duration,
+) -> Result<String, (StatusCode, String)> {
+    let key = active_key(keyring).ok_or((
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "no active JWT signing key".to_string(),
+    ))?;
+    let jti = Uuid::new_v4();
+
+    let access_token = encode_claims(user, jti, ttl, key)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    save_file
+        .save_session(&Session {
+            jti,
+            user_id: user.id,
+            issued_at: Utc::now().timestamp(),
+            revoked: false,
+        })
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(access_token)
+}
+
+/// Generate a new opaque refresh token (256 bits of randomness, hex-encoded
+/// for safe cookie transport), persist the hash of it, and return a cookie
+/// carrying the raw value for the client.
+///
+/// The raw token is never stored — only `sha256(raw_token)` — so a leaked
+/// save file doesn't hand over anything a reader could replay directly.
+fn issue_refresh_cookie(
+    save_file: &dyn Store,
+    user_id: Uuid,
+    ttl: Duration,
+) -> Result<Cookie<'static>, (StatusCode, String)> {
+    let mut raw = [0u8; 32];
+    OsRng.fill_bytes(&mut raw);
+    let raw_token = raw.iter().map(|b| format!("{b:02x}")).collect::<String>();
+
+    save_file
+        .save_refresh_token(&RefreshToken {
+            token_hash: hash_refresh_token(&raw_token),
+            user_id,
+            expires_at: (Utc::now() + ttl).timestamp(),
+        })
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(refresh_cookie(raw_token, ttl))
+}
+
+fn hash_refresh_token(raw_token: &str) -> Vec<u8> {
+    Sha256::digest(raw_token.as_bytes()).to_vec()
+}
+
+/// Build the `HttpOnly`/`Secure`/`SameSite=Strict` cookie the refresh token
+/// travels in, scoped to the auth endpoints that need it.
+fn refresh_cookie(value: String, ttl: Duration) -> Cookie<'static> {
+    let mut cookie = Cookie::new(REFRESH_COOKIE_NAME, value);
+    cookie.set_http_only(true);
+    cookie.set_secure(true);
+    cookie.set_same_site(SameSite::Strict);
+    cookie.set_path("/api/auth");
+    cookie.set_max_age(CookieDuration::seconds(ttl.num_seconds()));
+    cookie
+}
+
+/// An expired version of the refresh cookie, used to clear it on logout.
+fn expired_refresh_cookie() -> Cookie<'static> {
+    let mut cookie = Cookie::new(REFRESH_COOKIE_NAME, "");
+    cookie.set_http_only(true);
+    cookie.set_secure(true);
+    cookie.set_same_site(SameSite::Strict);
+    cookie.set_path("/api/auth");
+    cookie.set_max_age(CookieDuration::seconds(0));
+    cookie
 }
 
-pub fn verify_token(token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
+/// Verify a token's signature against whichever keyring entry signed it
+/// (selected by the `kid` stamped into its header), so tokens issued
+/// before a rotation keep verifying until they expire.
+pub fn verify_token(token: &str, keyring: &[JwtKey]) -> Result<Claims, jsonwebtoken::errors::Error> {
+    let kid = decode_header(token)?
+        .kid
+        .and_then(|k| Uuid::parse_str(&k).ok())
+        .ok_or(jsonwebtoken::errors::ErrorKind::InvalidToken)?;
+
+    let key = find_key(keyring, kid).ok_or(jsonwebtoken::errors::ErrorKind::InvalidToken)?;
+
     let token_data = decode::<Claims>(
         token,
-        &DecodingKey::from_secret(JWT_SECRET),
+        &DecodingKey::from_secret(&key.secret),
         &Validation::default(),
     )?;
     Ok(token_data.claims)
@@ -72,33 +404,242 @@ pub fn verify_password(password: &str, hash: &str) -> bool {
         .is_ok()
 }
 
-// Login handler
+/// Look up a session by `jti` and reject the request if it's missing or
+/// revoked. Shared by `auth_middleware` and `refresh`.
+fn require_live_session(
+    save_file: &dyn Store,
+    jti: Uuid,
+) -> Result<(), (StatusCode, String)> {
+    let session = save_file
+        .get_session(jti)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::UNAUTHORIZED, "Session revoked or unknown".to_string()))?;
+
+    if session.revoked {
+        return Err((StatusCode::UNAUTHORIZED, "Session revoked or unknown".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Same check as `require_live_session`, for the handlers that have moved to
+/// `AuthError`.
+fn require_live_session_typed(save_file: &dyn Store, jti: Uuid) -> Result<(), AuthError> {
+    let session = save_file
+        .get_session(jti)
+        .map_err(|e| AuthError::InternalError(anyhow::anyhow!(e.to_string())))?
+        .ok_or(AuthError::InvalidToken)?;
+
+    if session.revoked {
+        return Err(AuthError::InvalidToken);
+    }
+
+    Ok(())
+}
+
+/// Create a new Member account and log it straight in. New users always
+/// start as `Role::Member` — promoting to `Admin` is a separate, privileged
+/// action, not something self-registration can grant itself.
+pub async fn register(
+    State(state): State<SharedState>,
+    jar: CookieJar,
+    Json(payload): Json<RegisterRequest>,
+) -> Result<(CookieJar, Json<LoginResponse>), AuthError> {
+    if payload.username.is_empty() {
+        return Err(AuthError::MissingCredentials);
+    }
+    if payload.password.len() < MIN_PASSWORD_LEN {
+        return Err(AuthError::PasswordTooShort);
+    }
+
+    let (access_token, refresh, user) = {
+        let mut world = state.world.write().unwrap();
+        if world.get_user_by_username(&payload.username).is_some() {
+            return Err(AuthError::UsernameTaken);
+        }
+
+        let salt = argon2::password_hash::SaltString::generate(&mut OsRng);
+        let password_hash = Argon2::default()
+            .hash_password(payload.password.as_bytes(), &salt)
+            .map_err(|e| AuthError::InternalError(anyhow::anyhow!(e.to_string())))?
+            .to_string();
+
+        let user = User {
+            id: Uuid::new_v4(),
+            username: payload.username,
+            password_hash,
+            role: Role::Member,
+        };
+
+        state
+            .save_file
+            .save_user(&user)
+            .map_err(|e| AuthError::InternalError(anyhow::anyhow!(e.to_string())))?;
+        world.users.insert(user.id, user.clone());
+
+        let keyring = state.jwt_keyring.read().unwrap();
+        let access_token = issue_access_token(
+            &user,
+            state.save_file.as_ref(),
+            &keyring,
+            state.access_token_ttl,
+        )
+        .map_err(|(_, msg)| AuthError::InternalError(anyhow::anyhow!(msg)))?;
+        let refresh = issue_refresh_cookie(state.save_file.as_ref(), user.id, state.refresh_token_ttl)
+            .map_err(|(_, msg)| AuthError::InternalError(anyhow::anyhow!(msg)))?;
+        (access_token, refresh, UserResponse::from(&user))
+    };
+
+    let jar = jar.add(refresh);
+    Ok((jar, Json(LoginResponse { access_token, user })))
+}
+
+// Login handler: issues a short-lived access JWT in the response body and a
+// long-lived opaque refresh token in an HttpOnly cookie.
 pub async fn login(
     State(state): State<SharedState>,
+    jar: CookieJar,
     Json(payload): Json<LoginRequest>,
-) -> Result<Json<LoginResponse>, (StatusCode, String)> {
-    let user = state
-        .db
-        .get_user_by_username(&payload.username)
+) -> Result<(CookieJar, Json<LoginResponse>), AuthError> {
+    if payload.username.is_empty() || payload.password.is_empty() {
+        return Err(AuthError::MissingCredentials);
+    }
+
+    // Try each configured provider in order (local password store, LDAP,
+    // ...) and log in as whichever one first accepts the credentials.
+    let mut user = None;
+    for provider in &state.providers {
+        if let Ok(u) = provider.authenticate(&payload.username, &payload.password).await {
+            user = Some(u);
+            break;
+        }
+    }
+    let user = user.ok_or(AuthError::InvalidCredentials)?;
+
+    let keyring = state.jwt_keyring.read().unwrap();
+    let access_token = issue_access_token(
+        &user,
+        state.save_file.as_ref(),
+        &keyring,
+        state.access_token_ttl,
+    )
+    .map_err(|(_, msg)| AuthError::InternalError(anyhow::anyhow!(msg)))?;
+    let refresh = issue_refresh_cookie(state.save_file.as_ref(), user.id, state.refresh_token_ttl)
+        .map_err(|(_, msg)| AuthError::InternalError(anyhow::anyhow!(msg)))?;
+    let user = UserResponse::from(&user);
+
+    let jar = jar.add(refresh);
+    Ok((jar, Json(LoginResponse { access_token, user })))
+}
+
+/// Mint a new access token from a still-live refresh token, without
+/// requiring the user to log in again. The refresh token is rotated on
+/// every use — the one presented is deleted and a new one issued — so a
+/// stolen-and-replayed cookie stops working the moment the legitimate
+/// client refreshes again.
+pub async fn refresh(
+    State(state): State<SharedState>,
+    jar: CookieJar,
+) -> Result<(CookieJar, Json<RefreshResponse>), (StatusCode, String)> {
+    let raw_token = jar
+        .get(REFRESH_COOKIE_NAME)
+        .map(|c| c.value().to_string())
+        .ok_or((StatusCode::UNAUTHORIZED, "Missing refresh token".to_string()))?;
+
+    let stored = state
+        .save_file
+        .take_refresh_token(&hash_refresh_token(&raw_token))
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or((StatusCode::UNAUTHORIZED, "Invalid credentials".to_string()))?;
+        .ok_or((StatusCode::UNAUTHORIZED, "Invalid or already-used refresh token".to_string()))?;
 
-    if !verify_password(&payload.password, &user.password_hash) {
-        return Err((StatusCode::UNAUTHORIZED, "Invalid credentials".to_string()));
+    if stored.expires_at < Utc::now().timestamp() {
+        return Err((StatusCode::UNAUTHORIZED, "Refresh token expired".to_string()));
     }
 
-    let token = create_token(&user)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let (access_token, new_refresh) = {
+        let world = state.world.read().unwrap();
+        let user = world
+            .users
+            .get(&stored.user_id)
+            .ok_or((StatusCode::UNAUTHORIZED, "User not found".to_string()))?;
 
-    Ok(Json(LoginResponse {
-        token,
-        user: UserResponse::from(user),
-    }))
+        let keyring = state.jwt_keyring.read().unwrap();
+        let access_token = issue_access_token(
+            user,
+            state.save_file.as_ref(),
+            &keyring,
+            state.access_token_ttl,
+        )?;
+        let new_refresh =
+            issue_refresh_cookie(state.save_file.as_ref(), user.id, state.refresh_token_ttl)?;
+        (access_token, new_refresh)
+    };
+
+    let jar = jar.add(new_refresh);
+    Ok((jar, Json(RefreshResponse { access_token })))
 }
 
-// Logout handler (client-side token removal, but we can log it)
-pub async fn logout() -> impl IntoResponse {
-    StatusCode::OK
+// Logout handler: revokes the session backing the caller's access token and
+// deletes the refresh token backing their cookie, so neither can be used
+// again even before they'd naturally expire.
+pub async fn logout(
+    State(state): State<SharedState>,
+    jar: CookieJar,
+    request: Request<Body>,
+) -> Result<(CookieJar, StatusCode), AuthError> {
+    let auth_header = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+
+    let token = match auth_header {
+        Some(h) if h.starts_with("Bearer ") => &h[7..],
+        _ => return Err(AuthError::MissingToken),
+    };
+
+    let claims = {
+        let keyring = state.jwt_keyring.read().unwrap();
+        verify_token(token, &keyring).map_err(classify_jwt_error)?
+    };
+
+    state
+        .save_file
+        .revoke_session(claims.jti)
+        .map_err(|e| AuthError::InternalError(anyhow::anyhow!(e.to_string())))?;
+
+    if let Some(raw_token) = jar.get(REFRESH_COOKIE_NAME).map(|c| c.value().to_string()) {
+        state
+            .save_file
+            .take_refresh_token(&hash_refresh_token(&raw_token))
+            .map_err(|e| AuthError::InternalError(anyhow::anyhow!(e.to_string())))?;
+    }
+
+    let jar = jar.add(expired_refresh_cookie());
+    Ok((jar, StatusCode::OK))
+}
+
+/// Full bearer-token resolution: verify the JWT signature, confirm its
+/// session hasn't been revoked, then confirm the user it names still
+/// exists. Shared by `auth_middleware` (REST) and `game::ws_handler`'s
+/// WebSocket upgrade, so both paths enforce the exact same checks.
+pub fn resolve_token_user(state: &SharedState, token: &str) -> Result<(User, Claims), AuthError> {
+    let claims = {
+        let keyring = state.jwt_keyring.read().unwrap();
+        verify_token(token, &keyring).map_err(classify_jwt_error)?
+    };
+
+    require_live_session_typed(state.save_file.as_ref(), claims.jti)?;
+
+    let user = {
+        let world = state.world.read().unwrap();
+        world
+            .users
+            .get(&claims.sub)
+            .cloned()
+            .ok_or(AuthError::UserNotFound)?
+    };
+
+    Ok((user, claims))
 }
 
 // Auth middleware
@@ -106,7 +647,7 @@ pub async fn auth_middleware(
     State(state): State<SharedState>,
     mut request: Request<Body>,
     next: Next,
-) -> Result<Response, (StatusCode, String)> {
+) -> Result<Response, AuthError> {
     let auth_header = request
         .headers()
         .get(header::AUTHORIZATION)
@@ -114,21 +655,204 @@ pub async fn auth_middleware(
 
     let token = match auth_header {
         Some(h) if h.starts_with("Bearer ") => &h[7..],
-        _ => return Err((StatusCode::UNAUTHORIZED, "Missing authorization".to_string())),
+        _ => return Err(AuthError::MissingToken),
     };
 
-    let claims = verify_token(token)
-        .map_err(|_| (StatusCode::UNAUTHORIZED, "Invalid token".to_string()))?;
+    let (user, claims) = resolve_token_user(&state, token)?;
 
-    // Verify user still exists
-    let user = state
-        .db
-        .get_user(claims.sub)
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or((StatusCode::UNAUTHORIZED, "User not found".to_string()))?;
-
-    // Add user info to request extensions
+    // Add user info to request extensions. `Claims` is kept alongside `User`
+    // so `require_scope` can check the scopes the token was actually minted
+    // with, rather than recomputing them from the user's current role.
     request.extensions_mut().insert(user);
+    request.extensions_mut().insert(claims);
 
     Ok(next.run(request).await)
 }
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Build a middleware that requires the authenticated user's role to carry
+/// `permission`, returning `403` otherwise. Layer it after `auth_middleware`
+/// (which is what populates the `User` extension this reads):
+///
+/// ```ignore
+/// .route_layer(middleware::from_fn(auth::require_permission(Permissions::DELETE_TASK)))
+/// .route_layer(middleware::from_fn_with_state(state, auth::auth_middleware))
+/// ```
+pub fn require_permission(
+    permission: Permissions,
+) -> impl Fn(Request<Body>, Next) -> BoxFuture<'static, Result<Response, AuthError>> + Clone {
+    move |request: Request<Body>, next: Next| Box::pin(check_permission(request, next, permission))
+}
+
+async fn check_permission(
+    request: Request<Body>,
+    next: Next,
+    permission: Permissions,
+) -> Result<Response, AuthError> {
+    let user = request
+        .extensions()
+        .get::<User>()
+        .cloned()
+        .ok_or(AuthError::MissingToken)?;
+
+    if !user.role.permissions().contains(permission) {
+        return Err(AuthError::Forbidden);
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// The `Permissions` bit `cmd` requires to execute, or `None` if issuing it
+/// needs nothing beyond a live session. `Undo`/`Redo` fall in the latter
+/// bucket — they only ever replay the issuing user's own prior command,
+/// which already passed this check once when it was first applied.
+///
+/// Every task mutation multiplexes through the single `/api/game` socket
+/// rather than one REST route per operation, so there's no route for
+/// `require_permission` to `.route_layer` onto the way its doc example
+/// shows. `game::handle_command`/`handle_batch_command` call this directly
+/// (via `command_permitted`) before handing the command to `World::apply`.
+pub fn required_permission(cmd: &Command) -> Option<Permissions> {
+    match cmd {
+        Command::CreateTask { .. } | Command::CreateRecurringTask { .. } => Some(Permissions::CREATE_TASK),
+        Command::DeleteTask { .. } => Some(Permissions::DELETE_TASK),
+        Command::ScheduleTask { .. }
+        | Command::MoveTask { .. }
+        | Command::UnscheduleTask { .. }
+        | Command::CompleteTask { .. }
+        | Command::FailTask { .. }
+        | Command::RetryTask { .. }
+        | Command::AddDependency { .. }
+        | Command::RemoveDependency { .. } => Some(Permissions::ASSIGN_TASK),
+        Command::Undo | Command::Redo => None,
+    }
+}
+
+/// Whether `user`'s role carries whatever permission `cmd` requires.
+pub fn command_permitted(user: &User, cmd: &Command) -> bool {
+    match required_permission(cmd) {
+        Some(permission) => user.role.permissions().contains(permission),
+        None => true,
+    }
+}
+
+/// Build a middleware that requires `scope` among the ones the presented
+/// token was minted with, returning `403` otherwise. Layer it after
+/// `auth_middleware` the same way as `require_permission`:
+///
+/// ```ignore
+/// .route_layer(middleware::from_fn(auth::require_scope("users:admin")))
+/// .route_layer(middleware::from_fn_with_state(state, auth::auth_middleware))
+/// ```
+///
+/// This checks the token's own `scopes` claim, not the user's current role —
+/// unlike `require_permission`, which always reflects the role as of *this*
+/// request. The two diverge only when a role changes mid-session, which is
+/// the point: a scope grant is a property of the token, not a live lookup.
+pub fn require_scope(
+    scope: &'static str,
+) -> impl Fn(Request<Body>, Next) -> BoxFuture<'static, Result<Response, AuthError>> + Clone {
+    move |request: Request<Body>, next: Next| Box::pin(check_scope(request, next, scope))
+}
+
+async fn check_scope(request: Request<Body>, next: Next, scope: &'static str) -> Result<Response, AuthError> {
+    let claims = request
+        .extensions()
+        .get::<Claims>()
+        .cloned()
+        .ok_or(AuthError::MissingToken)?;
+
+    if !claims.scopes.iter().any(|s| s == scope) {
+        return Err(AuthError::Forbidden);
+    }
+
+    Ok(next.run(request).await)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IntrospectRequest {
+    pub token: String,
+}
+
+/// RFC 7662-flavored introspection response. Only `active` is guaranteed
+/// present — the rest are omitted entirely when the token isn't.
+#[derive(Debug, Serialize)]
+pub struct IntrospectResponse {
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<usize>,
+}
+
+impl IntrospectResponse {
+    fn inactive() -> Self {
+        IntrospectResponse { active: false, sub: None, username: None, scopes: None, exp: None }
+    }
+}
+
+/// Let another service ask "is this txxt-issued token still good, and what
+/// can it do" without sharing the signing key or reimplementing
+/// `verify_token`. Expired, malformed, mis-signed, and revoked tokens all
+/// come back as `{"active": false}` rather than an error — introspection
+/// isn't meant to tell a caller *why* a token is no good, only whether it is.
+pub async fn introspect(
+    State(state): State<SharedState>,
+    Json(payload): Json<IntrospectRequest>,
+) -> Json<IntrospectResponse> {
+    let claims = {
+        let keyring = state.jwt_keyring.read().unwrap();
+        verify_token(&payload.token, &keyring)
+    };
+
+    let claims = match claims {
+        Ok(claims) => claims,
+        Err(_) => return Json(IntrospectResponse::inactive()),
+    };
+
+    if require_live_session_typed(state.save_file.as_ref(), claims.jti).is_err() {
+        return Json(IntrospectResponse::inactive());
+    }
+
+    Json(IntrospectResponse {
+        active: true,
+        sub: Some(claims.sub),
+        username: Some(claims.username),
+        scopes: Some(claims.scopes),
+        exp: Some(claims.exp),
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct RotateKeyResponse {
+    pub kid: Uuid,
+    pub created_at: i64,
+}
+
+/// Generate a new JWT signing key, retire every previously-active one, and
+/// refresh `state.jwt_keyring`'s cache so the next token this process issues
+/// or verifies sees it immediately. `rotate_jwt_key` itself was only ever
+/// called once, at bootstrap in `main` — with no route mounted on it, it
+/// could never run again afterward, so a compromised or simply aging
+/// signing key had no way to actually get rotated in a live deployment.
+/// Gated by `require_permission(Permissions::MANAGE_USERS)` in `main`'s
+/// router, the same way any other admin-only action would be.
+pub async fn rotate_key(State(state): State<SharedState>) -> Result<Json<RotateKeyResponse>, AuthError> {
+    let new_key = state
+        .save_file
+        .rotate_jwt_key()
+        .map_err(|e| AuthError::InternalError(anyhow::anyhow!(e.to_string())))?;
+
+    let mut keyring = state.jwt_keyring.write().unwrap();
+    for key in keyring.iter_mut() {
+        key.retired = true;
+    }
+    keyring.push(new_key.clone());
+
+    Ok(Json(RotateKeyResponse { kid: new_key.kid, created_at: new_key.created_at }))
+}
@@ -0,0 +1,84 @@
+//! Pluggable authentication providers.
+//!
+//! `login` doesn't know how a password gets checked — it just tries each
+//! provider configured in `[auth] providers`, in order, and logs in as
+//! whichever one first returns a `User`. `LocalProvider` is the original
+//! Argon2-against-`World` check; other providers (e.g. `LdapProvider`) can
+//! sit in front of it without `login` itself changing.
+
+use crate::auth::{verify_password, AuthError};
+use crate::store::Store;
+use crate::world::{User, World};
+use async_trait::async_trait;
+use std::sync::{Arc, RwLock};
+
+#[async_trait]
+pub trait AuthProvider: Send + Sync {
+    async fn authenticate(&self, username: &str, password: &str) -> Result<User, AuthError>;
+}
+
+/// Verifies against `User::password_hash` in the World — Argon2, same hash
+/// `register` writes. The only provider that exists before LDAP support.
+pub struct LocalProvider {
+    world: Arc<RwLock<World>>,
+}
+
+impl LocalProvider {
+    pub fn new(world: Arc<RwLock<World>>) -> Self {
+        LocalProvider { world }
+    }
+}
+
+#[async_trait]
+impl AuthProvider for LocalProvider {
+    async fn authenticate(&self, username: &str, password: &str) -> Result<User, AuthError> {
+        let world = self.world.read().unwrap();
+        let user = world
+            .get_user_by_username(username)
+            .ok_or(AuthError::InvalidCredentials)?;
+
+        if !verify_password(password, &user.password_hash) {
+            return Err(AuthError::InvalidCredentials);
+        }
+
+        Ok(user.clone())
+    }
+}
+
+/// Build the provider chain `login` tries, in the order given by `names`
+/// (normally `Settings.auth.providers`). An unrecognized name, or `"ldap"`
+/// configured without `[auth.ldap]` or the `ldap` feature, is logged and
+/// skipped rather than failing boot — a typo in config shouldn't take the
+/// whole server down.
+pub fn build(
+    names: &[String],
+    world: Arc<RwLock<World>>,
+    save_file: Arc<dyn Store>,
+    ldap: Option<&crate::config::LdapConfig>,
+) -> Vec<Box<dyn AuthProvider>> {
+    let mut providers: Vec<Box<dyn AuthProvider>> = Vec::new();
+
+    for name in names {
+        match name.as_str() {
+            "local" => providers.push(Box::new(LocalProvider::new(world.clone()))),
+
+            #[cfg(feature = "ldap")]
+            "ldap" => match ldap {
+                Some(cfg) => providers.push(Box::new(crate::ldap_provider::LdapProvider::new(
+                    cfg.clone(),
+                    world.clone(),
+                    save_file.clone(),
+                ))),
+                None => eprintln!("auth provider \"ldap\" configured but [auth.ldap] is missing; skipping"),
+            },
+            #[cfg(not(feature = "ldap"))]
+            "ldap" => eprintln!(
+                "auth provider \"ldap\" configured but this binary was built without the \"ldap\" feature; skipping"
+            ),
+
+            other => eprintln!("unknown auth provider {other:?}; skipping"),
+        }
+    }
+
+    providers
+}
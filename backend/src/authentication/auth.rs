@@ -1,121 +1,81 @@
+//! Cookie-backed session authentication.
+//!
+//! A session cookie carries `{session_id}.{secret}`. The secret is never
+//! stored raw — only its hash, looked up and compared the same way
+//! `User::password_hash` is, via [`crate::security::hash`].
 
-use crate::{app_state::SharedState, login_request::LoginRequest, login_response::LoginResponse, settings::Settings, user::User};
-use argon2::{Argon2, PasswordHash, PasswordVerifier};
 use axum::{
     body::Body,
-    extract::State,
-    http::{header, Request, StatusCode},
+    extract::{FromRequestParts, State},
+    http::{header, request::Parts, HeaderMap, Request, StatusCode},
     middleware::Next,
-    response::{IntoResponse, Response},
-    Json,
+    response::Response,
 };
-use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
-use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Claims {
-    pub sub: Uuid,
-    pub username: String,
-    pub exp: usize,
-    pub iat: usize,
-}
-
-
+use crate::{app_state::SharedState, security::hash, user::User};
 
-pub fn create_token(user: &User) -> Result<String, jsonwebtoken::errors::Error> {
-    let now = Utc::now();
-    let settings = Settings::load().unwrap();
-    let expiry = now + Duration::hours(settings.jwt_expiration_in_minutes as i64);
+pub const SESSION_COOKIE: &str = "txxt_session";
 
-    let claims = Claims {
-        sub: user.id,
-        username: user.username.clone(),
-        exp: expiry.timestamp() as usize,
-        iat: now.timestamp() as usize,
-    };
-
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(settings.jwt_secret.as_bytes()),
-    )
+/// Pull the session cookie off a request and split it into `(session_id, secret)`.
+fn parse_session_cookie(headers: &HeaderMap) -> Option<(Uuid, String)> {
+    let cookie_header = headers.get(header::COOKIE)?.to_str().ok()?;
+    let prefix = format!("{SESSION_COOKIE}=");
+    let raw = cookie_header
+        .split(';')
+        .map(|kv| kv.trim())
+        .find_map(|kv| kv.strip_prefix(prefix.as_str()))?;
+    let (id, secret) = raw.split_once('.')?;
+    Some((Uuid::parse_str(id).ok()?, secret.to_string()))
 }
 
-pub fn verify_token(token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
-    let settings = Settings::load().unwrap();
-    let token_data = decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(settings.jwt_secret.as_bytes()),
-        &Validation::default(),
-    )?;
-    Ok(token_data.claims)
-}
+/// Resolve the user behind a session cookie, rejecting missing/expired/invalid sessions.
+fn resolve_session_user(state: &SharedState, headers: &HeaderMap) -> Result<User, (StatusCode, String)> {
+    let (session_id, secret) = parse_session_cookie(headers)
+        .ok_or((StatusCode::UNAUTHORIZED, "Missing or malformed session cookie".to_string()))?;
 
-pub fn verify_password(password: &str, hash: &str) -> bool {
-    let parsed_hash = match PasswordHash::new(hash) {
-        Ok(h) => h,
-        Err(_) => return false,
-    };
-
-    Argon2::default()
-        .verify_password(password.as_bytes(), &parsed_hash)
-        .is_ok()
-}
-
-pub fn login(
-    State(state): State<SharedState>,
-    Json(payload): Json<LoginRequest>,
-) -> Result<Json<LoginResponse>, (StatusCode, String)> {
-    let user = state
-        .data_context
-        .get_user_by_username(&payload.username)
-        .map_err(|e: redb::Error| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or((StatusCode::UNAUTHORIZED, "Invalid credentials".to_string()))?;
+    let session = state
+        .store
+        .get_session(session_id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::UNAUTHORIZED, "Invalid session".to_string()))?;
 
-    if !verify_password(&payload.password, &user.password_hash) {
-        return Err((StatusCode::UNAUTHORIZED, "Invalid credentials".to_string()));
+    if session.is_expired() {
+        return Err((StatusCode::UNAUTHORIZED, "Session expired".to_string()));
     }
 
-    let token = create_token(&user)
+    let secret_valid = hash::verify(&secret, &session.secret_hash)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if !secret_valid {
+        return Err((StatusCode::UNAUTHORIZED, "Invalid session".to_string()));
+    }
 
-    println!("{} has logged in", user.username);
-    Ok(Json(LoginResponse {
-        token
-    }))
+    state
+        .store
+        .get_user(session.user_id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::UNAUTHORIZED, "User not found".to_string()))
 }
 
-pub fn logout() -> impl IntoResponse {
-    StatusCode::OK
+/// Extractor that resolves the authenticated `User` from the session cookie.
+/// Use directly on a handler when it needs the actor, e.g. `UserController::edit`.
+pub struct RequireUser(pub User);
+
+impl FromRequestParts<SharedState> for RequireUser {
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, state: &SharedState) -> Result<Self, Self::Rejection> {
+        resolve_session_user(state, &parts.headers).map(RequireUser)
+    }
 }
 
+/// Route-layer equivalent of `RequireUser`, for `.layer(middleware::from_fn_with_state(...))`.
 pub async fn auth_middleware(
     State(state): State<SharedState>,
     mut request: Request<Body>,
     next: Next,
 ) -> Result<Response, (StatusCode, String)> {
-    let auth_header = request
-        .headers()
-        .get(header::AUTHORIZATION)
-        .and_then(|value| value.to_str().ok());
-
-    let token = match auth_header {
-        Some(h) if h.starts_with("Bearer ") => Some(&h[7..]),
-        _ => return Err((StatusCode::UNAUTHORIZED, "Missing or invalid token".to_string())),
-    };
-
-    let token = token.unwrap();
-    let claims = verify_token(token)
-        .map_err(|_| (StatusCode::UNAUTHORIZED, "Invalid token".to_string()))?;
-
-    let user = state
-        .data_context
-        .get_user(claims.sub)
-        .map_err(|e: redb::Error| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or((StatusCode::UNAUTHORIZED, "User not found".to_string()))?;
-
+    let user = resolve_session_user(&state, request.headers())?;
     request.extensions_mut().insert(user);
     Ok(next.run(request).await)
-}
\ No newline at end of file
+}
@@ -0,0 +1,168 @@
+//! Pluggable authentication providers.
+//!
+//! `SessionController::login` doesn't know how a password gets checked — it
+//! just tries each provider configured in `Settings.auth_providers`, in
+//! order, and logs in as whichever one first returns a `User`. A provider
+//! returning `Ok(None)` means "not mine to authenticate", not a failure;
+//! `login` only surfaces `InvalidCredentials` once every provider has
+//! passed. `LocalAuthProvider` is the original Argon2-against-`Store`
+//! check; `LdapAuthProvider` sits in front of it without `login` changing.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::{
+    data_access::store::Store, security::hash, settings::LdapSettings,
+    user::{IdentitySource, User},
+};
+
+#[async_trait]
+pub trait AuthProvider: Send + Sync {
+    /// `Ok(None)` means this provider doesn't own `username` (wrong
+    /// identity source, or no such user) — try the next provider, if any.
+    async fn authenticate(&self, username: &str, password: &str) -> Result<Option<User>, AuthError>;
+}
+
+/// Verifies against `User::password_hash` via [`hash::verify`] — the only
+/// provider that exists before LDAP support. Skips users whose identity is
+/// owned by another provider, since their `password_hash` is a random
+/// placeholder nothing should check.
+pub struct LocalAuthProvider {
+    store: Arc<dyn Store>,
+}
+
+impl LocalAuthProvider {
+    pub fn new(store: Arc<dyn Store>) -> Self {
+        LocalAuthProvider { store }
+    }
+}
+
+#[async_trait]
+impl AuthProvider for LocalAuthProvider {
+    async fn authenticate(&self, username: &str, password: &str) -> Result<Option<User>, AuthError> {
+        let Some(user) = self
+            .store
+            .get_user_by_username(username)
+            .map_err(|e| AuthError::Internal(e.into()))?
+        else {
+            return Ok(None);
+        };
+
+        if user.identity_source != IdentitySource::Local {
+            return Ok(None);
+        }
+
+        let verified = hash::verify(password, &user.password_hash).map_err(|e| AuthError::Internal(e.into()))?;
+        Ok(verified.then_some(user))
+    }
+}
+
+/// Binds as the user, not a service account — the bind succeeding *is* the
+/// password check, so txxt never sees or stores the directory password. On
+/// a user's first successful bind a local `User` record is created from the
+/// username (no directory attributes are read) so sessions and task
+/// ownership have a stable `Uuid` to hang off of regardless of which
+/// provider authenticated them.
+pub struct LdapAuthProvider {
+    settings: LdapSettings,
+    store: Arc<dyn Store>,
+}
+
+impl LdapAuthProvider {
+    pub fn new(settings: LdapSettings, store: Arc<dyn Store>) -> Self {
+        LdapAuthProvider { settings, store }
+    }
+
+    fn bind_dn(&self, username: &str) -> String {
+        self.settings.bind_dn_template.replace("{username}", username)
+    }
+
+    /// Look up (or provision) the local `User` backing a directory account.
+    fn provision(&self, username: &str) -> Result<User, AuthError> {
+        if let Some(existing) = self
+            .store
+            .get_user_by_username(username)
+            .map_err(|e| AuthError::Internal(e.into()))?
+        {
+            return Ok(existing);
+        }
+
+        let user = User {
+            id: uuid::Uuid::new_v4(),
+            username: username.to_string(),
+            // Never checked — LDAP verifies the password, not this hash. A
+            // random value just satisfies `User`'s shape and guarantees
+            // local login can't be used to impersonate a directory account.
+            password_hash: hash::hash(&hash::random()).map_err(|e| AuthError::Internal(e.into()))?,
+            email: format!("{username}@{}", self.settings.base_dn),
+            created_at: chrono::Utc::now(),
+            has_avatar: false,
+            identity_source: IdentitySource::Ldap,
+        };
+
+        self.store.create_user(&user).map_err(|e| AuthError::Internal(e.into()))?;
+        Ok(user)
+    }
+}
+
+#[async_trait]
+impl AuthProvider for LdapAuthProvider {
+    async fn authenticate(&self, username: &str, password: &str) -> Result<Option<User>, AuthError> {
+        let conn_settings = ldap3::LdapConnSettings::new()
+            .set_starttls(self.settings.tls && !self.settings.url.starts_with("ldaps://"));
+        let (conn, mut ldap) = ldap3::LdapConnAsync::with_settings(conn_settings, &self.settings.url)
+            .await
+            .map_err(|e| AuthError::Internal(anyhow::anyhow!(e)))?;
+        ldap3::drive!(conn);
+
+        let bind_dn = self.bind_dn(username);
+        let bind_result = ldap.simple_bind(&bind_dn, password).await.and_then(|res| res.success());
+        let _ = ldap.unbind().await;
+        if bind_result.is_err() {
+            return Ok(None);
+        }
+
+        self.provision(username).map(Some)
+    }
+}
+
+/// Build the provider chain `login` tries, in the order given by
+/// `settings.auth_providers`. An unrecognized name, or `"ldap"` configured
+/// without `settings.ldap`, is logged and skipped rather than failing boot —
+/// a typo in config shouldn't take the whole server down.
+pub fn build(settings: &crate::settings::Settings, store: Arc<dyn Store>) -> Vec<Box<dyn AuthProvider>> {
+    let mut providers: Vec<Box<dyn AuthProvider>> = Vec::new();
+
+    for name in &settings.auth_providers {
+        match name.as_str() {
+            "local" => providers.push(Box::new(LocalAuthProvider::new(store.clone()))),
+            "ldap" => match &settings.ldap {
+                Some(cfg) => providers.push(Box::new(LdapAuthProvider::new(cfg.clone(), store.clone()))),
+                None => eprintln!("auth provider \"ldap\" configured but settings.ldap is missing; skipping"),
+            },
+            other => eprintln!("unknown auth provider {other:?}; skipping"),
+        }
+    }
+
+    providers
+}
+
+/// Why an [`AuthProvider::authenticate`] call failed outright (as opposed to
+/// `Ok(None)`, which means "not my identity to check").
+#[derive(Debug)]
+pub enum AuthError {
+    InvalidCredentials,
+    Internal(anyhow::Error),
+}
+
+impl std::fmt::Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthError::InvalidCredentials => write!(f, "invalid credentials"),
+            AuthError::Internal(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
@@ -0,0 +1,110 @@
+//! Startup configuration for the auth layer.
+//!
+//! Loaded once in `main` from a TOML file (default `txxt.toml`, overridable
+//! via `TXXT_CONFIG`), with individual fields overridable by env var on top
+//! of whatever the file says. The JWT signing secret itself isn't part of
+//! this — it's generated and persisted by `SaveFile::rotate_jwt_key`, not
+//! configured — but the token lifetimes are, since how long a session stays
+//! valid is an operational decision, not a code constant.
+
+use serde::Deserialize;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AuthConfig {
+    /// How long an access token stays valid, e.g. "15m".
+    pub access_token_ttl: String,
+    /// How long a refresh token stays valid, e.g. "30d".
+    pub refresh_token_ttl: String,
+    /// Which `AuthProvider`s `login` tries, in order. `"local"` is always
+    /// available; `"ldap"` additionally requires the `ldap` feature and
+    /// `[auth.ldap]` below. Unknown or unavailable entries are skipped with
+    /// a warning at boot rather than failing startup.
+    pub providers: Vec<String>,
+    /// Directory connection details for the `"ldap"` provider. Ignored if
+    /// `"ldap"` isn't listed in `providers`.
+    pub ldap: Option<LdapConfig>,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        AuthConfig {
+            access_token_ttl: "15m".to_string(),
+            refresh_token_ttl: "30d".to_string(),
+            providers: vec!["local".to_string()],
+            ldap: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+pub struct LdapConfig {
+    /// e.g. "ldap://directory.example.com:389".
+    pub url: String,
+    /// Base DN searched for the bound user's attributes, e.g.
+    /// "ou=people,dc=example,dc=com".
+    pub base_dn: String,
+    /// Bind DN with `{username}` substituted in, e.g.
+    /// "uid={username},ou=people,dc=example,dc=com".
+    pub bind_dn_template: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub auth: AuthConfig,
+}
+
+impl Settings {
+    /// Load settings from `TXXT_CONFIG` (default `txxt.toml`), falling back
+    /// to defaults if the file doesn't exist. `TXXT_AUTH_ACCESS_TOKEN_TTL`
+    /// and `TXXT_AUTH_REFRESH_TOKEN_TTL` override the file's values.
+    pub fn load() -> Result<Self, ConfigError> {
+        let path = std::env::var("TXXT_CONFIG").unwrap_or_else(|_| "txxt.toml".to_string());
+
+        let mut settings: Settings = match std::fs::read_to_string(&path) {
+            Ok(contents) => {
+                toml::from_str(&contents).map_err(|e| ConfigError::Parse(path.clone(), e.to_string()))?
+            }
+            Err(_) => Settings::default(),
+        };
+
+        if let Ok(v) = std::env::var("TXXT_AUTH_ACCESS_TOKEN_TTL") {
+            settings.auth.access_token_ttl = v;
+        }
+        if let Ok(v) = std::env::var("TXXT_AUTH_REFRESH_TOKEN_TTL") {
+            settings.auth.refresh_token_ttl = v;
+        }
+
+        Ok(settings)
+    }
+
+    pub fn access_token_ttl(&self) -> Result<Duration, ConfigError> {
+        humantime::parse_duration(&self.auth.access_token_ttl)
+            .map_err(|e| ConfigError::InvalidDuration(self.auth.access_token_ttl.clone(), e.to_string()))
+    }
+
+    pub fn refresh_token_ttl(&self) -> Result<Duration, ConfigError> {
+        humantime::parse_duration(&self.auth.refresh_token_ttl)
+            .map_err(|e| ConfigError::InvalidDuration(self.auth.refresh_token_ttl.clone(), e.to_string()))
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Parse(String, String),
+    InvalidDuration(String, String),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Parse(path, e) => write!(f, "failed to parse config file {path}: {e}"),
+            ConfigError::InvalidDuration(raw, e) => {
+                write!(f, "invalid duration {raw:?}: {e}")
+            }
+        }
+    }
+}
@@ -1,68 +1,409 @@
-use redb::{Database, ReadableDatabase, ReadableTable, TableDefinition};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use redb::{Database, ReadableTable, TableDefinition, WriteTransaction};
 use std::{error::Error, sync::Arc};
 use uuid::Uuid;
 
-use crate::{settings::Settings, tao_task::TaoTask, user::User, user_add_request::UserAddRequest, user_edit_request::UserEditRequest};
+use crate::{session::Session, settings::Settings, tao_task::TaoTask, task_event::{TaskAction, TaskEvent}, task_priority::TaskPriority, task_status::TaskStatus, user::User, user_add_request::UserAddRequest, user_edit_request::UserEditRequest};
 
 const USERS_TABLE: TableDefinition<&[u8], &[u8]> = TableDefinition::new("users");
-const USERNAME_INDEX: TableDefinition<&str, &[u8]> = TableDefinition::new("username_index");
+// Keyed by a per-file HMAC of the username rather than the raw string, so a
+// stolen file doesn't hand over every username just by listing this table's
+// keys — only `USERS_TABLE`'s (encrypted) values do, and only to someone who
+// also has the encryption key.
+const USERNAME_INDEX: TableDefinition<&[u8], &[u8]> = TableDefinition::new("username_index");
+// Keyed the same way as USERNAME_INDEX and for the same reason — a stolen
+// file shouldn't hand over every email address just by listing this table.
+const EMAIL_INDEX: TableDefinition<&[u8], &[u8]> = TableDefinition::new("email_index");
 const TASKS_TABLE: TableDefinition<&[u8], &[u8]> = TableDefinition::new("tasks");
+// Secondary indexes over tasks, keyed by `index_value || task_id` so a range
+// scan over a value's prefix yields exactly the matching task ids, ordered.
+const TASKS_BY_STATUS: TableDefinition<&[u8], &[u8]> = TableDefinition::new("tasks_by_status");
+const TASKS_BY_ASSIGNEE: TableDefinition<&[u8], &[u8]> = TableDefinition::new("tasks_by_assignee");
+const TASKS_BY_CATEGORY: TableDefinition<&[u8], &[u8]> = TableDefinition::new("tasks_by_category");
+const TASKS_BY_PRIORITY: TableDefinition<&[u8], &[u8]> = TableDefinition::new("tasks_by_priority");
+// Keyed by `rfc3339_bytes || task_id` — RFC3339 timestamps with a fixed-width
+// offset sort lexicographically the same as chronologically, so a `due_before`
+// query is a plain range scan up to the bound's encoded bytes.
+const TASKS_BY_DUE_DATE: TableDefinition<&[u8], &[u8]> = TableDefinition::new("tasks_by_due_date");
+// Reverse index keyed by `user_id || task_id`, covering both `created_by` and
+// `assigned_to` — lets `delete_user` find every dependent task without a full
+// `TASKS_TABLE` scan.
+const TASKS_BY_USER: TableDefinition<&[u8], &[u8]> = TableDefinition::new("tasks_by_user");
+// Append-only audit log, keyed by `timestamp_micros_be || event_id` so it
+// iterates in chronological order regardless of which task an entry is for.
+const TASK_EVENTS: TableDefinition<&[u8], &[u8]> = TableDefinition::new("task_events");
+const SESSIONS_TABLE: TableDefinition<&[u8], &[u8]> = TableDefinition::new("sessions");
+const AVATARS_TABLE: TableDefinition<&[u8], &[u8]> = TableDefinition::new("avatars");
+// Schema-version bookkeeping for the migration subsystem below.
+const META_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("meta");
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+const CURRENT_SCHEMA_VERSION: u32 = 1;
 
+// Length in bytes of the random nonce XChaCha20-Poly1305 takes, prefixed
+// onto every encrypted value as `[nonce || ciphertext]`.
+const NONCE_LEN: usize = 24;
+// Prefixed onto every value `encrypt_value` produces, ahead of the nonce, so
+// `decrypt_value` can tell "this is ciphertext under our scheme, and it
+// failed to decrypt under the current key" apart from "this is a legacy
+// plaintext row written before encryption-at-rest existed" instead of
+// guessing from whether the AEAD tag happens to check out.
+const ENCRYPTED_MAGIC: &[u8] = b"txxt:enc:v1:";
+// `blake3::derive_key` context strings. Changing these would silently
+// rotate every derived key out from under an existing save file, so treat
+// them the same as a stored constant, never a runtime value.
+const RECORD_KEY_CONTEXT: &str = "txxt data_context record encryption 2026-07-31";
+const USERNAME_HMAC_CONTEXT: &str = "txxt data_context username index 2026-07-31";
+const EMAIL_HMAC_CONTEXT: &str = "txxt data_context email index 2026-07-31";
 
 #[derive(Clone)]
 pub struct DataContext {
-    db: Arc<Database>
+    db: Arc<Database>,
+    /// XChaCha20-Poly1305 key protecting `USERS_TABLE`/`TASKS_TABLE` values.
+    record_key: [u8; 32],
+    /// BLAKE3 keyed-hash key for `USERNAME_INDEX` keys.
+    username_hmac_key: [u8; 32],
+    /// BLAKE3 keyed-hash key for `EMAIL_INDEX` keys.
+    email_hmac_key: [u8; 32],
+}
+
+/// Error returned by [`DataContext::create_user`]. Distinguishes a genuine
+/// storage failure from a unique-constraint violation so callers can surface
+/// the latter as a `409 Conflict` instead of a `500`.
+#[derive(Debug)]
+pub enum CreateUserError {
+    Storage(redb::Error),
+    UsernameTaken,
+    EmailTaken,
+}
+
+impl std::fmt::Display for CreateUserError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CreateUserError::Storage(e) => write!(f, "{e}"),
+            CreateUserError::UsernameTaken => write!(f, "username already taken"),
+            CreateUserError::EmailTaken => write!(f, "email already taken"),
+        }
+    }
+}
+
+impl std::error::Error for CreateUserError {}
+
+impl From<redb::Error> for CreateUserError {
+    fn from(e: redb::Error) -> Self {
+        CreateUserError::Storage(e)
+    }
+}
+
+/// Error returned by [`DataContext::create_task`], [`DataContext::update_task`]
+/// and [`DataContext::delete_user`] when an operation would violate
+/// referential integrity.
+#[derive(Debug)]
+pub enum DbError {
+    Storage(redb::Error),
+    /// `create_task`/`update_task` named a `created_by` or `assigned_to` user
+    /// id that doesn't resolve in `USERS_TABLE`. Carries the field name.
+    DanglingReference(&'static str),
+    /// `delete_user` was called with `CascadeMode::Refuse` and the user still
+    /// has dependent tasks.
+    HasDependents(&'static str),
+    /// A stored value carries `ENCRYPTED_MAGIC` but didn't decrypt under the
+    /// current `record_key` — a wrong or rotated `data_encryption_key`, or a
+    /// corrupted blob. Deliberately distinct from a legacy unencrypted row
+    /// (which has no magic prefix at all and decrypts as a no-op), so this
+    /// never gets silently treated as plaintext and handed to `serde_json`.
+    Decrypt,
+}
+
+impl std::fmt::Display for DbError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DbError::Storage(e) => write!(f, "{e}"),
+            DbError::DanglingReference(field) => write!(f, "{field} does not reference an existing user"),
+            DbError::HasDependents(what) => write!(f, "refusing to delete: user still has {what}"),
+            DbError::Decrypt => write!(f, "failed to decrypt a stored value under the current key"),
+        }
+    }
+}
+
+impl std::error::Error for DbError {}
+
+impl From<redb::Error> for DbError {
+    fn from(e: redb::Error) -> Self {
+        DbError::Storage(e)
+    }
+}
+
+/// Selects what [`DataContext::delete_user`] does with a user's dependent
+/// tasks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CascadeMode {
+    /// Delete tasks the user created, and unassign (but keep) tasks only
+    /// assigned to them.
+    Cascade,
+    /// Fail with `DbError::HasDependents` if any dependent task exists.
+    Refuse,
 }
 
 impl DataContext {
-    pub fn new(path: &str) -> Result<Self, redb::Error> {
+    pub fn new(path: &str, settings: &Settings) -> Result<Self, redb::Error> {
         let db = Database::create(path)?;
         let write_txn = db.begin_write()?;
         let _ = write_txn.open_table(USERS_TABLE)?;
         let _ = write_txn.open_table(TASKS_TABLE)?;
+        let _ = write_txn.open_table(TASKS_BY_STATUS)?;
+        let _ = write_txn.open_table(TASKS_BY_ASSIGNEE)?;
+        let _ = write_txn.open_table(TASKS_BY_CATEGORY)?;
+        let _ = write_txn.open_table(TASKS_BY_PRIORITY)?;
+        let _ = write_txn.open_table(TASKS_BY_DUE_DATE)?;
+        let _ = write_txn.open_table(TASKS_BY_USER)?;
+        let _ = write_txn.open_table(TASK_EVENTS)?;
         let _ = write_txn.open_table(USERNAME_INDEX)?;
+        let _ = write_txn.open_table(EMAIL_INDEX)?;
+        let _ = write_txn.open_table(SESSIONS_TABLE)?;
+        let _ = write_txn.open_table(AVATARS_TABLE)?;
+        let _ = write_txn.open_table(META_TABLE)?;
+        write_txn.commit()?;
+
+        // Bring a fresh or older file up to CURRENT_SCHEMA_VERSION before
+        // anything else touches it, so a struct change never panics a
+        // postcard/serde_json decode on boot.
+        Self::run_migrations(&db)?;
+
+        // `data_encryption_key` is the passphrase of record if set; falling
+        // back to `jwt_secret` means a deployment that never opted into
+        // encryption-at-rest explicitly still gets a stable, secret-derived
+        // key rather than one hard-coded in source.
+        let key_material = settings
+            .data_encryption_key
+            .as_deref()
+            .unwrap_or(&settings.jwt_secret)
+            .as_bytes();
+
+        Ok(DataContext {
+            db: Arc::new(db),
+            record_key: blake3::derive_key(RECORD_KEY_CONTEXT, key_material),
+            username_hmac_key: blake3::derive_key(USERNAME_HMAC_CONTEXT, key_material),
+            email_hmac_key: blake3::derive_key(EMAIL_HMAC_CONTEXT, key_material),
+        })
+    }
+
+    /// The schema version this file is currently at, after any migrations
+    /// run by `new` have applied. Mostly useful for diagnostics/health checks.
+    pub fn current_schema_version(&self) -> Result<u32, redb::Error> {
+        Self::read_schema_version(&self.db)
+    }
+
+    fn read_schema_version(db: &Database) -> Result<u32, redb::Error> {
+        let read_txn = db.begin_read()?;
+        let meta = read_txn.open_table(META_TABLE)?;
+        match meta.get(SCHEMA_VERSION_KEY)? {
+            Some(v) => {
+                let bytes = v.value();
+                if bytes.len() != 4 {
+                    return Ok(0);
+                }
+                Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// Apply every pending migration in order, one write transaction per
+    /// step so a crash mid-run leaves the version at the last completed
+    /// step rather than a half-applied one.
+    fn run_migrations(db: &Database) -> Result<(), redb::Error> {
+        loop {
+            let version = Self::read_schema_version(db)?;
+            if version >= CURRENT_SCHEMA_VERSION {
+                return Ok(());
+            }
+
+            let migration = MIGRATIONS
+                .iter()
+                .find(|m| m.from_version == version)
+                .unwrap_or_else(|| panic!("no migration registered for schema version {version}"));
+
+            let txn = db.begin_write()?;
+            (migration.run)(&txn)?;
+            {
+                let mut meta = txn.open_table(META_TABLE)?;
+                meta.insert(SCHEMA_VERSION_KEY, (version + 1).to_le_bytes().as_slice())?;
+            }
+            txn.commit()?;
+        }
+    }
+
+    /// Encrypt a plaintext blob for storage as
+    /// `[ENCRYPTED_MAGIC || nonce || ciphertext]`, with a fresh random nonce
+    /// every call. The magic prefix is what lets `decrypt_value` distinguish
+    /// a value written by this function from a legacy plaintext row.
+    fn encrypt_value(&self, plaintext: &[u8]) -> Vec<u8> {
+        let cipher = XChaCha20Poly1305::new((&self.record_key).into());
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext)
+            .expect("XChaCha20-Poly1305 encryption of a bounded plaintext cannot fail");
+        let mut out = Vec::with_capacity(ENCRYPTED_MAGIC.len() + NONCE_LEN + ciphertext.len());
+        out.extend_from_slice(ENCRYPTED_MAGIC);
+        out.extend_from_slice(nonce.as_slice());
+        out.extend_from_slice(&ciphertext);
+        out
+    }
+
+    /// Decrypt a value written by `encrypt_value`. A blob with no
+    /// `ENCRYPTED_MAGIC` prefix is a legacy plaintext row written before this
+    /// subsystem existed — returned as-is, so the next write through
+    /// `encrypt_value` upgrades it in place. A blob that *does* carry the
+    /// magic prefix but fails to decrypt under `record_key` (too short, or
+    /// the AEAD tag doesn't match — a wrong or rotated
+    /// `data_encryption_key`, or corruption) is a genuine failure and comes
+    /// back as `DbError::Decrypt` rather than being handed to the caller as
+    /// if it were valid plaintext.
+    fn decrypt_value(&self, stored: &[u8]) -> Result<Vec<u8>, DbError> {
+        let Some(rest) = stored.strip_prefix(ENCRYPTED_MAGIC) else {
+            return Ok(stored.to_vec());
+        };
+        if rest.len() <= NONCE_LEN {
+            return Err(DbError::Decrypt);
+        }
+        let (nonce_bytes, ciphertext) = rest.split_at(NONCE_LEN);
+        let cipher = XChaCha20Poly1305::new((&self.record_key).into());
+        cipher
+            .decrypt(XNonce::from_slice(nonce_bytes), ciphertext)
+            .map_err(|_| DbError::Decrypt)
+    }
+
+    /// Keyed-hash a username into its `USERNAME_INDEX` key, so the index
+    /// stays searchable by exact username without storing it in the clear.
+    fn username_index_key(&self, username: &str) -> [u8; 32] {
+        *blake3::keyed_hash(&self.username_hmac_key, username.as_bytes()).as_bytes()
+    }
+
+    /// Keyed-hash an email into its `EMAIL_INDEX` key, so the index stays
+    /// searchable by exact email without storing it in the clear.
+    fn email_index_key(&self, email: &str) -> [u8; 32] {
+        *blake3::keyed_hash(&self.email_hmac_key, email.as_bytes()).as_bytes()
+    }
+
+    // SESSIONS
+    pub fn create_session(&self, session: &Session) -> Result<(), redb::Error> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut sessions_table = write_txn.open_table(SESSIONS_TABLE)?;
+            let session_bytes = serde_json::to_vec(session).unwrap();
+            sessions_table.insert(session.id.as_bytes().as_slice(), session_bytes.as_slice())?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    pub fn get_session(&self, id: Uuid) -> Result<Option<Session>, redb::Error> {
+        let read_txn = self.db.begin_read()?;
+        let sessions_table = read_txn.open_table(SESSIONS_TABLE)?;
+        match sessions_table.get(id.as_bytes().as_slice())? {
+            Some(data) => {
+                let session: Session = serde_json::from_slice(data.value()).unwrap();
+                Ok(Some(session))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn delete_session(&self, id: Uuid) -> Result<bool, redb::Error> {
+        let write_txn = self.db.begin_write()?;
+        let deleted;
+        {
+            let mut sessions_table = write_txn.open_table(SESSIONS_TABLE)?;
+            deleted = sessions_table.remove(id.as_bytes().as_slice())?.is_some();
+        }
+        write_txn.commit()?;
+        Ok(deleted)
+    }
+
+    // AVATARS
+    pub fn put_avatar(&self, user_id: Uuid, png_bytes: &[u8]) -> Result<(), redb::Error> {
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut avatars_table = write_txn.open_table(AVATARS_TABLE)?;
+            avatars_table.insert(user_id.as_bytes().as_slice(), png_bytes)?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    pub fn get_avatar(&self, user_id: Uuid) -> Result<Option<Vec<u8>>, redb::Error> {
+        let read_txn = self.db.begin_read()?;
+        let avatars_table = read_txn.open_table(AVATARS_TABLE)?;
+        match avatars_table.get(user_id.as_bytes().as_slice())? {
+            Some(data) => Ok(Some(data.value().to_vec())),
+            None => Ok(None),
+        }
+    }
+
+    pub fn mark_user_has_avatar(&self, id: Uuid) -> Result<bool, redb::Error> {
+        let user = if let Some(u) = self.get_user(id)? { u } else { return Ok(false) };
+        let write_txn = self.db.begin_write()?;
+        {
+            let mut users_table = write_txn.open_table(USERS_TABLE)?;
+            let updated = user.with_avatar(true);
+            let user_bytes = self.encrypt_value(&serde_json::to_vec(&updated).unwrap());
+            users_table.insert(updated.id.as_bytes().as_slice(), user_bytes.as_slice())?;
+        }
         write_txn.commit()?;
-        Ok(DataContext { db: Arc::new(db)})
+        Ok(true)
     }
 
     // USERS
-    pub fn create_user(&self, user: &User) -> Result<(), redb::Error> {
+    pub fn create_user(&self, user: &User) -> Result<(), CreateUserError> {
         let write_txn = self.db.begin_write()?;
         {
             let mut users_table = write_txn.open_table(USERS_TABLE)?;
             let mut username_index = write_txn.open_table(USERNAME_INDEX)?;
-            let user_bytes = serde_json::to_vec(user).unwrap();
+            let mut email_index = write_txn.open_table(EMAIL_INDEX)?;
+
+            let username_key = self.username_index_key(&user.username);
+            if username_index.get(username_key.as_slice())?.is_some() {
+                return Err(CreateUserError::UsernameTaken);
+            }
+            let email_key = self.email_index_key(&user.email);
+            if email_index.get(email_key.as_slice())?.is_some() {
+                return Err(CreateUserError::EmailTaken);
+            }
+
+            let user_bytes = self.encrypt_value(&serde_json::to_vec(user).unwrap());
             let id_bytes = user.id.as_bytes();
             users_table.insert(id_bytes.as_slice(), user_bytes.as_slice())?;
-            username_index.insert(user.username.as_str(), id_bytes.as_slice())?;
+            username_index.insert(username_key.as_slice(), id_bytes.as_slice())?;
+            email_index.insert(email_key.as_slice(), id_bytes.as_slice())?;
         }
         write_txn.commit()?;
         Ok(())
     }
 
-    pub fn get_user(&self, id: Uuid) -> Result<Option<User>, redb::Error> {
+    pub fn get_user(&self, id: Uuid) -> Result<Option<User>, DbError> {
         let read_txn = self.db.begin_read()?;
         let users_table = read_txn.open_table(USERS_TABLE)?;
         let id_bytes = id.as_bytes();
         match users_table.get(id_bytes.as_slice())? {
             Some(data) => {
-                let user: User = serde_json::from_slice(data.value()).unwrap();
+                let user: User = serde_json::from_slice(&self.decrypt_value(data.value())?).unwrap();
                 Ok(Some(user))
             }
             None => Ok(None),
         }
     }
 
-    pub fn get_user_by_username(&self, username: &str) -> Result<Option<User>, redb::Error> {
+    pub fn get_user_by_username(&self, username: &str) -> Result<Option<User>, DbError> {
         let read_txn = self.db.begin_read()?;
         let username_index = read_txn.open_table(USERNAME_INDEX)?;
 
-        match username_index.get(username)? {
+        match username_index.get(self.username_index_key(username).as_slice())? {
             Some(id_data) => {
                 let users_table = read_txn.open_table(USERS_TABLE)?;
                 match users_table.get(id_data.value())? {
                     Some(user_data) => {
-                        let user: User = serde_json::from_slice(user_data.value()).unwrap();
+                        let user: User = serde_json::from_slice(&self.decrypt_value(user_data.value())?).unwrap();
                         Ok(Some(user))
                     }
                     None => Ok(None),
@@ -72,26 +413,111 @@ impl DataContext {
         }
     }
 
-    pub fn list_users(&self) -> Result<Vec<User>, redb::Error> {
+    pub fn get_user_by_email(&self, email: &str) -> Result<Option<User>, DbError> {
+        let read_txn = self.db.begin_read()?;
+        let email_index = read_txn.open_table(EMAIL_INDEX)?;
+
+        match email_index.get(self.email_index_key(email).as_slice())? {
+            Some(id_data) => {
+                let users_table = read_txn.open_table(USERS_TABLE)?;
+                match users_table.get(id_data.value())? {
+                    Some(user_data) => {
+                        let user: User = serde_json::from_slice(&self.decrypt_value(user_data.value())?).unwrap();
+                        Ok(Some(user))
+                    }
+                    None => Ok(None),
+                }
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn list_users(&self) -> Result<Vec<User>, DbError> {
         let read_txn = self.db.begin_read()?;
         let users_table = read_txn.open_table(USERS_TABLE)?;
 
         let mut users = Vec::new();
         for entry in users_table.iter()? {
             let (_, value) = entry?;
-            let user: User = serde_json::from_slice(value.value()).unwrap();
+            let user: User = serde_json::from_slice(&self.decrypt_value(value.value())?).unwrap();
             users.push(user);
         }
         Ok(users)
     }
 
-    pub fn delete_user(&self, id: Uuid) -> Result<bool, redb::Error> {
+    /// Every task id referencing `user_id` as `created_by` or `assigned_to`,
+    /// via the `TASKS_BY_USER` reverse index — no `TASKS_TABLE` scan.
+    fn dependent_task_ids(&self, user_id: Uuid) -> Result<Vec<Uuid>, redb::Error> {
+        let read_txn = self.db.begin_read()?;
+        let by_user = read_txn.open_table(TASKS_BY_USER)?;
+        let mut ids = scan_index_prefix(&by_user, user_id.as_bytes().as_slice())?;
+        ids.sort();
+        ids.dedup();
+        Ok(ids)
+    }
+
+    /// Delete a user, honoring referential integrity with their tasks.
+    /// `CascadeMode::Refuse` fails with `DbError::HasDependents` if the user
+    /// still owns or is assigned any task. `CascadeMode::Cascade` instead
+    /// deletes tasks the user created (with no task, an orphaned assignee
+    /// reference is meaningless) and unassigns — without deleting — tasks
+    /// only assigned to them.
+    pub fn delete_user(&self, id: Uuid, mode: CascadeMode) -> Result<bool, DbError> {
         let user = if let Some(user) = self.get_user(id)? { user } else { return Ok(false) };
+        let dependents = self.dependent_task_ids(id)?;
+        if mode == CascadeMode::Refuse && !dependents.is_empty() {
+            return Err(DbError::HasDependents("tasks"));
+        }
+
         let write_txn = self.db.begin_write()?;
         let mut deleted;
         {
-            let mut table = write_txn.open_table(USERNAME_INDEX)?;   
-            deleted = table.remove(user.username.as_str())?.is_some();
+            let mut tasks_table = write_txn.open_table(TASKS_TABLE)?;
+            let mut by_status = write_txn.open_table(TASKS_BY_STATUS)?;
+            let mut by_assignee = write_txn.open_table(TASKS_BY_ASSIGNEE)?;
+            let mut by_category = write_txn.open_table(TASKS_BY_CATEGORY)?;
+            let mut by_priority = write_txn.open_table(TASKS_BY_PRIORITY)?;
+            let mut by_due_date = write_txn.open_table(TASKS_BY_DUE_DATE)?;
+            let mut by_user = write_txn.open_table(TASKS_BY_USER)?;
+            let mut events_table = write_txn.open_table(TASK_EVENTS)?;
+
+            for task_id in dependents {
+                let Some(data) = tasks_table.get(task_id.as_bytes().as_slice())? else { continue };
+                let task: TaoTask = serde_json::from_slice(&self.decrypt_value(data.value())?).unwrap();
+
+                if task.created_by == id {
+                    tasks_table.remove(task_id.as_bytes().as_slice())?;
+                    by_status.remove(status_index_key(&task.status, task.id).as_slice())?;
+                    by_user.remove(user_task_index_key(task.created_by, task.id).as_slice())?;
+                    if let Some(assigned_to) = task.assigned_to {
+                        by_assignee.remove(assignee_index_key(assigned_to, task.id).as_slice())?;
+                        by_user.remove(user_task_index_key(assigned_to, task.id).as_slice())?;
+                    }
+                    if let Some(category) = &task.category {
+                        by_category.remove(category_index_key(category, task.id).as_slice())?;
+                    }
+                    by_priority.remove(priority_index_key(&task.priority, task.id).as_slice())?;
+                    if let Some(due_date) = task.due_date {
+                        by_due_date.remove(due_date_index_key(due_date, task.id).as_slice())?;
+                    }
+                    insert_task_event(&mut events_table, task.id, TaskAction::Deleted, id)?;
+                } else if task.assigned_to == Some(id) {
+                    by_assignee.remove(assignee_index_key(id, task.id).as_slice())?;
+                    by_user.remove(user_task_index_key(id, task.id).as_slice())?;
+                    let updated = TaoTask { assigned_to: None, ..task };
+                    let task_bytes = self.encrypt_value(&serde_json::to_vec(&updated).unwrap());
+                    tasks_table.insert(task_id.as_bytes().as_slice(), task_bytes.as_slice())?;
+                    insert_task_event(&mut events_table, task_id, TaskAction::Updated { task: updated }, id)?;
+                }
+            }
+        }
+        {
+            let mut table = write_txn.open_table(USERNAME_INDEX)?;
+            deleted = table.remove(self.username_index_key(&user.username).as_slice())?.is_some();
+        }
+        {
+            let mut table = write_txn.open_table(EMAIL_INDEX)?;
+            table.remove(self.email_index_key(&user.email).as_slice())?;
         }
         {
             let mut table = write_txn.open_table(USERS_TABLE)?;
@@ -102,19 +528,24 @@ impl DataContext {
         Ok(deleted)
     }
 
-    pub fn edit_user(&self, id: Uuid, dto: UserEditRequest) -> Result<bool, redb::Error> {
+    pub fn edit_user(&self, id: Uuid, dto: UserEditRequest) -> Result<bool, DbError> {
         let user = if let Some(u) = self.get_user(id)? { u } else { return Ok(false) };
         let write_txn = self.db.begin_write()?;
         let edited_user = user.clone().edit(dto);
         {
             let mut users_table = write_txn.open_table(USERS_TABLE)?;
             let mut username_index = write_txn.open_table(USERNAME_INDEX)?;
-            let user_bytes = serde_json::to_vec(&edited_user).unwrap();
+            let mut email_index = write_txn.open_table(EMAIL_INDEX)?;
+            let user_bytes = self.encrypt_value(&serde_json::to_vec(&edited_user).unwrap());
             let id_bytes = edited_user.id.as_bytes();
             users_table.insert(id_bytes.as_slice(), user_bytes.as_slice())?;
-            username_index.insert(edited_user.username.as_str(), id_bytes.as_slice())?;
+            username_index.insert(self.username_index_key(&edited_user.username).as_slice(), id_bytes.as_slice())?;
+            email_index.insert(self.email_index_key(&edited_user.email).as_slice(), id_bytes.as_slice())?;
             if user.username != edited_user.username {
-                username_index.remove(user.username.as_str())?;
+                username_index.remove(self.username_index_key(&user.username).as_slice())?;
+            }
+            if user.email != edited_user.email {
+                email_index.remove(self.email_index_key(&user.email).as_slice())?;
             }
         }
         write_txn.commit()?;
@@ -132,7 +563,8 @@ impl DataContext {
                 username: settings.default_admin_username.clone(),
                 email: settings.default_admin_email.clone()
             };
-            let default_admin = User::new(default_user_creation_request);
+            let default_admin = User::new(default_user_creation_request)
+                .map_err(|e| format!("Failed to hash default admin password: {}", e))?;
             self.create_user(&default_admin)?;
             println!("Created default admin user {}", settings.default_admin_username);
         }
@@ -140,40 +572,78 @@ impl DataContext {
     }
 
     // TAOTASKS
-    pub fn create_task(&self, task: &TaoTask) -> Result<(), redb::Error> {
+    //
+    // `TaoTask` carries no service/category foreign key — `category` is a
+    // free-form string, not an id into any table — so the "service id
+    // resolves in SERVICES_TABLE" check doesn't apply to this schema; there
+    // is no `SERVICES_TABLE` here. Only `created_by`/`assigned_to` are
+    // validated against `USERS_TABLE` below.
+    pub fn create_task(&self, task: &TaoTask, actor: Uuid) -> Result<(), DbError> {
         let write_txn = self.db.begin_write()?;
         {
+            let users_table = write_txn.open_table(USERS_TABLE)?;
+            if users_table.get(task.created_by.as_bytes().as_slice())?.is_none() {
+                return Err(DbError::DanglingReference("created_by"));
+            }
+            if let Some(assigned_to) = task.assigned_to {
+                if users_table.get(assigned_to.as_bytes().as_slice())?.is_none() {
+                    return Err(DbError::DanglingReference("assigned_to"));
+                }
+            }
+
             let mut tasks_table = write_txn.open_table(TASKS_TABLE)?;
-            let task_bytes = serde_json::to_vec(task).unwrap();
+            let mut by_status = write_txn.open_table(TASKS_BY_STATUS)?;
+            let mut by_assignee = write_txn.open_table(TASKS_BY_ASSIGNEE)?;
+            let mut by_category = write_txn.open_table(TASKS_BY_CATEGORY)?;
+            let mut by_priority = write_txn.open_table(TASKS_BY_PRIORITY)?;
+            let mut by_due_date = write_txn.open_table(TASKS_BY_DUE_DATE)?;
+            let mut by_user = write_txn.open_table(TASKS_BY_USER)?;
+            let mut events_table = write_txn.open_table(TASK_EVENTS)?;
+
+            let task_bytes = self.encrypt_value(&serde_json::to_vec(task).unwrap());
             let id_bytes = task.id.as_bytes();
             tasks_table.insert(id_bytes.as_slice(), task_bytes.as_slice())?;
+            by_status.insert(status_index_key(&task.status, task.id).as_slice(), id_bytes.as_slice())?;
+            by_user.insert(user_task_index_key(task.created_by, task.id).as_slice(), id_bytes.as_slice())?;
+            if let Some(assigned_to) = task.assigned_to {
+                by_assignee.insert(assignee_index_key(assigned_to, task.id).as_slice(), id_bytes.as_slice())?;
+                by_user.insert(user_task_index_key(assigned_to, task.id).as_slice(), id_bytes.as_slice())?;
+            }
+            if let Some(category) = &task.category {
+                by_category.insert(category_index_key(category, task.id).as_slice(), id_bytes.as_slice())?;
+            }
+            by_priority.insert(priority_index_key(&task.priority, task.id).as_slice(), id_bytes.as_slice())?;
+            if let Some(due_date) = task.due_date {
+                by_due_date.insert(due_date_index_key(due_date, task.id).as_slice(), id_bytes.as_slice())?;
+            }
+            insert_task_event(&mut events_table, task.id, TaskAction::Created { task: task.clone() }, actor)?;
         }
         write_txn.commit()?;
         Ok(())
     }
 
-    pub fn get_task(&self, id: Uuid) -> Result<Option<TaoTask>, redb::Error> {
+    pub fn get_task(&self, id: Uuid) -> Result<Option<TaoTask>, DbError> {
         let read_txn = self.db.begin_read()?;
         let tasks_table = read_txn.open_table(TASKS_TABLE)?;
 
         let id_bytes = id.as_bytes();
         match tasks_table.get(id_bytes.as_slice())? {
             Some(data) => {
-                let task: TaoTask = serde_json::from_slice(data.value()).unwrap();
+                let task: TaoTask = serde_json::from_slice(&self.decrypt_value(data.value())?).unwrap();
                 Ok(Some(task))
             }
             None => Ok(None),
         }
     }
 
-    pub fn list_tasks(&self) -> Result<Vec<TaoTask>, redb::Error> {
+    pub fn list_tasks(&self) -> Result<Vec<TaoTask>, DbError> {
         let read_txn = self.db.begin_read()?;
         let tasks_table = read_txn.open_table(TASKS_TABLE)?;
 
         let mut tasks = Vec::new();
         for entry in tasks_table.iter()? {
             let (_, value) = entry?;
-            let task: TaoTask = serde_json::from_slice(value.value()).unwrap();
+            let task: TaoTask = serde_json::from_slice(&self.decrypt_value(value.value())?).unwrap();
             tasks.push(task);
         }
 
@@ -182,28 +652,429 @@ impl DataContext {
         Ok(tasks)
     }
 
-    pub fn update_task(&self, task: &TaoTask) -> Result<(), redb::Error> {
+    /// Run a filtered, paginated task query. When more than one of
+    /// `status`/`assigned_to`/`priority`/`due_before`/`category` is set on
+    /// `filter`, the first one present (in that order, narrowest first) drives
+    /// a redb range scan over its index; any remaining fields are then applied
+    /// as an in-memory filter over that already-narrowed set. With no filter
+    /// fields set at all, this falls back to the same full scan `list_tasks` does.
+    pub fn query_tasks(&self, filter: TaskFilter) -> Result<Vec<TaoTask>, DbError> {
+        let read_txn = self.db.begin_read()?;
+        let tasks_table = read_txn.open_table(TASKS_TABLE)?;
+
+        let ids: Option<Vec<Uuid>> = if let Some(status) = &filter.status {
+            let by_status = read_txn.open_table(TASKS_BY_STATUS)?;
+            Some(scan_index_prefix(&by_status, &[status_byte(status)])?)
+        } else if let Some(assigned_to) = filter.assigned_to {
+            let by_assignee = read_txn.open_table(TASKS_BY_ASSIGNEE)?;
+            Some(scan_index_prefix(&by_assignee, assigned_to.as_bytes().as_slice())?)
+        } else if let Some(priority) = &filter.priority {
+            let by_priority = read_txn.open_table(TASKS_BY_PRIORITY)?;
+            Some(scan_index_prefix(&by_priority, &[priority_byte(priority)])?)
+        } else if let Some(due_before) = filter.due_before {
+            let by_due_date = read_txn.open_table(TASKS_BY_DUE_DATE)?;
+            Some(scan_index_upper_bound(&by_due_date, due_before.to_rfc3339().as_bytes())?)
+        } else if let Some(category) = &filter.category {
+            let by_category = read_txn.open_table(TASKS_BY_CATEGORY)?;
+            Some(scan_index_prefix(&by_category, category.as_bytes())?)
+        } else {
+            None
+        };
+
+        let mut tasks: Vec<TaoTask> = match ids {
+            Some(ids) => {
+                let mut out = Vec::with_capacity(ids.len());
+                for id in ids {
+                    if let Some(data) = tasks_table.get(id.as_bytes().as_slice())? {
+                        out.push(serde_json::from_slice(&self.decrypt_value(data.value())?).unwrap());
+                    }
+                }
+                out
+            }
+            None => {
+                let mut out = Vec::new();
+                for entry in tasks_table.iter()? {
+                    let (_, value) = entry?;
+                    out.push(serde_json::from_slice(&self.decrypt_value(value.value())?).unwrap());
+                }
+                out
+            }
+        };
+
+        if let Some(status) = &filter.status {
+            tasks.retain(|t| &t.status == status);
+        }
+        if let Some(assigned_to) = filter.assigned_to {
+            tasks.retain(|t| t.assigned_to == Some(assigned_to));
+        }
+        if let Some(priority) = &filter.priority {
+            tasks.retain(|t| &t.priority == priority);
+        }
+        if let Some(due_before) = filter.due_before {
+            tasks.retain(|t| t.due_date.is_some_and(|d| d < due_before));
+        }
+        if let Some(category) = &filter.category {
+            tasks.retain(|t| t.category.as_deref() == Some(category.as_str()));
+        }
+
+        tasks.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(tasks.into_iter().skip(filter.offset).take(filter.limit).collect())
+    }
+
+    /// Batched username lookup for just the given ids, so rendering a page of
+    /// task responses costs one pass over `USERS_TABLE` instead of one
+    /// `get_user` round-trip per assignee.
+    pub fn get_usernames(&self, ids: &[Uuid]) -> Result<std::collections::HashMap<Uuid, String>, DbError> {
+        let read_txn = self.db.begin_read()?;
+        let users_table = read_txn.open_table(USERS_TABLE)?;
+
+        let mut usernames = std::collections::HashMap::with_capacity(ids.len());
+        for &id in ids {
+            if let Some(data) = users_table.get(id.as_bytes().as_slice())? {
+                let user: User = serde_json::from_slice(&self.decrypt_value(data.value())?).unwrap();
+                usernames.insert(id, user.username);
+            }
+        }
+        Ok(usernames)
+    }
+
+    pub fn update_task(&self, task: &TaoTask, actor: Uuid) -> Result<(), DbError> {
+        let old = self.get_task(task.id)?;
         let write_txn = self.db.begin_write()?;
         {
+            let users_table = write_txn.open_table(USERS_TABLE)?;
+            if users_table.get(task.created_by.as_bytes().as_slice())?.is_none() {
+                return Err(DbError::DanglingReference("created_by"));
+            }
+            if let Some(assigned_to) = task.assigned_to {
+                if users_table.get(assigned_to.as_bytes().as_slice())?.is_none() {
+                    return Err(DbError::DanglingReference("assigned_to"));
+                }
+            }
+
             let mut tasks_table = write_txn.open_table(TASKS_TABLE)?;
-            let task_bytes = serde_json::to_vec(task).unwrap();
+            let mut by_status = write_txn.open_table(TASKS_BY_STATUS)?;
+            let mut by_assignee = write_txn.open_table(TASKS_BY_ASSIGNEE)?;
+            let mut by_category = write_txn.open_table(TASKS_BY_CATEGORY)?;
+            let mut by_priority = write_txn.open_table(TASKS_BY_PRIORITY)?;
+            let mut by_due_date = write_txn.open_table(TASKS_BY_DUE_DATE)?;
+            let mut by_user = write_txn.open_table(TASKS_BY_USER)?;
+            let mut events_table = write_txn.open_table(TASK_EVENTS)?;
+
+            if let Some(old) = &old {
+                if old.status != task.status {
+                    by_status.remove(status_index_key(&old.status, old.id).as_slice())?;
+                }
+                if old.created_by != task.created_by {
+                    by_user.remove(user_task_index_key(old.created_by, old.id).as_slice())?;
+                }
+                if old.assigned_to != task.assigned_to {
+                    if let Some(old_assignee) = old.assigned_to {
+                        by_assignee.remove(assignee_index_key(old_assignee, old.id).as_slice())?;
+                        by_user.remove(user_task_index_key(old_assignee, old.id).as_slice())?;
+                    }
+                }
+                if old.category != task.category {
+                    if let Some(old_category) = &old.category {
+                        by_category.remove(category_index_key(old_category, old.id).as_slice())?;
+                    }
+                }
+                if old.priority != task.priority {
+                    by_priority.remove(priority_index_key(&old.priority, old.id).as_slice())?;
+                }
+                if old.due_date != task.due_date {
+                    if let Some(old_due_date) = old.due_date {
+                        by_due_date.remove(due_date_index_key(old_due_date, old.id).as_slice())?;
+                    }
+                }
+            }
+
+            let task_bytes = self.encrypt_value(&serde_json::to_vec(task).unwrap());
             let id_bytes = task.id.as_bytes();
             tasks_table.insert(id_bytes.as_slice(), task_bytes.as_slice())?;
+            by_status.insert(status_index_key(&task.status, task.id).as_slice(), id_bytes.as_slice())?;
+            by_user.insert(user_task_index_key(task.created_by, task.id).as_slice(), id_bytes.as_slice())?;
+            if let Some(assigned_to) = task.assigned_to {
+                by_assignee.insert(assignee_index_key(assigned_to, task.id).as_slice(), id_bytes.as_slice())?;
+                by_user.insert(user_task_index_key(assigned_to, task.id).as_slice(), id_bytes.as_slice())?;
+            }
+            if let Some(category) = &task.category {
+                by_category.insert(category_index_key(category, task.id).as_slice(), id_bytes.as_slice())?;
+            }
+            by_priority.insert(priority_index_key(&task.priority, task.id).as_slice(), id_bytes.as_slice())?;
+            if let Some(due_date) = task.due_date {
+                by_due_date.insert(due_date_index_key(due_date, task.id).as_slice(), id_bytes.as_slice())?;
+            }
+            insert_task_event(&mut events_table, task.id, TaskAction::Updated { task: task.clone() }, actor)?;
         }
         write_txn.commit()?;
         Ok(())
     }
 
-    pub fn delete_task(&self, id: Uuid) -> Result<bool, redb::Error> {
+    pub fn delete_task(&self, id: Uuid, actor: Uuid) -> Result<bool, DbError> {
+        let task = if let Some(t) = self.get_task(id)? { t } else { return Ok(false) };
         let write_txn = self.db.begin_write()?;
         let deleted;
         {
             let mut tasks_table = write_txn.open_table(TASKS_TABLE)?;
+            let mut by_status = write_txn.open_table(TASKS_BY_STATUS)?;
+            let mut by_assignee = write_txn.open_table(TASKS_BY_ASSIGNEE)?;
+            let mut by_category = write_txn.open_table(TASKS_BY_CATEGORY)?;
+            let mut by_priority = write_txn.open_table(TASKS_BY_PRIORITY)?;
+            let mut by_due_date = write_txn.open_table(TASKS_BY_DUE_DATE)?;
+            let mut by_user = write_txn.open_table(TASKS_BY_USER)?;
+            let mut events_table = write_txn.open_table(TASK_EVENTS)?;
+
             let id_bytes = id.as_bytes();
             let result = tasks_table.remove(id_bytes.as_slice())?;
             deleted = result.is_some();
+            by_status.remove(status_index_key(&task.status, task.id).as_slice())?;
+            by_user.remove(user_task_index_key(task.created_by, task.id).as_slice())?;
+            if let Some(assigned_to) = task.assigned_to {
+                by_assignee.remove(assignee_index_key(assigned_to, task.id).as_slice())?;
+                by_user.remove(user_task_index_key(assigned_to, task.id).as_slice())?;
+            }
+            if let Some(category) = &task.category {
+                by_category.remove(category_index_key(category, task.id).as_slice())?;
+            }
+            by_priority.remove(priority_index_key(&task.priority, task.id).as_slice())?;
+            if let Some(due_date) = task.due_date {
+                by_due_date.remove(due_date_index_key(due_date, task.id).as_slice())?;
+            }
+            if deleted {
+                insert_task_event(&mut events_table, task.id, TaskAction::Deleted, actor)?;
+            }
         }
         write_txn.commit()?;
         Ok(deleted)
     }
+
+    /// The ordered audit trail for one task. `Empty` means the task id was
+    /// never seen at all, distinct from `Found` with an empty `events` list
+    /// (a task that predates audit logging, or whose events were pruned).
+    pub fn task_history(&self, id: Uuid) -> Result<TaskHistory, DbError> {
+        let task_exists = self.get_task(id)?.is_some();
+
+        let read_txn = self.db.begin_read()?;
+        let events_table = read_txn.open_table(TASK_EVENTS)?;
+
+        let mut events = Vec::new();
+        for entry in events_table.iter()? {
+            let (_, value) = entry?;
+            let event: TaskEvent = serde_json::from_slice(value.value()).unwrap();
+            if event.task_id == id {
+                events.push(event);
+            }
+        }
+        if events.is_empty() && !task_exists {
+            return Ok(TaskHistory::Empty);
+        }
+        Ok(TaskHistory::Found { events })
+    }
+
+    /// Every recorded task event at or after `since`, in chronological order,
+    /// regardless of which task it's for. `None` returns the full log. Backs
+    /// `TaskWsController`'s replay-on-connect: a client reports the last
+    /// event time it saw, and gets everything since instead of a full
+    /// `list_tasks` refetch.
+    pub fn task_events_since(&self, since: Option<chrono::DateTime<chrono::Utc>>) -> Result<Vec<TaskEvent>, redb::Error> {
+        let read_txn = self.db.begin_read()?;
+        let events_table = read_txn.open_table(TASK_EVENTS)?;
+
+        let mut events = Vec::new();
+        let rows: Box<dyn Iterator<Item = Result<_, redb::StorageError>>> = match since {
+            Some(since) => {
+                let lower = task_event_key(since, Uuid::nil());
+                Box::new(events_table.range(lower.as_slice()..)?)
+            }
+            None => Box::new(events_table.iter()?),
+        };
+        for entry in rows {
+            let (_, value) = entry?;
+            events.push(serde_json::from_slice::<TaskEvent>(value.value()).unwrap());
+        }
+        Ok(events)
+    }
+}
+
+/// Result of [`DataContext::task_history`].
+#[derive(Debug, Clone)]
+pub enum TaskHistory {
+    Found { events: Vec<TaskEvent> },
+    Empty,
+}
+
+/// Filter + pagination parameters for [`DataContext::query_tasks`].
+#[derive(Debug, Default, Clone)]
+pub struct TaskFilter {
+    pub status: Option<TaskStatus>,
+    pub assigned_to: Option<Uuid>,
+    pub category: Option<String>,
+    pub priority: Option<TaskPriority>,
+    pub due_before: Option<chrono::DateTime<chrono::Utc>>,
+    pub limit: usize,
+    pub offset: usize,
+}
+
+fn status_byte(status: &TaskStatus) -> u8 {
+    match status {
+        TaskStatus::Pending => 0,
+        TaskStatus::InProgress => 1,
+        TaskStatus::Completed => 2,
+    }
+}
+
+fn priority_byte(priority: &TaskPriority) -> u8 {
+    match priority {
+        TaskPriority::Low => 0,
+        TaskPriority::Medium => 1,
+        TaskPriority::High => 2,
+        TaskPriority::Urgent => 3,
+    }
+}
+
+fn status_index_key(status: &TaskStatus, task_id: Uuid) -> Vec<u8> {
+    let mut key = vec![status_byte(status)];
+    key.extend_from_slice(task_id.as_bytes());
+    key
+}
+
+fn assignee_index_key(assigned_to: Uuid, task_id: Uuid) -> Vec<u8> {
+    let mut key = assigned_to.as_bytes().to_vec();
+    key.extend_from_slice(task_id.as_bytes());
+    key
+}
+
+/// Key into `TASKS_BY_USER`: `user_id || task_id`. Shares the same encoding
+/// as `assignee_index_key` (both are 16-byte-user-id-prefixed), but lives in
+/// its own table since one user id can legitimately own two entries for the
+/// same task (creator and assignee).
+fn user_task_index_key(user_id: Uuid, task_id: Uuid) -> Vec<u8> {
+    let mut key = user_id.as_bytes().to_vec();
+    key.extend_from_slice(task_id.as_bytes());
+    key
+}
+
+fn category_index_key(category: &str, task_id: Uuid) -> Vec<u8> {
+    let mut key = category.as_bytes().to_vec();
+    key.extend_from_slice(task_id.as_bytes());
+    key
+}
+
+fn priority_index_key(priority: &TaskPriority, task_id: Uuid) -> Vec<u8> {
+    let mut key = vec![priority_byte(priority)];
+    key.extend_from_slice(task_id.as_bytes());
+    key
+}
+
+fn due_date_index_key(due_date: chrono::DateTime<chrono::Utc>, task_id: Uuid) -> Vec<u8> {
+    let mut key = due_date.to_rfc3339().into_bytes();
+    key.extend_from_slice(task_id.as_bytes());
+    key
+}
+
+fn task_event_key(occurred_at: chrono::DateTime<chrono::Utc>, event_id: Uuid) -> Vec<u8> {
+    let micros = occurred_at.timestamp_micros().max(0) as u64;
+    let mut key = micros.to_be_bytes().to_vec();
+    key.extend_from_slice(event_id.as_bytes());
+    key
+}
+
+/// Append one entry to `TASK_EVENTS`, stamped with the current time and a
+/// fresh event id (so two events in the same microsecond don't collide).
+fn insert_task_event(
+    events_table: &mut redb::Table<&[u8], &[u8]>,
+    task_id: Uuid,
+    action: TaskAction,
+    actor: Uuid,
+) -> Result<(), redb::Error> {
+    let event = TaskEvent { task_id, action, actor, occurred_at: chrono::Utc::now() };
+    let key = task_event_key(event.occurred_at, Uuid::new_v4());
+    let event_bytes = serde_json::to_vec(&event).unwrap();
+    events_table.insert(key.as_slice(), event_bytes.as_slice())?;
+    Ok(())
+}
+
+/// Read the task id back out of a composite index key (the trailing 16 bytes).
+fn task_id_from_index_key(key: &[u8]) -> Uuid {
+    let id_bytes: [u8; 16] = key[key.len() - 16..].try_into().unwrap();
+    Uuid::from_bytes(id_bytes)
+}
+
+/// Exclusive upper bound for a `prefix` range scan: the smallest byte string
+/// greater than every string starting with `prefix`. `None` if `prefix` is
+/// all `0xFF` and has no such bound — in that (practically unreachable) case
+/// the caller should scan unbounded and stop at the first non-matching key.
+fn prefix_upper_bound(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut bound = prefix.to_vec();
+    for i in (0..bound.len()).rev() {
+        if bound[i] != 0xFF {
+            bound[i] += 1;
+            bound.truncate(i + 1);
+            return Some(bound);
+        }
+    }
+    None
+}
+
+/// Range-scan a secondary index table for every key starting with `prefix`,
+/// returning the task ids encoded in those keys in index order.
+fn scan_index_prefix(table: &redb::ReadOnlyTable<&[u8], &[u8]>, prefix: &[u8]) -> Result<Vec<Uuid>, redb::Error> {
+    let mut ids = Vec::new();
+    match prefix_upper_bound(prefix) {
+        Some(upper) => {
+            for entry in table.range(prefix..upper.as_slice())? {
+                let (key, _) = entry?;
+                ids.push(task_id_from_index_key(key.value()));
+            }
+        }
+        None => {
+            for entry in table.range(prefix..)? {
+                let (key, _) = entry?;
+                if !key.value().starts_with(prefix) {
+                    break;
+                }
+                ids.push(task_id_from_index_key(key.value()));
+            }
+        }
+    }
+    Ok(ids)
+}
+
+/// Range-scan a secondary index table for every key strictly less than
+/// `upper` — used for `due_before`, where the matching keys aren't a shared
+/// prefix but a contiguous range up to the bound.
+fn scan_index_upper_bound(table: &redb::ReadOnlyTable<&[u8], &[u8]>, upper: &[u8]) -> Result<Vec<Uuid>, redb::Error> {
+    let mut ids = Vec::new();
+    for entry in table.range(..upper)? {
+        let (key, _) = entry?;
+        ids.push(task_id_from_index_key(key.value()));
+    }
+    Ok(ids)
+}
+
+// ── Migrations ─────────────────────────────────────────────────
+
+/// One schema step: `from_version` is the version it upgrades *from*, and
+/// `run` does the work inside a write transaction that also bumps
+/// `schema_version` to `from_version + 1` on commit.
+struct Migration {
+    from_version: u32,
+    run: fn(&WriteTransaction) -> Result<(), redb::Error>,
+}
+
+static MIGRATIONS: &[Migration] = &[Migration {
+    from_version: 0,
+    run: establish_baseline_schema,
+}];
+
+/// Version 0 is "no `META_TABLE` row yet" — a file created before this
+/// subsystem existed, or a brand-new one. Either way there's nothing to
+/// re-encode; this step only exists so `run_migrations` has a registered
+/// entry to walk version 0 forward to version 1. Future migrations that
+/// actually reshape `User`/`TaoTask`/etc. rows land here, each reading raw
+/// bytes out of the relevant table, decoding the old shape, mapping into
+/// the new one, and re-inserting before returning.
+fn establish_baseline_schema(_txn: &WriteTransaction) -> Result<(), redb::Error> {
+    Ok(())
 }
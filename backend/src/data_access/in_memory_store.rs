@@ -0,0 +1,258 @@
+//! In-memory [`Store`] implementation, for fast controller/router unit
+//! tests that shouldn't have to stand up a redb file on disk. Mirrors
+//! `DataContext`'s observable behavior (unique username/email, referential
+//! integrity on task create/update, cascade-or-refuse on user delete) but
+//! keeps everything in plain `BTreeMap`s behind a `Mutex`, so there's no
+//! encryption, no secondary indexes, and no persistence across restarts —
+//! none of which a test double needs.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{session::Session, tao_task::TaoTask, task_event::{TaskAction, TaskEvent}, user::User, user_edit_request::UserEditRequest};
+
+use super::data_context::{CascadeMode, TaskFilter, TaskHistory};
+use super::store::{Store, StoreError};
+
+#[derive(Default)]
+pub struct InMemoryStore {
+    users: Mutex<BTreeMap<Uuid, User>>,
+    sessions: Mutex<BTreeMap<Uuid, Session>>,
+    avatars: Mutex<BTreeMap<Uuid, Vec<u8>>>,
+    tasks: Mutex<BTreeMap<Uuid, TaoTask>>,
+    events: Mutex<Vec<TaskEvent>>,
+}
+
+impl InMemoryStore {
+    pub fn new() -> Self {
+        InMemoryStore::default()
+    }
+
+    fn record_event(&self, task_id: Uuid, action: TaskAction, actor: Uuid) {
+        self.events.lock().unwrap().push(TaskEvent { task_id, action, actor, occurred_at: Utc::now() });
+    }
+}
+
+impl Store for InMemoryStore {
+    fn create_session(&self, session: &Session) -> Result<(), StoreError> {
+        self.sessions.lock().unwrap().insert(session.id, session.clone());
+        Ok(())
+    }
+
+    fn get_session(&self, id: Uuid) -> Result<Option<Session>, StoreError> {
+        Ok(self.sessions.lock().unwrap().get(&id).cloned())
+    }
+
+    fn delete_session(&self, id: Uuid) -> Result<bool, StoreError> {
+        Ok(self.sessions.lock().unwrap().remove(&id).is_some())
+    }
+
+    fn put_avatar(&self, user_id: Uuid, png_bytes: &[u8]) -> Result<(), StoreError> {
+        self.avatars.lock().unwrap().insert(user_id, png_bytes.to_vec());
+        Ok(())
+    }
+
+    fn get_avatar(&self, user_id: Uuid) -> Result<Option<Vec<u8>>, StoreError> {
+        Ok(self.avatars.lock().unwrap().get(&user_id).cloned())
+    }
+
+    fn mark_user_has_avatar(&self, id: Uuid) -> Result<bool, StoreError> {
+        let mut users = self.users.lock().unwrap();
+        match users.get_mut(&id) {
+            Some(user) => {
+                *user = user.clone().with_avatar(true);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn create_user(&self, user: &User) -> Result<(), StoreError> {
+        let mut users = self.users.lock().unwrap();
+        if users.values().any(|u| u.username == user.username) {
+            return Err(StoreError::UsernameTaken);
+        }
+        if users.values().any(|u| u.email == user.email) {
+            return Err(StoreError::EmailTaken);
+        }
+        users.insert(user.id, user.clone());
+        Ok(())
+    }
+
+    fn get_user(&self, id: Uuid) -> Result<Option<User>, StoreError> {
+        Ok(self.users.lock().unwrap().get(&id).cloned())
+    }
+
+    fn get_user_by_username(&self, username: &str) -> Result<Option<User>, StoreError> {
+        Ok(self.users.lock().unwrap().values().find(|u| u.username == username).cloned())
+    }
+
+    fn get_user_by_email(&self, email: &str) -> Result<Option<User>, StoreError> {
+        Ok(self.users.lock().unwrap().values().find(|u| u.email == email).cloned())
+    }
+
+    fn list_users(&self) -> Result<Vec<User>, StoreError> {
+        Ok(self.users.lock().unwrap().values().cloned().collect())
+    }
+
+    fn delete_user(&self, id: Uuid, mode: CascadeMode) -> Result<bool, StoreError> {
+        if self.users.lock().unwrap().get(&id).is_none() {
+            return Ok(false);
+        }
+
+        let dependents: Vec<Uuid> = self
+            .tasks
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|t| t.created_by == id || t.assigned_to == Some(id))
+            .map(|t| t.id)
+            .collect();
+
+        if mode == CascadeMode::Refuse && !dependents.is_empty() {
+            return Err(StoreError::HasDependents("tasks"));
+        }
+
+        {
+            let mut tasks = self.tasks.lock().unwrap();
+            for task_id in dependents {
+                let Some(task) = tasks.get(&task_id).cloned() else { continue };
+                if task.created_by == id {
+                    tasks.remove(&task_id);
+                    self.record_event(task_id, TaskAction::Deleted, id);
+                } else if task.assigned_to == Some(id) {
+                    let updated = TaoTask { assigned_to: None, ..task };
+                    tasks.insert(task_id, updated.clone());
+                    self.record_event(task_id, TaskAction::Updated { task: updated }, id);
+                }
+            }
+        }
+
+        Ok(self.users.lock().unwrap().remove(&id).is_some())
+    }
+
+    fn edit_user(&self, id: Uuid, dto: UserEditRequest) -> Result<bool, StoreError> {
+        let mut users = self.users.lock().unwrap();
+        match users.get(&id).cloned() {
+            Some(user) => {
+                users.insert(id, user.edit(dto));
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn ensure_default_user(&self) -> Result<(), StoreError> {
+        use crate::{settings::Settings, user_add_request::UserAddRequest};
+
+        let settings = Settings::load().map_err(|e| StoreError::Storage(e.to_string()))?;
+        if self.list_users()?.is_empty() {
+            let default_user_creation_request = UserAddRequest {
+                password: settings.default_admin_password.clone(),
+                username: settings.default_admin_username.clone(),
+                email: settings.default_admin_email.clone(),
+            };
+            let default_admin = User::new(default_user_creation_request)
+                .map_err(|e| StoreError::Storage(format!("failed to hash default admin password: {e}")))?;
+            self.create_user(&default_admin)?;
+        }
+        Ok(())
+    }
+
+    fn create_task(&self, task: &TaoTask, actor: Uuid) -> Result<(), StoreError> {
+        {
+            let users = self.users.lock().unwrap();
+            if !users.contains_key(&task.created_by) {
+                return Err(StoreError::DanglingReference("created_by"));
+            }
+            if let Some(assigned_to) = task.assigned_to {
+                if !users.contains_key(&assigned_to) {
+                    return Err(StoreError::DanglingReference("assigned_to"));
+                }
+            }
+        }
+        self.tasks.lock().unwrap().insert(task.id, task.clone());
+        self.record_event(task.id, TaskAction::Created { task: task.clone() }, actor);
+        Ok(())
+    }
+
+    fn get_task(&self, id: Uuid) -> Result<Option<TaoTask>, StoreError> {
+        Ok(self.tasks.lock().unwrap().get(&id).cloned())
+    }
+
+    fn list_tasks(&self) -> Result<Vec<TaoTask>, StoreError> {
+        let mut tasks: Vec<TaoTask> = self.tasks.lock().unwrap().values().cloned().collect();
+        tasks.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(tasks)
+    }
+
+    fn query_tasks(&self, filter: TaskFilter) -> Result<Vec<TaoTask>, StoreError> {
+        let mut tasks: Vec<TaoTask> = self.tasks.lock().unwrap().values().cloned().collect();
+        if let Some(status) = &filter.status {
+            tasks.retain(|t| &t.status == status);
+        }
+        if let Some(assigned_to) = filter.assigned_to {
+            tasks.retain(|t| t.assigned_to == Some(assigned_to));
+        }
+        if let Some(priority) = &filter.priority {
+            tasks.retain(|t| &t.priority == priority);
+        }
+        if let Some(due_before) = filter.due_before {
+            tasks.retain(|t| t.due_date.is_some_and(|d| d < due_before));
+        }
+        if let Some(category) = &filter.category {
+            tasks.retain(|t| t.category.as_deref() == Some(category.as_str()));
+        }
+        tasks.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(tasks.into_iter().skip(filter.offset).take(filter.limit).collect())
+    }
+
+    fn get_usernames(&self, ids: &[Uuid]) -> Result<std::collections::HashMap<Uuid, String>, StoreError> {
+        let users = self.users.lock().unwrap();
+        Ok(ids.iter().filter_map(|id| users.get(id).map(|u| (*id, u.username.clone()))).collect())
+    }
+
+    fn update_task(&self, task: &TaoTask, actor: Uuid) -> Result<(), StoreError> {
+        {
+            let users = self.users.lock().unwrap();
+            if !users.contains_key(&task.created_by) {
+                return Err(StoreError::DanglingReference("created_by"));
+            }
+            if let Some(assigned_to) = task.assigned_to {
+                if !users.contains_key(&assigned_to) {
+                    return Err(StoreError::DanglingReference("assigned_to"));
+                }
+            }
+        }
+        self.tasks.lock().unwrap().insert(task.id, task.clone());
+        self.record_event(task.id, TaskAction::Updated { task: task.clone() }, actor);
+        Ok(())
+    }
+
+    fn delete_task(&self, id: Uuid, actor: Uuid) -> Result<bool, StoreError> {
+        let deleted = self.tasks.lock().unwrap().remove(&id).is_some();
+        if deleted {
+            self.record_event(id, TaskAction::Deleted, actor);
+        }
+        Ok(deleted)
+    }
+
+    fn task_history(&self, id: Uuid) -> Result<TaskHistory, StoreError> {
+        let events: Vec<TaskEvent> = self.events.lock().unwrap().iter().filter(|e| e.task_id == id).cloned().collect();
+        if events.is_empty() && !self.tasks.lock().unwrap().contains_key(&id) {
+            return Ok(TaskHistory::Empty);
+        }
+        Ok(TaskHistory::Found { events })
+    }
+
+    fn task_events_since(&self, since: Option<DateTime<Utc>>) -> Result<Vec<TaskEvent>, StoreError> {
+        let events = self.events.lock().unwrap();
+        Ok(match since {
+            Some(since) => events.iter().filter(|e| e.occurred_at >= since).cloned().collect(),
+            None => events.clone(),
+        })
+    }
+}
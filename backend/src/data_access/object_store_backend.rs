@@ -0,0 +1,132 @@
+//! Object-store-backed [`Store`] — future work.
+//!
+//! The intent is to persist each record as a keyed object (`{prefix}/users/{id}`,
+//! `{prefix}/tasks/{id}`, ...) in a bucket named by `Settings`, batching the
+//! writes a single `DataContext` transaction would make into one request
+//! per logical operation so a stateless deployment doesn't need a local
+//! redb file at all. That needs an async object-store client (e.g. the
+//! `object_store` crate) bridged into `Store`'s synchronous, redb-shaped
+//! methods — a real design decision (block on a runtime handle per call? make
+//! `Store` async and thread that through every controller?) that deserves
+//! its own change, not a default picked to make this stub compile. Until
+//! that's settled, every method below fails with `StoreError::Storage`
+//! rather than silently behaving like an empty store.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{session::Session, settings::ObjectStoreSettings, tao_task::TaoTask, task_event::TaskEvent, user::User, user_edit_request::UserEditRequest};
+
+use super::data_context::{CascadeMode, TaskFilter, TaskHistory};
+use super::store::{Store, StoreError};
+
+pub struct ObjectStoreBackend {
+    #[allow(dead_code)]
+    settings: ObjectStoreSettings,
+}
+
+impl ObjectStoreBackend {
+    pub fn new(settings: ObjectStoreSettings) -> Self {
+        ObjectStoreBackend { settings }
+    }
+}
+
+fn not_yet_implemented() -> StoreError {
+    StoreError::Storage("ObjectStoreBackend is not yet implemented — see module docs".to_string())
+}
+
+impl Store for ObjectStoreBackend {
+    fn create_session(&self, _session: &Session) -> Result<(), StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn get_session(&self, _id: Uuid) -> Result<Option<Session>, StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn delete_session(&self, _id: Uuid) -> Result<bool, StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn put_avatar(&self, _user_id: Uuid, _png_bytes: &[u8]) -> Result<(), StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn get_avatar(&self, _user_id: Uuid) -> Result<Option<Vec<u8>>, StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn mark_user_has_avatar(&self, _id: Uuid) -> Result<bool, StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn create_user(&self, _user: &User) -> Result<(), StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn get_user(&self, _id: Uuid) -> Result<Option<User>, StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn get_user_by_username(&self, _username: &str) -> Result<Option<User>, StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn get_user_by_email(&self, _email: &str) -> Result<Option<User>, StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn list_users(&self) -> Result<Vec<User>, StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn delete_user(&self, _id: Uuid, _mode: CascadeMode) -> Result<bool, StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn edit_user(&self, _id: Uuid, _dto: UserEditRequest) -> Result<bool, StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn ensure_default_user(&self) -> Result<(), StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn create_task(&self, _task: &TaoTask, _actor: Uuid) -> Result<(), StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn get_task(&self, _id: Uuid) -> Result<Option<TaoTask>, StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn list_tasks(&self) -> Result<Vec<TaoTask>, StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn query_tasks(&self, _filter: TaskFilter) -> Result<Vec<TaoTask>, StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn get_usernames(&self, _ids: &[Uuid]) -> Result<HashMap<Uuid, String>, StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn update_task(&self, _task: &TaoTask, _actor: Uuid) -> Result<(), StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn delete_task(&self, _id: Uuid, _actor: Uuid) -> Result<bool, StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn task_history(&self, _id: Uuid) -> Result<TaskHistory, StoreError> {
+        Err(not_yet_implemented())
+    }
+
+    fn task_events_since(&self, _since: Option<DateTime<Utc>>) -> Result<Vec<TaskEvent>, StoreError> {
+        Err(not_yet_implemented())
+    }
+}
@@ -0,0 +1,249 @@
+//! Backend-agnostic storage trait.
+//!
+//! `AppState` used to hold a concrete `DataContext`, so exercising a
+//! controller in a test meant standing up a real redb file on disk, and a
+//! stateless deployment had no storage option that didn't write to local
+//! disk. `Store` captures the surface `AppState` and the controllers
+//! actually call; `RedbStore` (an alias for the existing `DataContext`) is
+//! the original implementation, and `InMemoryStore` backs fast controller
+//! tests (see its unit tests in `web_api::controllers::user_controller`).
+//! `ObjectStoreBackend` is scaffolding toward a future stateless deployment
+//! target — `build` below refuses to construct it rather than hand out a
+//! `Store` that fails every call.
+//!
+//! This schema has no `SERVICES_TABLE` or service id — `TaoTask::category`
+//! is a free-form string, not a foreign key — so there is no
+//! `create_service`/`list_services` here, the same gap already noted above
+//! `DataContext::create_task`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{session::Session, settings::{Settings, StorageBackend}, tao_task::TaoTask, task_event::TaskEvent, user::User, user_edit_request::UserEditRequest};
+
+use super::data_context::{CascadeMode, CreateUserError, DataContext, DbError, TaskFilter, TaskHistory};
+use super::in_memory_store::InMemoryStore;
+
+/// The original, redb-backed [`Store`] implementation. `DataContext` already
+/// implements `Store` below — this alias just names it by its role so
+/// callers choosing a backend can write `RedbStore::new` instead of reaching
+/// for `DataContext` by name.
+pub type RedbStore = DataContext;
+
+/// Everything `AppState` and the controllers need from persistence, with no
+/// reference to redb, a file path, or any other backend-specific detail.
+pub trait Store: Send + Sync {
+    // SESSIONS
+    fn create_session(&self, session: &Session) -> Result<(), StoreError>;
+    fn get_session(&self, id: Uuid) -> Result<Option<Session>, StoreError>;
+    fn delete_session(&self, id: Uuid) -> Result<bool, StoreError>;
+
+    // AVATARS
+    fn put_avatar(&self, user_id: Uuid, png_bytes: &[u8]) -> Result<(), StoreError>;
+    fn get_avatar(&self, user_id: Uuid) -> Result<Option<Vec<u8>>, StoreError>;
+    fn mark_user_has_avatar(&self, id: Uuid) -> Result<bool, StoreError>;
+
+    // USERS
+    fn create_user(&self, user: &User) -> Result<(), StoreError>;
+    fn get_user(&self, id: Uuid) -> Result<Option<User>, StoreError>;
+    fn get_user_by_username(&self, username: &str) -> Result<Option<User>, StoreError>;
+    fn get_user_by_email(&self, email: &str) -> Result<Option<User>, StoreError>;
+    fn list_users(&self) -> Result<Vec<User>, StoreError>;
+    fn delete_user(&self, id: Uuid, mode: CascadeMode) -> Result<bool, StoreError>;
+    fn edit_user(&self, id: Uuid, dto: UserEditRequest) -> Result<bool, StoreError>;
+    /// Seed a default admin user (from `Settings`) if no users exist yet.
+    fn ensure_default_user(&self) -> Result<(), StoreError>;
+
+    // TASKS
+    fn create_task(&self, task: &TaoTask, actor: Uuid) -> Result<(), StoreError>;
+    fn get_task(&self, id: Uuid) -> Result<Option<TaoTask>, StoreError>;
+    fn list_tasks(&self) -> Result<Vec<TaoTask>, StoreError>;
+    fn query_tasks(&self, filter: TaskFilter) -> Result<Vec<TaoTask>, StoreError>;
+    fn get_usernames(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, String>, StoreError>;
+    fn update_task(&self, task: &TaoTask, actor: Uuid) -> Result<(), StoreError>;
+    fn delete_task(&self, id: Uuid, actor: Uuid) -> Result<bool, StoreError>;
+    fn task_history(&self, id: Uuid) -> Result<TaskHistory, StoreError>;
+    fn task_events_since(&self, since: Option<DateTime<Utc>>) -> Result<Vec<TaskEvent>, StoreError>;
+}
+
+/// Build the `Store` named by `settings.storage_backend`. `redb_path` is
+/// only used for `StorageBackend::Redb` — it's kept a separate argument
+/// rather than folded into `Settings` because it's wherever the caller
+/// decided the save file should live (`Settings.redb_file_path` already
+/// serves that for `main`, but tests building an `InMemoryStore` shouldn't
+/// need to pick a path at all).
+pub fn build(settings: &Settings, redb_path: &str) -> Result<Arc<dyn Store>, StoreError> {
+    match settings.storage_backend {
+        StorageBackend::Redb => Ok(Arc::new(RedbStore::new(redb_path, settings)?)),
+        StorageBackend::InMemory => Ok(Arc::new(InMemoryStore::new())),
+        // `ObjectStoreBackend` is scaffolding, not a working backend — see its
+        // module docs. Rejecting the selection here, at boot, means a
+        // deployment that picks it finds out immediately and loudly instead
+        // of only on the first `Store` call some request happens to make.
+        StorageBackend::ObjectStore => Err(StoreError::Storage(
+            "storage_backend \"object_store\" is scaffolding only (see data_access::object_store_backend) \
+             and refuses to build rather than run unimplemented — pick \"redb\" or \"in_memory\" instead"
+                .to_string(),
+        )),
+    }
+}
+
+/// Error returned by every [`Store`] method. Unifies `DataContext`'s
+/// `redb::Error`/`CreateUserError`/`DbError` into one backend-agnostic type
+/// so `InMemoryStore` and `ObjectStoreBackend` aren't forced to manufacture
+/// fake `redb::Error`s just to satisfy the trait.
+#[derive(Debug)]
+pub enum StoreError {
+    /// A backend-specific failure, flattened to its message — the backend
+    /// that produced it (redb, an object store request, in-memory) doesn't
+    /// matter to a `Store` caller, only that the operation didn't happen.
+    Storage(String),
+    UsernameTaken,
+    EmailTaken,
+    /// `create_task`/`update_task` named a `created_by` or `assigned_to`
+    /// user id that doesn't resolve. Carries the field name.
+    DanglingReference(&'static str),
+    /// `delete_user` was called with `CascadeMode::Refuse` and the user
+    /// still has dependent tasks.
+    HasDependents(&'static str),
+}
+
+impl std::fmt::Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoreError::Storage(msg) => write!(f, "{msg}"),
+            StoreError::UsernameTaken => write!(f, "username already taken"),
+            StoreError::EmailTaken => write!(f, "email already taken"),
+            StoreError::DanglingReference(field) => write!(f, "{field} does not reference an existing user"),
+            StoreError::HasDependents(what) => write!(f, "refusing to delete: user still has {what}"),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<redb::Error> for StoreError {
+    fn from(e: redb::Error) -> Self {
+        StoreError::Storage(e.to_string())
+    }
+}
+
+impl From<CreateUserError> for StoreError {
+    fn from(e: CreateUserError) -> Self {
+        match e {
+            CreateUserError::Storage(e) => StoreError::Storage(e.to_string()),
+            CreateUserError::UsernameTaken => StoreError::UsernameTaken,
+            CreateUserError::EmailTaken => StoreError::EmailTaken,
+        }
+    }
+}
+
+impl From<DbError> for StoreError {
+    fn from(e: DbError) -> Self {
+        match e {
+            DbError::Storage(e) => StoreError::Storage(e.to_string()),
+            DbError::DanglingReference(field) => StoreError::DanglingReference(field),
+            DbError::HasDependents(what) => StoreError::HasDependents(what),
+            DbError::Decrypt => StoreError::Storage(
+                "failed to decrypt a stored value under the current data_encryption_key".to_string(),
+            ),
+        }
+    }
+}
+
+impl Store for DataContext {
+    fn create_session(&self, session: &Session) -> Result<(), StoreError> {
+        Ok(DataContext::create_session(self, session)?)
+    }
+
+    fn get_session(&self, id: Uuid) -> Result<Option<Session>, StoreError> {
+        Ok(DataContext::get_session(self, id)?)
+    }
+
+    fn delete_session(&self, id: Uuid) -> Result<bool, StoreError> {
+        Ok(DataContext::delete_session(self, id)?)
+    }
+
+    fn put_avatar(&self, user_id: Uuid, png_bytes: &[u8]) -> Result<(), StoreError> {
+        Ok(DataContext::put_avatar(self, user_id, png_bytes)?)
+    }
+
+    fn get_avatar(&self, user_id: Uuid) -> Result<Option<Vec<u8>>, StoreError> {
+        Ok(DataContext::get_avatar(self, user_id)?)
+    }
+
+    fn mark_user_has_avatar(&self, id: Uuid) -> Result<bool, StoreError> {
+        Ok(DataContext::mark_user_has_avatar(self, id)?)
+    }
+
+    fn create_user(&self, user: &User) -> Result<(), StoreError> {
+        Ok(DataContext::create_user(self, user)?)
+    }
+
+    fn get_user(&self, id: Uuid) -> Result<Option<User>, StoreError> {
+        Ok(DataContext::get_user(self, id)?)
+    }
+
+    fn get_user_by_username(&self, username: &str) -> Result<Option<User>, StoreError> {
+        Ok(DataContext::get_user_by_username(self, username)?)
+    }
+
+    fn get_user_by_email(&self, email: &str) -> Result<Option<User>, StoreError> {
+        Ok(DataContext::get_user_by_email(self, email)?)
+    }
+
+    fn list_users(&self) -> Result<Vec<User>, StoreError> {
+        Ok(DataContext::list_users(self)?)
+    }
+
+    fn delete_user(&self, id: Uuid, mode: CascadeMode) -> Result<bool, StoreError> {
+        Ok(DataContext::delete_user(self, id, mode)?)
+    }
+
+    fn edit_user(&self, id: Uuid, dto: UserEditRequest) -> Result<bool, StoreError> {
+        Ok(DataContext::edit_user(self, id, dto)?)
+    }
+
+    fn ensure_default_user(&self) -> Result<(), StoreError> {
+        DataContext::ensure_default_user(self).map_err(|e| StoreError::Storage(e.to_string()))
+    }
+
+    fn create_task(&self, task: &TaoTask, actor: Uuid) -> Result<(), StoreError> {
+        Ok(DataContext::create_task(self, task, actor)?)
+    }
+
+    fn get_task(&self, id: Uuid) -> Result<Option<TaoTask>, StoreError> {
+        Ok(DataContext::get_task(self, id)?)
+    }
+
+    fn list_tasks(&self) -> Result<Vec<TaoTask>, StoreError> {
+        Ok(DataContext::list_tasks(self)?)
+    }
+
+    fn query_tasks(&self, filter: TaskFilter) -> Result<Vec<TaoTask>, StoreError> {
+        Ok(DataContext::query_tasks(self, filter)?)
+    }
+
+    fn get_usernames(&self, ids: &[Uuid]) -> Result<HashMap<Uuid, String>, StoreError> {
+        Ok(DataContext::get_usernames(self, ids)?)
+    }
+
+    fn update_task(&self, task: &TaoTask, actor: Uuid) -> Result<(), StoreError> {
+        Ok(DataContext::update_task(self, task, actor)?)
+    }
+
+    fn delete_task(&self, id: Uuid, actor: Uuid) -> Result<bool, StoreError> {
+        Ok(DataContext::delete_task(self, id, actor)?)
+    }
+
+    fn task_history(&self, id: Uuid) -> Result<TaskHistory, StoreError> {
+        Ok(DataContext::task_history(self, id)?)
+    }
+
+    fn task_events_since(&self, since: Option<DateTime<Utc>>) -> Result<Vec<TaskEvent>, StoreError> {
+        Ok(DataContext::task_events_since(self, since)?)
+    }
+}
@@ -0,0 +1,191 @@
+//! Pluggable cross-instance event broadcast.
+//!
+//! The WS handler publishes packed event/batch frames to a channel and
+//! subscribes to that same channel to forward frames to its own sockets.
+//! A plain `tokio::sync::broadcast::Sender` only reaches sockets on this
+//! process, so two server instances behind a load balancer never see each
+//! other's mutations. `EventBus` abstracts "publish this frame, get a
+//! receiver that yields every frame published anywhere" behind a trait the
+//! same way `Store` abstracts persistence: an in-process backend for the
+//! zero-dependency single-node case, and a Redis pub/sub backend (behind
+//! the `redis` feature) for running more than one instance.
+//!
+//! Every frame a `RedisEventBus` publishes is tagged with a random
+//! per-instance origin id, so an instance that reads its own frame back
+//! off the Redis channel can recognize and skip it rather than
+//! double-delivering it to its own sockets.
+
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Tags which instance published a frame, so a `RedisEventBus` can skip
+/// re-delivering its own already-locally-broadcast frames to itself.
+pub type OriginId = Uuid;
+
+/// Everything the game loop needs from cross-instance broadcast. `World`
+/// and the WS handler only ever see `dyn EventBus`, selected once at boot
+/// the way `Store` is.
+pub trait EventBus: Send + Sync {
+    /// Publish a packed frame to every subscriber of this bus, on this
+    /// instance and (for a multi-instance backend) every other one.
+    fn publish(&self, frame: Vec<u8>);
+
+    /// A receiver that yields every frame published through this bus,
+    /// including ones published on another instance. Never yields a frame
+    /// this same subscription's own publisher already delivered.
+    fn subscribe(&self) -> broadcast::Receiver<Vec<u8>>;
+}
+
+/// Zero-dependency single-node backend: a bare in-process broadcast
+/// channel, exactly what `game_tx` was before this module existed.
+pub struct LocalEventBus {
+    tx: broadcast::Sender<Vec<u8>>,
+}
+
+impl LocalEventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity);
+        LocalEventBus { tx }
+    }
+}
+
+impl EventBus for LocalEventBus {
+    fn publish(&self, frame: Vec<u8>) {
+        let _ = self.tx.send(frame);
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<Vec<u8>> {
+        self.tx.subscribe()
+    }
+}
+
+/// Which backend to use, read from the environment at boot (mirrors
+/// `store::Backend`'s `TXXT_STORE_BACKEND` selection).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Local,
+    Redis,
+}
+
+#[derive(Debug)]
+pub enum EventBusError {
+    Redis(String),
+}
+
+impl std::fmt::Display for EventBusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EventBusError::Redis(e) => write!(f, "redis event bus: {e}"),
+        }
+    }
+}
+
+/// Open the backend selected by `backend`. `location` is unused for
+/// `Local`; for `Redis` it's the connection URL and `channel` is the
+/// pub/sub channel every instance publishes to and subscribes on.
+pub async fn open(backend: Backend, location: &str, channel: &str) -> Result<Box<dyn EventBus>, EventBusError> {
+    match backend {
+        Backend::Local => Ok(Box::new(LocalEventBus::new(256))),
+
+        #[cfg(feature = "redis")]
+        Backend::Redis => {
+            let bus = RedisEventBus::connect(location, channel).await?;
+            Ok(Box::new(bus))
+        }
+        #[cfg(not(feature = "redis"))]
+        Backend::Redis => Err(EventBusError::Redis(
+            "this binary was built without the \"redis\" feature".to_string(),
+        )),
+    }
+}
+
+#[cfg(feature = "redis")]
+pub use redis_backend::RedisEventBus;
+
+#[cfg(feature = "redis")]
+mod redis_backend {
+    use super::{EventBusError, EventBus, OriginId};
+    use futures_util::StreamExt;
+    use tokio::sync::broadcast;
+    use uuid::Uuid;
+
+    /// Multi-instance backend: frames are published to a Redis pub/sub
+    /// channel shared by every instance. Delivery to this instance's own
+    /// sockets happens immediately through `local` rather than waiting on
+    /// the round-trip through Redis; a background task only has to forward
+    /// *other* instances' frames in, and filters out this instance's own
+    /// echo by origin id.
+    pub struct RedisEventBus {
+        origin: OriginId,
+        channel: String,
+        local: broadcast::Sender<Vec<u8>>,
+        client: redis::Client,
+    }
+
+    impl RedisEventBus {
+        /// Connect to `url`, subscribe to `channel` on a background task
+        /// that forwards every frame published by another instance into
+        /// the local broadcast channel `subscribe()` hands out, and return
+        /// the bus ready for `publish`.
+        pub async fn connect(url: &str, channel: &str) -> Result<Self, EventBusError> {
+            let client = redis::Client::open(url).map_err(|e| EventBusError::Redis(e.to_string()))?;
+            let (local, _) = broadcast::channel(256);
+            let origin = Uuid::new_v4();
+
+            let mut pubsub = client
+                .get_async_pubsub()
+                .await
+                .map_err(|e| EventBusError::Redis(e.to_string()))?;
+            pubsub
+                .subscribe(channel)
+                .await
+                .map_err(|e| EventBusError::Redis(e.to_string()))?;
+
+            let forward_tx = local.clone();
+            let mut stream = pubsub.into_on_message();
+            tokio::spawn(async move {
+                while let Some(msg) = stream.next().await {
+                    let Ok(payload) = msg.get_payload::<Vec<u8>>() else { continue };
+                    if let Some((frame_origin, frame)) = split_origin(&payload) {
+                        if frame_origin != origin {
+                            let _ = forward_tx.send(frame.to_vec());
+                        }
+                    }
+                }
+            });
+
+            Ok(RedisEventBus { origin, channel: channel.to_string(), local, client })
+        }
+    }
+
+    impl EventBus for RedisEventBus {
+        fn publish(&self, frame: Vec<u8>) {
+            let _ = self.local.send(frame.clone());
+
+            let mut payload = self.origin.as_bytes().to_vec();
+            payload.extend_from_slice(&frame);
+            let channel = self.channel.clone();
+            let client = self.client.clone();
+            tokio::spawn(async move {
+                if let Ok(mut conn) = client.get_async_connection().await {
+                    let _: Result<(), redis::RedisError> =
+                        redis::AsyncCommands::publish(&mut conn, channel, payload).await;
+                }
+            });
+        }
+
+        fn subscribe(&self) -> broadcast::Receiver<Vec<u8>> {
+            self.local.subscribe()
+        }
+    }
+
+    /// Split a Redis pub/sub payload into the origin id it was tagged with
+    /// and the frame bytes that follow.
+    fn split_origin(payload: &[u8]) -> Option<(OriginId, &[u8])> {
+        if payload.len() < 16 {
+            return None;
+        }
+        let origin = Uuid::from_bytes(payload[..16].try_into().ok()?);
+        Some((origin, &payload[16..]))
+    }
+}
@@ -6,77 +6,197 @@
 //! - Client sends: packed binary commands (wire::unpack_command)
 //! - Server sends: packed binary snapshots + events (wire::pack_*)
 
-use crate::auth::SharedState;
+use crate::auth::{self, SharedState};
 use crate::wire;
+use crate::world::World;
 use axum::{
     extract::{
-        ws::{Message, WebSocket, WebSocketUpgrade},
+        ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade},
         State,
     },
+    http::HeaderMap,
     response::IntoResponse,
 };
 #[cfg(feature = "profile")]
 use std::time::Instant;
 use uuid::Uuid;
 
+/// Policy-violation close code (RFC 6455 §7.4.1), sent when the client never
+/// presented a valid bearer token.
+const CLOSE_POLICY_VIOLATION: u16 = 1008;
+
+/// "Service Restart" close code (IANA registry, not in the base RFC), sent
+/// on graceful shutdown so a client knows to reconnect immediately rather
+/// than back off the way it would for an unexpected drop.
+const CLOSE_SERVICE_RESTART: u16 = 1012;
+
+/// Removes this connection's entry from `AppState::client_revisions` when
+/// the connection's task ends, via `Drop`, so a disconnected client stops
+/// pinning the compaction boundary regardless of which `break`/`return` in
+/// `handle_socket` ends the loop.
+struct ConnectionRevisionGuard {
+    state: SharedState,
+    id: Uuid,
+}
+
+impl Drop for ConnectionRevisionGuard {
+    fn drop(&mut self) {
+        self.state.client_revisions.lock().unwrap().remove(&self.id);
+    }
+}
+
 // ── WS upgrade handler ────────────────────────────────────────
 
 pub async fn ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<SharedState>,
+    headers: HeaderMap,
 ) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_socket(socket, state))
+    // Browsers can't set an `Authorization` header on a WebSocket upgrade, so
+    // the bearer token rides in as the `Sec-WebSocket-Protocol` value instead
+    // — the one request header client-side WS APIs let callers control.
+    let token = headers
+        .get("sec-websocket-protocol")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+    ws.on_upgrade(move |socket| handle_socket(socket, state, token))
 }
 
 // ── Socket lifecycle ───────────────────────────────────────────
 
-async fn handle_socket(socket: WebSocket, state: SharedState) {
+async fn handle_socket(socket: WebSocket, state: SharedState, token: Option<String>) {
     let mut socket = socket;
     #[cfg(feature = "profile")]
     tracing::info!("ws client connected");
 
-    // Step 1: Subscribe to broadcast BEFORE reading snapshot.
+    // Step 0: Authenticate before doing anything else — same checks
+    // `auth_middleware` runs for REST, so a revoked session or an unknown
+    // user is rejected here exactly like it would be over HTTP.
+    let user_id = match token.as_deref().map(|t| auth::resolve_token_user(&state, t)) {
+        Some(Ok((user, _claims))) => user.id,
+        Some(Err(e)) => {
+            eprintln!("ws auth rejected: {e:?}");
+            let _ = socket
+                .send(Message::Close(Some(CloseFrame {
+                    code: CLOSE_POLICY_VIOLATION,
+                    reason: "invalid or expired token".into(),
+                })))
+                .await;
+            return;
+        }
+        None => {
+            let _ = socket
+                .send(Message::Close(Some(CloseFrame {
+                    code: CLOSE_POLICY_VIOLATION,
+                    reason: "missing bearer token".into(),
+                })))
+                .await;
+            return;
+        }
+    };
+
+    // Step 1: Client sends HELLO first; validate protocol version and record
+    // strides before doing anything else, then echo back the *negotiated*
+    // capabilities — the client's request ANDed with what this build
+    // actually honors — so a client never acts on a bit this server never
+    // wired up (see `wire::supported_capabilities`).
+    let negotiated_capabilities = match socket.recv().await {
+        Some(Ok(Message::Binary(data))) => match wire::unpack_hello(&data).and_then(|hello| {
+            wire::validate_hello(&hello)?;
+            Ok(hello)
+        }) {
+            Ok(hello) => {
+                let negotiated = hello.capabilities & wire::supported_capabilities();
+                let reply = wire::pack_hello(negotiated);
+                if socket.send(Message::Binary(reply.into())).await.is_err() {
+                    return;
+                }
+                negotiated
+            }
+            Err(e) => {
+                eprintln!("ws handshake rejected: {e}");
+                let _ = socket.send(Message::Binary(wire::pack_hello(0).into())).await;
+                return;
+            }
+        },
+        _ => return, // client must HELLO first
+    };
+
+    // Column-transposed/RLE snapshots when the client asked for them.
+    let compressed_snapshots = negotiated_capabilities & wire::capabilities::COMPRESSED_SNAPSHOTS != 0;
+
+    // A session key for authenticated framing, derived from the bearer token
+    // the client already authenticated with (so there's no extra exchange) —
+    // only when both sides negotiated `AUTH_FRAMES`.
+    #[cfg(feature = "wire-auth")]
+    let session_key: Option<[u8; 32]> = (negotiated_capabilities & wire::capabilities::AUTH_FRAMES != 0)
+        .then(|| derive_session_key(token.as_deref().expect("authenticated above, so token is Some")));
+    #[cfg(not(feature = "wire-auth"))]
+    let session_key: Option<[u8; 32]> = None;
+
+    // Step 2: Subscribe to broadcast BEFORE reading snapshot.
     // This ensures we don't miss events between snapshot and subscription.
-    let mut broadcast_rx = state.game_tx.subscribe();
+    let mut broadcast_rx = state.event_bus.subscribe();
+    let mut shutdown_rx = state.shutdown.subscribe();
 
-    // Step 2: Read-lock World, pack binary snapshot, send to this client.
+    // Step 3: Read-lock World, pack binary snapshot, send to this client.
     #[cfg(feature = "profile")]
     let snapshot_start = Instant::now();
     let snapshot_bytes = {
         let world = state.world.read().unwrap();
-        wire::pack_snapshot(&world)
+        snapshot_frame(&world, compressed_snapshots)
     };
     #[cfg(feature = "profile")]
     tracing::debug!(elapsed_us = snapshot_start.elapsed().as_micros() as u64, bytes = snapshot_bytes.len(), "snapshot packed");
 
     #[cfg(feature = "profile")]
     let snapshot_send_start = Instant::now();
+    let snapshot_bytes = tag_outgoing(snapshot_bytes, session_key);
     if socket.send(Message::Binary(snapshot_bytes.into())).await.is_err() {
         return; // client already gone
     }
     #[cfg(feature = "profile")]
     tracing::debug!(elapsed_us = snapshot_send_start.elapsed().as_micros() as u64, "snapshot sent");
 
-    // Dev mode: use first user in World, or Uuid::nil if none.
-    let user_id = {
+    // Track the revision this client has actually been sent, so a broadcast
+    // lag (the channel overwrote frames faster than we could forward them)
+    // can be healed by replaying the gap instead of silently skipping it.
+    let mut last_sent_revision = {
         let world = state.world.read().unwrap();
-        world.users.keys().next().copied().unwrap_or(Uuid::nil())
+        world.revision
     };
 
-    // Step 3: Forward broadcasts and process client commands in one loop.
+    // Publish it to `AppState::client_revisions` so the periodic compaction
+    // task never folds away a revision this still-connected client hasn't
+    // seen yet. `_revision_guard` removes the entry again on disconnect,
+    // however the loop below exits.
+    let connection_id = Uuid::new_v4();
+    state.client_revisions.lock().unwrap().insert(connection_id, last_sent_revision);
+    let _revision_guard = ConnectionRevisionGuard { state: state.clone(), id: connection_id };
+
+    // Step 4: Forward broadcasts and process client commands in one loop.
     loop {
         tokio::select! {
             recv = broadcast_rx.recv() => {
                 match recv {
                     Ok(bytes) => {
+                        last_sent_revision = last_sent_revision.max(wire::frame_revision(&bytes));
+                        state.client_revisions.lock().unwrap().insert(connection_id, last_sent_revision);
+                        let bytes = tag_outgoing(bytes, session_key);
                         if socket.send(Message::Binary(bytes.into())).await.is_err() {
                             break;
                         }
                     }
                     Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {
                         #[cfg(feature = "profile")]
-                        tracing::warn!("ws client lagged behind broadcast channel");
-                        continue;
+                        tracing::warn!("ws client lagged behind broadcast channel, catching up");
+                        let catch_up_bytes = catch_up(&state, last_sent_revision, compressed_snapshots);
+                        last_sent_revision = state.world.read().unwrap().revision;
+                        state.client_revisions.lock().unwrap().insert(connection_id, last_sent_revision);
+                        let catch_up_bytes = tag_outgoing(catch_up_bytes, session_key);
+                        if socket.send(Message::Binary(catch_up_bytes.into())).await.is_err() {
+                            break;
+                        }
                     }
                     Err(tokio::sync::broadcast::error::RecvError::Closed) => {
                         break;
@@ -84,10 +204,34 @@ async fn handle_socket(socket: WebSocket, state: SharedState) {
                 }
             }
 
+            _ = shutdown_rx.recv() => {
+                let _ = socket
+                    .send(Message::Close(Some(CloseFrame {
+                        code: CLOSE_SERVICE_RESTART,
+                        reason: "server shutting down, reconnect shortly".into(),
+                    })))
+                    .await;
+                break;
+            }
+
             msg = socket.recv() => {
                 match msg {
                     Some(Ok(Message::Binary(data))) => {
-                        handle_command(&state, &data, user_id);
+                        let frame = match strip_incoming(&data, session_key) {
+                            Ok(frame) => frame,
+                            Err(e) => {
+                                eprintln!("bad authenticated frame from client: {e}");
+                                continue;
+                            }
+                        };
+                        if let Some(reply) = handle_command(&state, frame, user_id, compressed_snapshots) {
+                            last_sent_revision = last_sent_revision.max(wire::frame_revision(&reply));
+                            state.client_revisions.lock().unwrap().insert(connection_id, last_sent_revision);
+                            let reply = tag_outgoing(reply, session_key);
+                            if socket.send(Message::Binary(reply.into())).await.is_err() {
+                                break;
+                            }
+                        }
                     }
                     Some(Ok(Message::Close(_))) => break,
                     Some(Ok(_)) => {}
@@ -101,11 +245,63 @@ async fn handle_socket(socket: WebSocket, state: SharedState) {
     tracing::info!("ws client disconnected");
 }
 
+// ── Authenticated framing / compressed snapshots ───────────────
+
+/// Derive this connection's frame-tagging key from the bearer token it
+/// already authenticated with, so negotiating `AUTH_FRAMES` needs no
+/// separate key exchange — the client has the same token.
+#[cfg(feature = "wire-auth")]
+fn derive_session_key(token: &str) -> [u8; 32] {
+    *blake3::hash(token.as_bytes()).as_bytes()
+}
+
+/// Append this connection's frame tag to an outgoing frame, or pass it
+/// through unchanged when `AUTH_FRAMES` wasn't negotiated (or this build
+/// doesn't have the `wire-auth` feature at all).
+fn tag_outgoing(bytes: Vec<u8>, session_key: Option<[u8; 32]>) -> Vec<u8> {
+    match session_key {
+        #[cfg(feature = "wire-auth")]
+        Some(key) => wire::auth::append_tag(&key, bytes),
+        _ => bytes,
+    }
+}
+
+/// Verify and strip this connection's frame tag off an incoming frame, or
+/// pass it through unchanged when `AUTH_FRAMES` wasn't negotiated.
+fn strip_incoming<'a>(data: &'a [u8], session_key: Option<[u8; 32]>) -> Result<&'a [u8], wire::WireError> {
+    match session_key {
+        #[cfg(feature = "wire-auth")]
+        Some(key) => wire::auth::verify_and_strip_tag(&key, data),
+        _ => Ok(data),
+    }
+}
+
+/// Pack a world snapshot, column-transposed and RLE-compressed when the
+/// connection negotiated `COMPRESSED_SNAPSHOTS`.
+fn snapshot_frame(world: &World, compressed: bool) -> Vec<u8> {
+    if compressed {
+        wire::pack_snapshot_compressed(world)
+    } else {
+        wire::pack_snapshot(world)
+    }
+}
+
 // ── Command processing ─────────────────────────────────────────
 
 /// Unpack a binary command, apply it to the World, flush to disk, broadcast the event.
 /// All synchronous under the write lock — microseconds at this scale.
-fn handle_command(state: &SharedState, data: &[u8], user_id: Uuid) {
+///
+/// Returns `Some(bytes)` when the caller owes this client a direct reply
+/// (currently only `CMD_RESYNC`'s response) rather than a broadcast.
+fn handle_command(state: &SharedState, data: &[u8], user_id: Uuid, compressed_snapshots: bool) -> Option<Vec<u8>> {
+    if data.first() == Some(&wire::msg::CMD_BATCH) {
+        handle_batch_command(state, &data[1..], user_id);
+        return None;
+    }
+    if data.first() == Some(&wire::msg::CMD_RESYNC) {
+        return Some(handle_resync(state, &data[1..], compressed_snapshots));
+    }
+
     #[cfg(feature = "profile")]
     let total_start = Instant::now();
 
@@ -118,7 +314,7 @@ fn handle_command(state: &SharedState, data: &[u8], user_id: Uuid) {
             eprintln!("bad command from client: {e}");
             #[cfg(feature = "profile")]
             tracing::warn!(error = %e, frame_len = data.len(), "bad command from client");
-            return;
+            return None;
         }
     };
 
@@ -133,6 +329,13 @@ fn handle_command(state: &SharedState, data: &[u8], user_id: Uuid) {
         #[cfg(feature = "profile")]
         tracing::debug!(elapsed_us = lock_start.elapsed().as_micros() as u64, "world write lock acquired");
 
+        if let Some(user) = world.users.get(&user_id) {
+            if !auth::command_permitted(user, &cmd) {
+                eprintln!("command rejected: user {user_id} lacks permission for {cmd:?}");
+                return None;
+            }
+        }
+
         #[cfg(feature = "profile")]
         let apply_start = Instant::now();
         match world.apply(cmd, user_id) {
@@ -143,7 +346,7 @@ fn handle_command(state: &SharedState, data: &[u8], user_id: Uuid) {
                 // Flush to save file (sync, fast)
                 #[cfg(feature = "profile")]
                 let flush_start = Instant::now();
-                if let Err(e) = state.save_file.flush(&world, &event) {
+                if let Err(e) = state.save_file.flush(&world, &event, user_id) {
                     eprintln!("save file flush failed: {e}");
                     #[cfg(feature = "profile")]
                     tracing::warn!(error = %e, "save file flush failed");
@@ -157,7 +360,7 @@ fn handle_command(state: &SharedState, data: &[u8], user_id: Uuid) {
                 eprintln!("command rejected: {e:?}");
                 #[cfg(feature = "profile")]
                 tracing::warn!(error = ?e, "command rejected");
-                return;
+                return None;
             }
         }
     };
@@ -171,7 +374,87 @@ fn handle_command(state: &SharedState, data: &[u8], user_id: Uuid) {
 
     #[cfg(feature = "profile")]
     let tx_start = Instant::now();
-    let _ = state.game_tx.send(bytes);
+    state.event_bus.publish(bytes);
     #[cfg(feature = "profile")]
     tracing::debug!(elapsed_us = tx_start.elapsed().as_micros() as u64, total_us = total_start.elapsed().as_micros() as u64, "command pipeline complete");
+    None
+}
+
+/// Handle a `CMD_RESYNC` request: reply with only the events the client
+/// missed if they're still within the retained log window, or a full
+/// snapshot if they've fallen too far behind to replay.
+fn handle_resync(state: &SharedState, data: &[u8], compressed_snapshots: bool) -> Vec<u8> {
+    let last_seen = match wire::unpack_resync(data) {
+        Ok(rev) => rev,
+        Err(e) => {
+            eprintln!("bad resync request from client: {e}");
+            return snapshot_frame(&state.world.read().unwrap(), compressed_snapshots);
+        }
+    };
+
+    catch_up(state, last_seen, compressed_snapshots)
+}
+
+/// Reply with everything that happened after `last_seen`: just the missed
+/// events if they're still within the retained log window, or a full
+/// snapshot if the caller's fallen too far behind to replay. Shared by
+/// `handle_resync` (client asked explicitly) and the broadcast-lag recovery
+/// in `handle_socket` (client fell behind the broadcast channel itself).
+fn catch_up(state: &SharedState, last_seen: u64, compressed_snapshots: bool) -> Vec<u8> {
+    let world = state.world.read().unwrap();
+    match world.events_since(last_seen) {
+        Ok(events) if !events.is_empty() => {
+            wire::pack_batch(&events.iter().map(|(_, event)| event.clone()).collect::<Vec<_>>())
+        }
+        Ok(_) => wire::pack_batch(&[]), // already up to date
+        Err(_needs_snapshot) => snapshot_frame(&world, compressed_snapshots), // too far behind — full refresh
+    }
+}
+
+/// Apply every command in a `CMD_BATCH` payload atomically under a single
+/// write-lock acquisition — via `World::apply_batch` — then broadcast the
+/// whole set of resulting events as one `BATCH` frame. If any command in
+/// the batch fails, none of them take effect and nothing is broadcast, so
+/// a multi-task drag-move never leaves clients with a half-applied state.
+fn handle_batch_command(state: &SharedState, data: &[u8], user_id: Uuid) {
+    let commands = match wire::unpack_batch(data) {
+        Ok(commands) => commands,
+        Err(e) => {
+            eprintln!("bad batch from client: {e}");
+            return;
+        }
+    };
+
+    if commands.is_empty() {
+        return;
+    }
+
+    let events = {
+        let mut world = state.world.write().unwrap();
+
+        if let Some(user) = world.users.get(&user_id) {
+            if let Some(cmd) = commands.iter().find(|cmd| !auth::command_permitted(user, cmd)) {
+                eprintln!("batch rejected: user {user_id} lacks permission for {cmd:?}");
+                return;
+            }
+        }
+
+        match world.apply_batch(commands, user_id) {
+            Ok(events) => {
+                for event in &events {
+                    if let Err(e) = state.save_file.flush(&world, event, user_id) {
+                        eprintln!("save file flush failed: {e}");
+                    }
+                }
+                events
+            }
+            Err(e) => {
+                eprintln!("batch rejected, rolled back: {e:?}");
+                return;
+            }
+        }
+    };
+
+    let bytes = wire::pack_batch(&events);
+    state.event_bus.publish(bytes);
 }
@@ -0,0 +1,99 @@
+//! LDAP authentication provider (feature = "ldap").
+//!
+//! Binds as the user, not a service account — the bind succeeding *is* the
+//! password check, so txxt never sees or stores the directory password. On
+//! a user's first successful bind their directory entry is mirrored into a
+//! local `User` record, the same way `register` creates one, so sessions,
+//! roles, and task ownership have a stable `Uuid` to hang off of regardless
+//! of which provider authenticated them.
+
+use crate::auth::AuthError;
+use crate::auth_provider::AuthProvider;
+use crate::config::LdapConfig;
+use crate::store::Store;
+use crate::world::{Role, User, World};
+use async_trait::async_trait;
+use ldap3::{LdapConnAsync, Scope, SearchEntry};
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+
+pub struct LdapProvider {
+    config: LdapConfig,
+    world: Arc<RwLock<World>>,
+    save_file: Arc<dyn Store>,
+}
+
+impl LdapProvider {
+    pub fn new(config: LdapConfig, world: Arc<RwLock<World>>, save_file: Arc<dyn Store>) -> Self {
+        LdapProvider { config, world, save_file }
+    }
+
+    fn bind_dn(&self, username: &str) -> String {
+        self.config.bind_dn_template.replace("{username}", username)
+    }
+
+    /// Look up (or create) the local `User` backing a directory account.
+    /// LDAP owns the password — this row only exists so the rest of txxt
+    /// can treat directory users identically to local ones.
+    fn provision(&self, username: &str) -> Result<User, AuthError> {
+        let mut world = self.world.write().unwrap();
+        if let Some(existing) = world.get_user_by_username(username) {
+            return Ok(existing.clone());
+        }
+
+        let user = User {
+            id: Uuid::new_v4(),
+            username: username.to_string(),
+            // Never checked — LDAP verifies the password, not this hash.
+            // A random value just satisfies `User`'s shape and guarantees
+            // local login can't be used to impersonate a directory account.
+            password_hash: Uuid::new_v4().to_string(),
+            role: Role::Member,
+        };
+
+        self.save_file
+            .save_user(&user)
+            .map_err(|e| AuthError::InternalError(anyhow::anyhow!(e.to_string())))?;
+        world.users.insert(user.id, user.clone());
+
+        Ok(user)
+    }
+}
+
+#[async_trait]
+impl AuthProvider for LdapProvider {
+    async fn authenticate(&self, username: &str, password: &str) -> Result<User, AuthError> {
+        let (conn, mut ldap) = LdapConnAsync::new(&self.config.url)
+            .await
+            .map_err(|e| AuthError::InternalError(anyhow::anyhow!(e)))?;
+        ldap3::drive!(conn);
+
+        let bind_dn = self.bind_dn(username);
+        ldap.simple_bind(&bind_dn, password)
+            .await
+            .and_then(|res| res.success())
+            .map_err(|_| AuthError::InvalidCredentials)?;
+
+        let (entries, _) = ldap
+            .search(
+                &self.config.base_dn,
+                Scope::Subtree,
+                &format!("(uid={username})"),
+                vec!["uid"],
+            )
+            .await
+            .and_then(|res| res.success())
+            .map_err(|e| AuthError::InternalError(anyhow::anyhow!(e)))?;
+
+        let _ = ldap.unbind().await;
+
+        let directory_username = entries
+            .into_iter()
+            .next()
+            .map(SearchEntry::construct)
+            .and_then(|entry| entry.attrs.get("uid").and_then(|v| v.first()).cloned())
+            .unwrap_or_else(|| username.to_string());
+
+        self.provision(&directory_username)
+    }
+}
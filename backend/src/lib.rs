@@ -3,10 +3,13 @@
 pub mod web_api {
     pub mod routes;
     pub mod controllers;
+    pub mod error;
+    pub mod openapi;
 }
 
 pub use web_api::routes::map_routes;
 pub use web_api::controllers::*;
+pub use web_api::error::ApiError;
 //---------------------------------------
 
 //---------------------------------------
@@ -22,11 +25,22 @@ pub use shared::dto::*;
 //---------------------------------------
 pub mod authentication {
     pub mod auth;
+    pub mod auth_provider;
 }
 //---------------------------------------
 
 //---------------------------------------
 pub mod data_access {
     pub mod data_context;
+    pub mod store;
+    pub mod in_memory_store;
+    pub mod object_store_backend;
+}
+//---------------------------------------
+
+//---------------------------------------
+pub mod security {
+    pub mod hash;
+    pub mod public_id;
 }
 //---------------------------------------
\ No newline at end of file
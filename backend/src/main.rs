@@ -1,19 +1,50 @@
 mod auth;
+mod auth_provider;
+mod config;
+mod event_bus;
 mod game;
+#[cfg(feature = "ldap")]
+mod ldap_provider;
+#[cfg(feature = "redb")]
 mod persist;
+#[cfg(feature = "sql")]
+mod sql_store;
+mod store;
+mod taskwarrior;
+mod watcher;
 mod wire;
 mod world;
 
-use auth::{AppState, SharedState};
+use auth::{AppState, Permissions, SharedState};
 use axum::{
+    middleware,
     routing::{get, post},
     Router,
 };
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::sync::broadcast;
+use std::time::Duration;
 use tower_http::services::ServeDir;
 
+/// How often the background compaction task folds the log into a fresh
+/// `World` snapshot. `World::compact` was implemented and unit-tested but
+/// never actually called outside of tests, so `trim_log`'s capacity-based
+/// eviction ran with no snapshot to fall back on before old entries aged
+/// out of the ring buffer. Five minutes is frequent enough that the log
+/// between snapshots stays small without fsyncing `World` state, which
+/// `compact` doesn't do at all — it only reshapes the in-memory log.
+const COMPACTION_INTERVAL: Duration = Duration::from_secs(300);
+
+// The `web_api` track (`DataContext`/`Store`/`map_routes`, built out across the
+// chunk8/chunk0/chunk2 series) lives in this crate's lib target but nothing
+// here ever called into it, so none of its encryption-at-rest, referential
+// integrity, or session auth ever ran against real traffic. It's mounted
+// below under its own `AppState`, alongside (not instead of) the `World`/
+// `SaveFile` path above, since the two tracks model the data differently
+// (a `World` event log vs. `DataContext`'s user/task tables) and reconciling
+// them into one is a bigger change than "make the code that exists run".
+use backend::app_state::AppState as WebApiAppState;
+
 #[cfg(feature = "profile")]
 fn init_tracing() {
     use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
@@ -41,13 +72,33 @@ fn init_tracing() {}
 async fn main() {
     init_tracing();
 
+    // ── Load configuration ──────────────────────────────────────
+    let settings = config::Settings::load().expect("Failed to load configuration");
+    let access_token_ttl = chrono::Duration::from_std(
+        settings.access_token_ttl().expect("Invalid access_token_ttl in configuration"),
+    )
+    .expect("access_token_ttl out of range");
+    let refresh_token_ttl = chrono::Duration::from_std(
+        settings.refresh_token_ttl().expect("Invalid refresh_token_ttl in configuration"),
+    )
+    .expect("refresh_token_ttl out of range");
+
+    // ── Select storage backend ─────────────────────────────────
+    // TXXT_STORE_BACKEND selects "redb" (default, a local file — set
+    // TXXT_SAVE_FILE to its path) or "sql" (a shared Postgres — set
+    // TXXT_SAVE_FILE to its connection string).
+    let backend = match std::env::var("TXXT_STORE_BACKEND").as_deref() {
+        Ok("sql") => store::Backend::Sql,
+        Ok("redb") | Err(_) => store::Backend::Redb,
+        Ok(other) => panic!("unknown TXXT_STORE_BACKEND {other:?} (expected \"redb\" or \"sql\")"),
+    };
     let save_path = std::env::var("TXXT_SAVE_FILE").unwrap_or_else(|_| "tasks.redb".to_string());
 
     // ── Boot the World ─────────────────────────────────────────
-    let save_file = persist::SaveFile::open(&save_path)
-        .expect("Failed to open save file");
+    let save_file = store::open(backend, &save_path)
+        .expect("Failed to open storage backend");
     #[cfg(feature = "profile")]
-    tracing::info!(save_path = %save_path, "opened save file");
+    tracing::info!(save_path = %save_path, backend = ?backend, "opened storage backend");
 
     let mut world = save_file.load_world()
         .expect("Failed to load world from save file");
@@ -77,16 +128,97 @@ async fn main() {
         world.revision,
     );
 
-    // ── Broadcast channel ──────────────────────────────────────
-    let (game_tx, _) = broadcast::channel::<Vec<u8>>(256);
+    // ── Event bus ────────────────────────────────────────────────
+    // TXXT_EVENT_BUS selects "local" (default, in-process broadcast only)
+    // or "redis" (shared pub/sub, for running more than one instance — set
+    // TXXT_REDIS_URL and optionally TXXT_REDIS_CHANNEL).
+    let event_bus_backend = match std::env::var("TXXT_EVENT_BUS").as_deref() {
+        Ok("redis") => event_bus::Backend::Redis,
+        Ok("local") | Err(_) => event_bus::Backend::Local,
+        Ok(other) => panic!("unknown TXXT_EVENT_BUS {other:?} (expected \"local\" or \"redis\")"),
+    };
+    let redis_url = std::env::var("TXXT_REDIS_URL").unwrap_or_default();
+    let redis_channel = std::env::var("TXXT_REDIS_CHANNEL").unwrap_or_else(|_| "txxt:events".to_string());
+    let event_bus: Arc<dyn event_bus::EventBus> = Arc::from(
+        event_bus::open(event_bus_backend, &redis_url, &redis_channel)
+            .await
+            .expect("Failed to open event bus backend"),
+    );
+
+    // ── JWT signing keyring ─────────────────────────────────────
+    let mut jwt_keyring = save_file
+        .load_jwt_keyring()
+        .expect("Failed to load JWT keyring");
+    if !jwt_keyring.iter().any(|key| !key.retired) {
+        let key = save_file
+            .rotate_jwt_key()
+            .expect("Failed to bootstrap JWT signing key");
+        jwt_keyring.push(key);
+    }
+
+    // ── web_api state (DataContext/Store track — see the comment on the
+    // `WebApiAppState` import above) ────────────────────────────
+    let web_api_settings = backend::settings::Settings::from_env_only()
+        .expect("Failed to load web API configuration (set TXXT_JWT_SECRET at minimum)");
+    let web_api_store = backend::data_access::store::build(&web_api_settings, &web_api_settings.redb_file_path)
+        .expect("Failed to open web API storage backend");
+    web_api_store
+        .ensure_default_user()
+        .expect("Failed to seed web API default user");
+    let web_api_auth_providers = backend::authentication::auth_provider::build(&web_api_settings, web_api_store.clone());
+    let (web_api_shutdown, _) = tokio::sync::broadcast::channel(1);
+    let (web_api_ws_broadcast, _) = tokio::sync::broadcast::channel(256);
+    let web_api_state = Arc::new(WebApiAppState {
+        store: web_api_store,
+        ws_broadcast: web_api_ws_broadcast,
+        shutdown: web_api_shutdown,
+        auth_providers: web_api_auth_providers,
+    });
 
     // ── Shared state ───────────────────────────────────────────
+    let world = Arc::new(std::sync::RwLock::new(world));
+    let save_file: Arc<dyn store::Store> = Arc::from(save_file);
+
+    let providers = auth_provider::build(
+        &settings.auth.providers,
+        world.clone(),
+        save_file.clone(),
+        settings.auth.ldap.as_ref(),
+    );
+
+    let (shutdown, _) = tokio::sync::broadcast::channel(1);
+
     let state: SharedState = Arc::new(AppState {
-        world: std::sync::RwLock::new(world),
+        world,
         save_file,
-        game_tx,
+        event_bus,
+        jwt_keyring: std::sync::RwLock::new(jwt_keyring),
+        access_token_ttl,
+        refresh_token_ttl,
+        providers,
+        shutdown,
+        client_revisions: std::sync::Mutex::new(std::collections::HashMap::new()),
     });
 
+    // ── Periodic log compaction ─────────────────────────────────
+    // Bound by the slowest connected client rather than the current
+    // revision, so a merely-lagging (but still connected) client's next
+    // `Lagged` broadcast error can still heal via `catch_up`'s incremental
+    // replay instead of being forced into a full snapshot resync — see
+    // `AppState::min_client_revision`.
+    {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(COMPACTION_INTERVAL);
+            loop {
+                ticker.tick().await;
+                let mut world = state.world.write().unwrap();
+                let up_to = state.min_client_revision().unwrap_or(world.revision);
+                world.compact(up_to);
+            }
+        });
+    }
+
     // ── Resolve IRONCLAD path relative to Cargo.toml ────────────
     let ironclad_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
         .join("../frontend");
@@ -94,16 +226,36 @@ async fn main() {
     #[cfg(feature = "profile")]
     tracing::info!(static_dir = %ironclad_dir.display(), "frontend directory resolved");
 
+    // ── Admin routes ─────────────────────────────────────────────
+    // The only route in this binary actually wired behind `require_permission`
+    // — every task mutation instead enforces RBAC inline in `game.rs`, since
+    // they multiplex through one WebSocket rather than one REST route each.
+    // `require_permission` runs *after* `auth_middleware` in the layer stack
+    // below, since route_layer wraps outside-in and auth_middleware is what
+    // populates the `User` extension `require_permission` reads.
+    let admin_routes = Router::new()
+        .route("/api/auth/rotate_key", post(auth::rotate_key))
+        .route_layer(middleware::from_fn(auth::require_permission(Permissions::MANAGE_USERS)))
+        .route_layer(middleware::from_fn_with_state(state.clone(), auth::auth_middleware));
+
     // ── Router ─────────────────────────────────────────────────
     let app = Router::new()
         // Auth (REST, JSON — called once per session)
+        .route("/api/auth/register", post(auth::register))
         .route("/api/auth/login", post(auth::login))
         .route("/api/auth/logout", post(auth::logout))
+        .route("/api/auth/refresh", post(auth::refresh))
+        .route("/api/auth/introspect", post(auth::introspect))
         // Game WebSocket (binary protocol — the real data path)
         .route("/api/game", get(game::ws_handler))
+        .merge(admin_routes)
+        .with_state(state.clone())
+        // web_api track — /api/authentication/*, /api/user/*, /api/tasks*,
+        // /api/health/check_status, /api-docs/*. Its own `AppState`, so it
+        // merges in already-stated rather than sharing `.with_state` above.
+        .merge(backend::map_routes(web_api_state.clone()))
         // Static files — serve IRONCLAD renderer from txxt2 repo
-        .fallback_service(ServeDir::new(&ironclad_dir).append_index_html_on_directories(true))
-        .with_state(state);
+        .fallback_service(ServeDir::new(&ironclad_dir).append_index_html_on_directories(true));
 
     // ── Start ──────────────────────────────────────────────────
     let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
@@ -114,5 +266,33 @@ async fn main() {
     tracing::info!("server start listening");
 
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown_signal(state, web_api_state))
+        .await
+        .unwrap();
+}
+
+/// Wait for SIGTERM (or Ctrl+C during local dev) and tell every connected
+/// WebSocket (both the game socket and the web_api task-events socket) to
+/// start draining before `axum::serve` stops accepting new connections and
+/// awaits the in-flight ones.
+async fn shutdown_signal(state: SharedState, web_api_state: Arc<WebApiAppState>) {
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate => {}
+    }
+
+    println!("Shutdown signal received, draining connections...");
+    state.begin_shutdown();
+    web_api_state.begin_shutdown();
 }
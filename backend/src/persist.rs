@@ -3,11 +3,12 @@
 //! redb is a save file: loaded on boot, flushed on every mutation.
 //! Never queried at runtime — World is the runtime truth.
 
-use crate::world::{Event, Service, Task, User, World};
-use redb::{Database, ReadableTable, TableDefinition};
-use std::sync::Arc;
-#[cfg(feature = "profile")]
-use std::time::Instant;
+use crate::world::{Event, JwtKey, RefreshToken, Service, Session, Task, TaskStatus, User, World};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use redb::{Database, ReadableTable, TableDefinition, WriteTransaction};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use uuid::Uuid;
 
 // New tables — separate from the old db.rs tables so both coexist during transition.
@@ -15,17 +16,88 @@ const WORLD_TASKS: TableDefinition<&[u8], &[u8]> = TableDefinition::new("world_t
 const WORLD_USERS: TableDefinition<&[u8], &[u8]> = TableDefinition::new("world_users");
 const WORLD_SERVICES: TableDefinition<&[u8], &[u8]> = TableDefinition::new("world_services");
 const WORLD_META: TableDefinition<&str, &[u8]> = TableDefinition::new("world_meta");
+// Append-only event journal, keyed by the 8-byte big-endian revision so a
+// range scan over keys visits events in revision order.
+const WORLD_EVENTS: TableDefinition<&[u8], &[u8]> = TableDefinition::new("world_events");
+// Keyed by session jti. Not part of World (World only holds entities a
+// client's snapshot needs); sessions are an auth-layer concern that happens
+// to live in the same save file.
+const WORLD_SESSIONS: TableDefinition<&[u8], &[u8]> = TableDefinition::new("world_sessions");
+// Keyed by the SHA-256 hash of the opaque refresh token the client holds.
+// Rotated (deleted + replaced) on every use, so a row existing at all means
+// that token hasn't been redeemed yet.
+const WORLD_REFRESH_TOKENS: TableDefinition<&[u8], &[u8]> = TableDefinition::new("world_refresh_tokens");
+
+// The old db.rs / data_context tables this save file used to share a file with,
+// kept here only so the first migration can read whatever they left behind.
+const LEGACY_USERS: TableDefinition<&[u8], &[u8]> = TableDefinition::new("users");
+const LEGACY_TASKS: TableDefinition<&[u8], &[u8]> = TableDefinition::new("tasks");
+const LEGACY_SERVICES: TableDefinition<&[u8], &[u8]> = TableDefinition::new("services");
+
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+const CURRENT_SCHEMA_VERSION: u32 = 1;
+const JWT_KEYRING_META_KEY: &str = "jwt_keyring";
+
+/// Reserved service that migrated legacy tasks are filed under: the old task
+/// model had no concept of a service, so there's nothing truer to map to.
+const MIGRATED_SERVICE_ID: Uuid = Uuid::from_u128(1);
+
+/// One row of the event journal: who did it and when, alongside the event
+/// itself. Stored postcard-encoded under the revision's big-endian bytes.
+#[derive(Serialize, Deserialize)]
+struct JournalEntry {
+    user_id: Uuid,
+    /// Unix timestamp (seconds) at the moment this event was flushed.
+    timestamp: i64,
+    event: Event,
+}
+
+/// How durably `flush` commits writes.
+///
+/// `Grouped` trades a durability window for throughput: writes still commit
+/// (and are immediately visible to `load_world`) on every `flush`, but most
+/// of those commits skip the fsync via `redb::Durability::None`, only
+/// paying for one every `max_events` commits or `max_latency`, whichever
+/// comes first. A crash between checkpoints can roll back up to that many
+/// revisions (or that much time) of writes — the in-memory `World` may be
+/// ahead of what's actually on disk until the next checkpoint.
+#[derive(Debug, Clone, Copy)]
+pub enum Durability {
+    /// fsync on every flush. The default, and the only safe choice unless
+    /// you've decided the durability window below is acceptable.
+    Immediate,
+    Grouped {
+        max_events: usize,
+        max_latency: Duration,
+    },
+}
+
+/// Tracks how far the current durability window has gotten.
+struct GroupState {
+    events_since_checkpoint: usize,
+    last_checkpoint: Instant,
+}
 
 /// Thin handle to the redb file. Cloneable (Arc inside).
 #[derive(Clone)]
 pub struct SaveFile {
     db: Arc<Database>,
+    durability: Durability,
+    group_state: Arc<Mutex<GroupState>>,
 }
 
 impl SaveFile {
-    /// Open (or create) the save file at the given path.
-    /// Creates tables if they don't exist.
+    /// Open (or create) the save file at the given path with the default
+    /// `Durability::Immediate` policy (fsync on every flush).
     pub fn open(path: &str) -> Result<Self, SaveFileError> {
+        Self::open_with_durability(path, Durability::Immediate)
+    }
+
+    /// Open (or create) the save file with a chosen durability policy.
+    /// Creates tables if they don't exist, then brings the schema up to date.
+    /// `Durability::Grouped` also spawns a background task that checkpoints
+    /// every `max_latency` even if `max_events` never accumulates.
+    pub fn open_with_durability(path: &str, durability: Durability) -> Result<Self, SaveFileError> {
         let db = Database::create(path)?;
 
         // Ensure tables exist
@@ -35,14 +107,126 @@ impl SaveFile {
             let _ = txn.open_table(WORLD_USERS)?;
             let _ = txn.open_table(WORLD_SERVICES)?;
             let _ = txn.open_table(WORLD_META)?;
+            let _ = txn.open_table(WORLD_EVENTS)?;
+            let _ = txn.open_table(WORLD_SESSIONS)?;
+            let _ = txn.open_table(WORLD_REFRESH_TOKENS)?;
         }
         txn.commit()?;
 
-        Ok(SaveFile { db: Arc::new(db) })
+        Self::run_migrations(&db)?;
+
+        let save_file = SaveFile {
+            db: Arc::new(db),
+            durability,
+            group_state: Arc::new(Mutex::new(GroupState {
+                events_since_checkpoint: 0,
+                last_checkpoint: Instant::now(),
+            })),
+        };
+
+        if let Durability::Grouped { max_latency, .. } = durability {
+            let background = save_file.clone();
+            tokio::spawn(async move {
+                let mut ticker = tokio::time::interval(max_latency);
+                loop {
+                    ticker.tick().await;
+                    if let Err(e) = background.sync() {
+                        eprintln!("periodic save-file checkpoint failed: {e}");
+                    }
+                }
+            });
+        }
+
+        Ok(save_file)
+    }
+
+    /// Force a durable checkpoint right now, fsyncing anything committed
+    /// with `Durability::None` since the last one. Call this on graceful
+    /// shutdown so a clean exit never loses the buffered window.
+    pub fn sync(&self) -> Result<(), SaveFileError> {
+        let mut txn = self.db.begin_write()?;
+        txn.set_durability(redb::Durability::Immediate);
+        txn.commit()?;
+
+        if matches!(self.durability, Durability::Grouped { .. }) {
+            let mut state = self.group_state.lock().unwrap();
+            state.events_since_checkpoint = 0;
+            state.last_checkpoint = Instant::now();
+        }
+        Ok(())
+    }
+
+    /// Decide the redb durability for the next commit, advancing the
+    /// grouped-mode checkpoint window as a side effect.
+    fn next_commit_durability(&self) -> redb::Durability {
+        match self.durability {
+            Durability::Immediate => redb::Durability::Immediate,
+            Durability::Grouped { max_events, max_latency } => {
+                let mut state = self.group_state.lock().unwrap();
+                state.events_since_checkpoint += 1;
+                if state.events_since_checkpoint >= max_events
+                    || state.last_checkpoint.elapsed() >= max_latency
+                {
+                    state.events_since_checkpoint = 0;
+                    state.last_checkpoint = Instant::now();
+                    redb::Durability::Immediate
+                } else {
+                    redb::Durability::None
+                }
+            }
+        }
+    }
+
+    /// Apply every pending migration in order, one transaction per step so a
+    /// crash mid-run leaves the version pointing at the last completed step
+    /// rather than a half-applied one.
+    fn run_migrations(db: &Database) -> Result<(), SaveFileError> {
+        loop {
+            let version = Self::read_schema_version(db)?;
+            if version > CURRENT_SCHEMA_VERSION {
+                return Err(SaveFileError::UnsupportedVersion(version));
+            }
+            if version == CURRENT_SCHEMA_VERSION {
+                return Ok(());
+            }
+
+            let migration = MIGRATIONS
+                .iter()
+                .find(|m| m.from_version == version)
+                .unwrap_or_else(|| panic!("no migration registered for schema version {version}"));
+
+            let txn = db.begin_write()?;
+            (migration.run)(&txn)?;
+            {
+                let mut meta = txn.open_table(WORLD_META)?;
+                meta.insert(SCHEMA_VERSION_KEY, (version + 1).to_le_bytes().as_slice())?;
+            }
+            txn.commit()?;
+        }
+    }
+
+    fn read_schema_version(db: &Database) -> Result<u32, SaveFileError> {
+        let txn = db.begin_read()?;
+        let meta = txn.open_table(WORLD_META)?;
+        match meta.get(SCHEMA_VERSION_KEY)? {
+            Some(v) => {
+                let bytes = v.value();
+                if bytes.len() != 4 {
+                    return Ok(0);
+                }
+                Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+            }
+            None => Ok(0),
+        }
     }
 
     /// Load the entire World from disk. Called once at boot.
     pub fn load_world(&self) -> Result<World, SaveFileError> {
+        let version = Self::read_schema_version(&self.db)?;
+        if version > CURRENT_SCHEMA_VERSION {
+            return Err(SaveFileError::UnsupportedVersion(version));
+        }
+
         let mut world = World::new();
         let txn = self.db.begin_read()?;
 
@@ -82,20 +266,24 @@ impl SaveFile {
             }
         }
 
+        world.rebuild_indexes();
         Ok(world)
     }
 
     /// Flush a single event to disk. Called after every World::apply().
-    /// Writes the affected entity + updated revision in one transaction.
-    pub fn flush(&self, world: &World, event: &Event) -> Result<(), SaveFileError> {
+    /// Writes the affected entity + updated revision + journal entry in one
+    /// transaction, so the journal and the materialized snapshot never drift.
+    pub fn flush(&self, world: &World, event: &Event, user_id: Uuid) -> Result<(), SaveFileError> {
         #[cfg(feature = "profile")]
         let total_start = Instant::now();
-        let txn = self.db.begin_write()?;
+        let mut txn = self.db.begin_write()?;
+        txn.set_durability(self.next_commit_durability());
         {
             #[cfg(feature = "profile")]
             let table_start = Instant::now();
             let mut tasks = txn.open_table(WORLD_TASKS)?;
             let mut meta = txn.open_table(WORLD_META)?;
+            let mut events = txn.open_table(WORLD_EVENTS)?;
             #[cfg(feature = "profile")]
             tracing::debug!(elapsed_us = table_start.elapsed().as_micros() as u64, "flush opened tables");
 
@@ -111,7 +299,9 @@ impl SaveFile {
                 Event::TaskScheduled { task_id, .. }
                 | Event::TaskMoved { task_id, .. }
                 | Event::TaskUnscheduled { task_id, .. }
-                | Event::TaskCompleted { task_id, .. } => {
+                | Event::TaskCompleted { task_id, .. }
+                | Event::TaskFailed { task_id, .. }
+                | Event::TaskRetried { task_id, .. } => {
                     // Look up the current state in World and write the whole entity
                     let task = &world.tasks[task_id];
                     let bytes = postcard::to_allocvec(task)
@@ -122,10 +312,27 @@ impl SaveFile {
                 Event::TaskDeleted { task_id, .. } => {
                     tasks.remove(task_id.as_bytes().as_slice())?;
                 }
+
+                Event::DependencyAdded { task_id, .. } | Event::DependencyRemoved { task_id, .. } => {
+                    let task = &world.tasks[task_id];
+                    let bytes = postcard::to_allocvec(task)
+                        .map_err(|e| SaveFileError::Encode(e.to_string()))?;
+                    tasks.insert(task_id.as_bytes().as_slice(), bytes.as_slice())?;
+                }
             }
 
             // Always update revision
             meta.insert("revision", world.revision.to_le_bytes().as_slice())?;
+
+            // Append to the journal
+            let entry = JournalEntry {
+                user_id,
+                timestamp: chrono::Utc::now().timestamp(),
+                event: event.clone(),
+            };
+            let entry_bytes = postcard::to_allocvec(&entry)
+                .map_err(|e| SaveFileError::Encode(e.to_string()))?;
+            events.insert(world.revision.to_be_bytes().as_slice(), entry_bytes.as_slice())?;
             #[cfg(feature = "profile")]
             tracing::debug!(elapsed_us = write_start.elapsed().as_micros() as u64, "flush wrote rows and revision");
         }
@@ -137,6 +344,72 @@ impl SaveFile {
         Ok(())
     }
 
+    /// Reconstruct World state as of a past `revision` by replaying the
+    /// event journal from scratch through `World::apply_event` — a pure
+    /// fold with no validation and no side effects, unlike `World::apply`.
+    ///
+    /// Users and services aren't event-sourced (they're written directly by
+    /// `save_user`/`save_service`), so only `tasks` and `revision` reflect
+    /// the requested point in time; `users`/`services` reflect the current
+    /// on-disk state.
+    pub fn load_world_at(&self, revision: u64) -> Result<World, SaveFileError> {
+        let mut world = World::new();
+        let txn = self.db.begin_read()?;
+
+        let users_table = txn.open_table(WORLD_USERS)?;
+        for entry in users_table.iter()? {
+            let (_, value) = entry?;
+            let user: User = postcard::from_bytes(value.value())
+                .map_err(|e| SaveFileError::Decode(e.to_string()))?;
+            world.users.insert(user.id, user);
+        }
+
+        let services_table = txn.open_table(WORLD_SERVICES)?;
+        for entry in services_table.iter()? {
+            let (_, value) = entry?;
+            let service: Service = postcard::from_bytes(value.value())
+                .map_err(|e| SaveFileError::Decode(e.to_string()))?;
+            world.services.insert(service.id, service);
+        }
+
+        let events_table = txn.open_table(WORLD_EVENTS)?;
+        let lo = 1u64.to_be_bytes();
+        let hi = revision.to_be_bytes();
+        for entry in events_table.range(lo.as_slice()..=hi.as_slice())? {
+            let (_, value) = entry?;
+            let journal: JournalEntry = postcard::from_bytes(value.value())
+                .map_err(|e| SaveFileError::Decode(e.to_string()))?;
+            world.apply_event(&journal.event);
+        }
+
+        world.rebuild_indexes();
+        Ok(world)
+    }
+
+    /// Decode journal entries whose revision falls in `range`, for an admin
+    /// audit view. Returns `(revision, user_id, timestamp, event)` tuples in
+    /// revision order.
+    pub fn replay_audit(
+        &self,
+        range: std::ops::RangeInclusive<u64>,
+    ) -> Result<Vec<(u64, Uuid, i64, Event)>, SaveFileError> {
+        let txn = self.db.begin_read()?;
+        let events_table = txn.open_table(WORLD_EVENTS)?;
+
+        let lo = range.start().to_be_bytes();
+        let hi = range.end().to_be_bytes();
+        let mut out = Vec::new();
+        for entry in events_table.range(lo.as_slice()..=hi.as_slice())? {
+            let (key, value) = entry?;
+            let revision = u64::from_be_bytes(key.value().try_into().unwrap());
+            let journal: JournalEntry = postcard::from_bytes(value.value())
+                .map_err(|e| SaveFileError::Decode(e.to_string()))?;
+            out.push((revision, journal.user_id, journal.timestamp, journal.event));
+        }
+
+        Ok(out)
+    }
+
     /// Write a user to the save file (for seeding / account creation).
     pub fn save_user(&self, user: &User) -> Result<(), SaveFileError> {
         let txn = self.db.begin_write()?;
@@ -169,22 +442,7 @@ impl SaveFile {
             return Ok(0);
         }
 
-        let defaults = [
-            ("6b3c18d4-2a1d-4f2b-9d4c-0a0c3f0f2f10", "Billing Portal"),
-            ("a8c2f1f0-8b8f-4a62-9d3a-8c1d7b4c2a01", "Customer Support"),
-            ("2e6a7c11-8c39-4d5f-9a0e-6e1a4c7f3b22", "Data Warehouse"),
-            ("d0b74f7e-3c2a-4a58-8b21-5e9d2a1c4f33", "Fraud Detection"),
-            ("f2a1c3b4-5d6e-4f70-8123-4567890abcde", "Identity"),
-            ("0c1d2e3f-4a5b-6c7d-8e9f-0123456789ab", "Internal Tools"),
-            ("11121314-1516-1718-191a-1b1c1d1e1f20", "Mobile App"),
-            ("21222324-2526-2728-292a-2b2c2d2e2f30", "Payments"),
-            ("31323334-3536-3738-393a-3b3c3d3e3f40", "Reporting"),
-            ("41424344-4546-4748-494a-4b4c4d4e4f50", "Search"),
-            ("51525354-5556-5758-595a-5b5c5d5e5f60", "Shipping"),
-            ("61626364-6566-6768-696a-6b6c6d6e6f70", "Web App"),
-        ];
-
-        for (id_str, name) in defaults {
+        for (id_str, name) in crate::store::DEFAULT_SERVICES {
             let service = Service {
                 id: Uuid::parse_str(id_str).unwrap(),
                 name: name.to_string(),
@@ -193,7 +451,7 @@ impl SaveFile {
             world.services.insert(service.id, service);
         }
 
-        Ok(defaults.len())
+        Ok(crate::store::DEFAULT_SERVICES.len())
     }
 
     /// Seed default admin user if no users exist. Returns true if created.
@@ -217,12 +475,353 @@ impl SaveFile {
             id: Uuid::new_v4(),
             username: "admin".to_string(),
             password_hash,
+            role: crate::world::Role::Admin,
         };
 
         self.save_user(&user)?;
         world.users.insert(user.id, user);
         Ok(true)
     }
+
+    /// Persist a newly issued session.
+    pub fn save_session(&self, session: &Session) -> Result<(), SaveFileError> {
+        let txn = self.db.begin_write()?;
+        {
+            let mut sessions = txn.open_table(WORLD_SESSIONS)?;
+            let bytes = postcard::to_allocvec(session)
+                .map_err(|e| SaveFileError::Encode(e.to_string()))?;
+            sessions.insert(session.jti.as_bytes().as_slice(), bytes.as_slice())?;
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Look up a session by its token's `jti`.
+    pub fn get_session(&self, jti: Uuid) -> Result<Option<Session>, SaveFileError> {
+        let txn = self.db.begin_read()?;
+        let sessions = txn.open_table(WORLD_SESSIONS)?;
+        match sessions.get(jti.as_bytes().as_slice())? {
+            Some(v) => {
+                let session = postcard::from_bytes(v.value())
+                    .map_err(|e| SaveFileError::Decode(e.to_string()))?;
+                Ok(Some(session))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Mark a session revoked. Returns false if no such session exists.
+    pub fn revoke_session(&self, jti: Uuid) -> Result<bool, SaveFileError> {
+        let txn = self.db.begin_write()?;
+        let mut found = false;
+        {
+            let mut sessions = txn.open_table(WORLD_SESSIONS)?;
+            let existing = sessions
+                .get(jti.as_bytes().as_slice())?
+                .map(|v| v.value().to_vec());
+            if let Some(bytes) = existing {
+                let mut session: Session = postcard::from_bytes(&bytes)
+                    .map_err(|e| SaveFileError::Decode(e.to_string()))?;
+                session.revoked = true;
+                let bytes = postcard::to_allocvec(&session)
+                    .map_err(|e| SaveFileError::Encode(e.to_string()))?;
+                sessions.insert(jti.as_bytes().as_slice(), bytes.as_slice())?;
+                found = true;
+            }
+        }
+        txn.commit()?;
+        Ok(found)
+    }
+
+    /// Load the persisted JWT signing-key keyring (empty if none exists yet
+    /// — callers bootstrap one via `rotate_jwt_key`).
+    pub fn load_jwt_keyring(&self) -> Result<Vec<JwtKey>, SaveFileError> {
+        let txn = self.db.begin_read()?;
+        let meta = txn.open_table(WORLD_META)?;
+        match meta.get(JWT_KEYRING_META_KEY)? {
+            Some(v) => postcard::from_bytes(v.value()).map_err(|e| SaveFileError::Decode(e.to_string())),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Generate a fresh signing key, mark it active, and retire every
+    /// previously-active key. Retired keys are kept so tokens they already
+    /// signed keep verifying until they expire naturally.
+    pub fn rotate_jwt_key(&self) -> Result<JwtKey, SaveFileError> {
+        let txn = self.db.begin_write()?;
+        let new_key;
+        {
+            let mut meta = txn.open_table(WORLD_META)?;
+            let mut keyring: Vec<JwtKey> = match meta.get(JWT_KEYRING_META_KEY)? {
+                Some(v) => postcard::from_bytes(v.value())
+                    .map_err(|e| SaveFileError::Decode(e.to_string()))?,
+                None => Vec::new(),
+            };
+            for key in &mut keyring {
+                key.retired = true;
+            }
+
+            let mut secret = vec![0u8; 32];
+            OsRng.fill_bytes(&mut secret);
+            new_key = JwtKey {
+                kid: Uuid::new_v4(),
+                secret,
+                created_at: chrono::Utc::now().timestamp(),
+                retired: false,
+            };
+            keyring.push(new_key.clone());
+
+            let bytes = postcard::to_allocvec(&keyring)
+                .map_err(|e| SaveFileError::Encode(e.to_string()))?;
+            meta.insert(JWT_KEYRING_META_KEY, bytes.as_slice())?;
+        }
+        txn.commit()?;
+        Ok(new_key)
+    }
+
+    /// Persist a freshly issued refresh token.
+    pub fn save_refresh_token(&self, token: &RefreshToken) -> Result<(), SaveFileError> {
+        let txn = self.db.begin_write()?;
+        {
+            let mut tokens = txn.open_table(WORLD_REFRESH_TOKENS)?;
+            let bytes = postcard::to_allocvec(token)
+                .map_err(|e| SaveFileError::Encode(e.to_string()))?;
+            tokens.insert(token.token_hash.as_slice(), bytes.as_slice())?;
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Look up and delete a refresh token by its hash in one transaction, so
+    /// a concurrent refresh can never redeem the same token twice.
+    pub fn take_refresh_token(&self, token_hash: &[u8]) -> Result<Option<RefreshToken>, SaveFileError> {
+        let txn = self.db.begin_write()?;
+        let found;
+        {
+            let mut tokens = txn.open_table(WORLD_REFRESH_TOKENS)?;
+            let existing = tokens.get(token_hash)?.map(|v| v.value().to_vec());
+            found = match existing {
+                Some(bytes) => {
+                    let token: RefreshToken = postcard::from_bytes(&bytes)
+                        .map_err(|e| SaveFileError::Decode(e.to_string()))?;
+                    tokens.remove(token_hash)?;
+                    Some(token)
+                }
+                None => None,
+            };
+        }
+        txn.commit()?;
+        Ok(found)
+    }
+}
+
+impl From<SaveFileError> for crate::store::StoreError {
+    fn from(e: SaveFileError) -> Self {
+        crate::store::StoreError::Redb(e.to_string())
+    }
+}
+
+impl crate::store::Store for SaveFile {
+    fn load_world(&self) -> Result<World, crate::store::StoreError> {
+        self.load_world().map_err(Into::into)
+    }
+
+    fn flush(&self, world: &World, event: &Event, user_id: Uuid) -> Result<(), crate::store::StoreError> {
+        self.flush(world, event, user_id).map_err(Into::into)
+    }
+
+    fn save_user(&self, user: &User) -> Result<(), crate::store::StoreError> {
+        self.save_user(user).map_err(Into::into)
+    }
+
+    fn save_service(&self, service: &Service) -> Result<(), crate::store::StoreError> {
+        self.save_service(service).map_err(Into::into)
+    }
+
+    fn ensure_default_services(&self, world: &mut World) -> Result<usize, crate::store::StoreError> {
+        self.ensure_default_services(world).map_err(Into::into)
+    }
+
+    fn ensure_default_user(&self, world: &mut World) -> Result<bool, crate::store::StoreError> {
+        self.ensure_default_user(world).map_err(Into::into)
+    }
+
+    fn save_session(&self, session: &Session) -> Result<(), crate::store::StoreError> {
+        self.save_session(session).map_err(Into::into)
+    }
+
+    fn get_session(&self, jti: Uuid) -> Result<Option<Session>, crate::store::StoreError> {
+        self.get_session(jti).map_err(Into::into)
+    }
+
+    fn revoke_session(&self, jti: Uuid) -> Result<bool, crate::store::StoreError> {
+        self.revoke_session(jti).map_err(Into::into)
+    }
+
+    fn load_jwt_keyring(&self) -> Result<Vec<JwtKey>, crate::store::StoreError> {
+        self.load_jwt_keyring().map_err(Into::into)
+    }
+
+    fn rotate_jwt_key(&self) -> Result<JwtKey, crate::store::StoreError> {
+        self.rotate_jwt_key().map_err(Into::into)
+    }
+
+    fn save_refresh_token(&self, token: &RefreshToken) -> Result<(), crate::store::StoreError> {
+        self.save_refresh_token(token).map_err(Into::into)
+    }
+
+    fn take_refresh_token(&self, token_hash: &[u8]) -> Result<Option<RefreshToken>, crate::store::StoreError> {
+        self.take_refresh_token(token_hash).map_err(Into::into)
+    }
+}
+
+// ── Migrations ─────────────────────────────────────────────────
+
+/// One schema step: `from_version` is the version it upgrades *from*, and
+/// `run` does the work inside a write transaction that also bumps
+/// `schema_version` to `from_version + 1` on commit.
+struct Migration {
+    from_version: u32,
+    run: fn(&WriteTransaction) -> Result<(), SaveFileError>,
+}
+
+static MIGRATIONS: &[Migration] = &[Migration {
+    from_version: 0,
+    run: migrate_legacy_tables,
+}];
+
+// Mirrors the on-disk JSON shape of the pre-World `Db`/`models.rs` prototype
+// closely enough to decode its rows. That code isn't wired into main.rs
+// anymore, but a save file created by it may still have these tables sitting
+// next to the WORLD_* ones.
+#[derive(Deserialize)]
+struct LegacyUser {
+    id: Uuid,
+    username: String,
+    password_hash: String,
+    // created_at has no home in world::User — dropped on migration.
+}
+
+#[derive(Deserialize)]
+struct LegacyTask {
+    id: Uuid,
+    status: LegacyTaskStatus,
+    priority: LegacyPriority,
+    created_by: Uuid,
+    assigned_to: Option<Uuid>,
+    title: String,
+    // description/category/tags/due_date/created_at/updated_at have no home
+    // in world::Task and are dropped on migration.
+}
+
+#[derive(Deserialize)]
+enum LegacyTaskStatus {
+    Pending,
+    InProgress,
+    Completed,
+}
+
+#[derive(Deserialize)]
+enum LegacyPriority {
+    Low,
+    Medium,
+    High,
+    Urgent,
+}
+
+impl From<LegacyPriority> for crate::world::Priority {
+    fn from(p: LegacyPriority) -> Self {
+        match p {
+            LegacyPriority::Low => crate::world::Priority::Low,
+            LegacyPriority::Medium => crate::world::Priority::Medium,
+            LegacyPriority::High => crate::world::Priority::High,
+            LegacyPriority::Urgent => crate::world::Priority::Urgent,
+        }
+    }
+}
+
+/// Copy rows out of the legacy `users`/`tasks`/`services` tables into the
+/// `WORLD_*` tables, re-encoding from JSON into postcard.
+///
+/// The legacy task model never recorded a service or any scheduling info, so
+/// every migrated task is filed under `MIGRATED_SERVICE_ID` and left Staged —
+/// `InProgress` tasks migrate to Staged too, since there's no date/time to
+/// make them Scheduled or Active with.
+fn migrate_legacy_tables(txn: &WriteTransaction) -> Result<(), SaveFileError> {
+    let mut world_users = txn.open_table(WORLD_USERS)?;
+    let legacy_users = txn.open_table(LEGACY_USERS)?;
+    for entry in legacy_users.iter()? {
+        let (_, value) = entry?;
+        let legacy: LegacyUser = serde_json::from_slice(value.value())
+            .map_err(|e| SaveFileError::Decode(e.to_string()))?;
+        let user = User {
+            id: legacy.id,
+            username: legacy.username,
+            password_hash: legacy.password_hash,
+            // The legacy model had no notion of roles — migrated accounts
+            // land as Member and an admin can promote them afterward.
+            role: crate::world::Role::Member,
+        };
+        let bytes =
+            postcard::to_allocvec(&user).map_err(|e| SaveFileError::Encode(e.to_string()))?;
+        world_users.insert(user.id.as_bytes().as_slice(), bytes.as_slice())?;
+    }
+
+    let mut world_services = txn.open_table(WORLD_SERVICES)?;
+    let legacy_services = txn.open_table(LEGACY_SERVICES)?;
+    let mut migrated_any_task = false;
+    for entry in legacy_services.iter()? {
+        let (_, value) = entry?;
+        // Best effort: carry over any legacy service verbatim if the shape matches.
+        if let Ok(service) = postcard::from_bytes::<Service>(value.value()) {
+            let bytes = postcard::to_allocvec(&service)
+                .map_err(|e| SaveFileError::Encode(e.to_string()))?;
+            world_services.insert(service.id.as_bytes().as_slice(), bytes.as_slice())?;
+        }
+    }
+
+    let mut world_tasks = txn.open_table(WORLD_TASKS)?;
+    let legacy_tasks = txn.open_table(LEGACY_TASKS)?;
+    for entry in legacy_tasks.iter()? {
+        let (_, value) = entry?;
+        let legacy: LegacyTask = serde_json::from_slice(value.value())
+            .map_err(|e| SaveFileError::Decode(e.to_string()))?;
+        let task = Task {
+            id: legacy.id,
+            title: legacy.title,
+            status: match legacy.status {
+                LegacyTaskStatus::Completed => TaskStatus::Completed,
+                LegacyTaskStatus::Pending | LegacyTaskStatus::InProgress => TaskStatus::Staged,
+            },
+            priority: legacy.priority.into(),
+            service_id: MIGRATED_SERVICE_ID,
+            created_by: legacy.created_by,
+            assigned_to: legacy.assigned_to,
+            date: None,
+            start_time: None,
+            duration: None,
+            // The legacy model had no notion of recurrence or dependencies.
+            recurrence: None,
+            parent_id: None,
+            depends_on: Vec::new(),
+        };
+        let bytes =
+            postcard::to_allocvec(&task).map_err(|e| SaveFileError::Encode(e.to_string()))?;
+        world_tasks.insert(task.id.as_bytes().as_slice(), bytes.as_slice())?;
+        migrated_any_task = true;
+    }
+
+    if migrated_any_task && world_services.get(MIGRATED_SERVICE_ID.as_bytes().as_slice())?.is_none() {
+        let service = Service {
+            id: MIGRATED_SERVICE_ID,
+            name: "Migrated".to_string(),
+        };
+        let bytes =
+            postcard::to_allocvec(&service).map_err(|e| SaveFileError::Encode(e.to_string()))?;
+        world_services.insert(service.id.as_bytes().as_slice(), bytes.as_slice())?;
+    }
+
+    Ok(())
 }
 
 // ── Errors ─────────────────────────────────────────────────────
@@ -232,6 +831,8 @@ pub enum SaveFileError {
     Redb(String),
     Decode(String),
     Encode(String),
+    /// The save file's schema_version is newer than this binary understands.
+    UnsupportedVersion(u32),
 }
 
 // redb 2.x has many error types. Blanket them all into SaveFileError::Redb.
@@ -258,6 +859,10 @@ impl std::fmt::Display for SaveFileError {
             SaveFileError::Redb(e) => write!(f, "redb: {e}"),
             SaveFileError::Decode(e) => write!(f, "decode: {e}"),
             SaveFileError::Encode(e) => write!(f, "encode: {e}"),
+            SaveFileError::UnsupportedVersion(v) => write!(
+                f,
+                "save file schema version {v} is newer than this binary understands (expected <= {CURRENT_SCHEMA_VERSION})"
+            ),
         }
     }
 }
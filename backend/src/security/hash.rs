@@ -0,0 +1,35 @@
+use argon2::{
+    password_hash::{rand_core::OsRng, SaltString},
+    Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
+};
+use rand::Rng;
+
+/// Hash a plaintext password, returning the PHC string (algorithm + salt + hash).
+/// This is the only form that should ever reach the data context.
+pub fn hash(plaintext: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(Argon2::default()
+        .hash_password(plaintext.as_bytes(), &salt)?
+        .to_string())
+}
+
+/// Verify a plaintext password against a stored PHC hash.
+/// Returns `Ok(false)` on mismatch rather than an error; only a malformed
+/// stored hash is treated as an error.
+pub fn verify(plaintext: &str, hash: &str) -> Result<bool, argon2::password_hash::Error> {
+    let parsed_hash = PasswordHash::new(hash)?;
+    Ok(Argon2::default()
+        .verify_password(plaintext.as_bytes(), &parsed_hash)
+        .is_ok())
+}
+
+/// Generate a random alphanumeric secret of at least 20 characters, suitable
+/// for session ids / tokens before they're hashed at rest.
+pub fn random() -> String {
+    const LEN: usize = 32;
+    rand::thread_rng()
+        .sample_iter(&rand::distributions::Alphanumeric)
+        .take(LEN)
+        .map(char::from)
+        .collect()
+}
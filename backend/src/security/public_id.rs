@@ -0,0 +1,70 @@
+use sqids::Sqids;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+
+const ALPHABET: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+const MIN_LENGTH: u8 = 10;
+
+fn sqids() -> Sqids {
+    Sqids::builder()
+        .alphabet(ALPHABET.chars().collect())
+        .min_length(MIN_LENGTH)
+        .build()
+        .expect("static sqids alphabet/min_length are always valid")
+}
+
+/// Short, opaque, URL-safe stand-in for a `Uuid` exposed across the public API.
+/// Internal identifiers never leave the process in their raw form.
+#[derive(Debug, Clone, PartialEq, Eq, ToSchema)]
+pub struct PublicId(String);
+
+impl PublicId {
+    pub fn encode(id: Uuid) -> Self {
+        let (high, low) = id.as_u64_pair();
+        Self(sqids().encode(&[high, low]).expect("u64 pair always fits the alphabet"))
+    }
+
+    pub fn decode(&self) -> Result<Uuid, ApiError> {
+        let numbers = sqids().decode(&self.0);
+        let [high, low]: [u64; 2] = numbers
+            .try_into()
+            .map_err(|_| ApiError::BadRequest("malformed id".to_string()))?;
+        Ok(Uuid::from_u64_pair(high, low))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for PublicId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<String> for PublicId {
+    fn from(s: String) -> Self {
+        Self(s)
+    }
+}
+
+impl serde::Serialize for PublicId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for PublicId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(PublicId)
+    }
+}
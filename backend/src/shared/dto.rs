@@ -1,6 +1,8 @@
 // Requests
 pub mod create_tao_task_request;
 pub mod update_task_request;
+pub mod task_query_params;
+pub mod task_history_query;
 pub mod login_request;
 pub mod user_add_request;
 pub mod user_edit_request;
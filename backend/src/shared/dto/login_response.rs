@@ -0,0 +1,8 @@
+use serde::Serialize;
+
+use crate::user_get_response::UserGetResponse;
+
+#[derive(Debug, Serialize)]
+pub struct LoginResponse {
+    pub user: UserGetResponse,
+}
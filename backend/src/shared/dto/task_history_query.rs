@@ -0,0 +1,8 @@
+use serde::Deserialize;
+use uuid::Uuid;
+
+/// Query-string parameters for `GET /api/tasks/history`.
+#[derive(Debug, Deserialize)]
+pub struct TaskHistoryQuery {
+    pub id: Uuid,
+}
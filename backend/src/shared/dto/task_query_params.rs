@@ -0,0 +1,18 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{task_priority::TaskPriority, task_status::TaskStatus};
+
+/// Query-string parameters for `GET /api/tasks`. All fields are optional;
+/// the controller applies defaults for `limit`/`offset` when absent.
+#[derive(Debug, Deserialize)]
+pub struct TaskQueryParams {
+    pub status: Option<TaskStatus>,
+    pub assigned_to: Option<Uuid>,
+    pub category: Option<String>,
+    pub priority: Option<TaskPriority>,
+    pub due_before: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+    pub offset: Option<usize>,
+}
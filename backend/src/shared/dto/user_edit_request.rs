@@ -1,7 +1,11 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UserEditRequest {
     pub username: Option<String>,
     pub email: Option<String>,
+    /// New plaintext password. Rejected by `UserController::edit` if the
+    /// target user's identity is owned by an external `AuthProvider`.
+    pub password: Option<String>,
 }
\ No newline at end of file
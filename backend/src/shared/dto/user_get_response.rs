@@ -1,10 +1,13 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::security::public_id::PublicId;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UserGetResponse {
-    pub id: Uuid,
+    pub id: PublicId,
     pub username: String,
     pub created_at: DateTime<Utc>,
+    pub avatar_url: Option<String>,
 }
\ No newline at end of file
@@ -0,0 +1,8 @@
+pub mod app_state;
+pub mod session;
+pub mod settings;
+pub mod tao_task;
+pub mod task_event;
+pub mod task_priority;
+pub mod task_status;
+pub mod user;
@@ -1,10 +1,31 @@
 use std::sync::Arc;
-// use tokio::sync::broadcast::Sender;
-use crate::data_access::data_context::DataContext;
+use tokio::sync::broadcast::Sender;
+use crate::authentication::auth_provider::AuthProvider;
+use crate::data_access::store::Store;
 
 pub struct AppState {
-    pub data_context: DataContext,
-    //pub ws_broadcast: Sender<String>,
+    /// The backend chosen by `Settings.storage_backend` — see
+    /// `data_access::store::build`. Controllers never see which concrete
+    /// `Store` they're talking to.
+    pub store: Arc<dyn Store>,
+    /// JSON-serialized `TaskEvent`s, broadcast to every subscriber of
+    /// `TaskWsController::ws_handler` as task mutations happen.
+    pub ws_broadcast: Sender<String>,
+    /// Fired once by `begin_shutdown` on SIGTERM/SIGINT. `TaskWsController`
+    /// holds a subscriber alongside `ws_broadcast` so it can drain its
+    /// socket with a clean Close frame instead of being killed mid-frame.
+    pub shutdown: Sender<()>,
+    /// The provider chain `SessionController::login` tries, in order, built
+    /// from `Settings.auth_providers` by `auth_provider::build`.
+    pub auth_providers: Vec<Box<dyn AuthProvider>>,
 }
 
-pub type SharedState = Arc<AppState>;
\ No newline at end of file
+pub type SharedState = Arc<AppState>;
+
+impl AppState {
+    /// Tell every connected WebSocket to wind down. Safe to call more than
+    /// once; only subscribers present at call time observe it.
+    pub fn begin_shutdown(&self) {
+        let _ = self.shutdown.send(());
+    }
+}
\ No newline at end of file
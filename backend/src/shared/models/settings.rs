@@ -1,9 +1,19 @@
-use std::{error::Error, fs};
+//! Application configuration for the web API.
+//!
+//! Loaded in layers, each overriding the previous: built-in defaults, then
+//! an optional `settings.json`/`settings.toml` (auto-detected by the
+//! settings file's extension, default `settings.json` in the CWD, overridable
+//! via `TXXT_SETTINGS_FILE`), then `TXXT_`-prefixed environment variables.
+//! Nothing here panics — a missing file falls back to defaults, and a bad
+//! field surfaces as a `SettingsError` for the caller to report.
+
+use std::{env, fmt, fs};
 use serde::Deserialize;
 
-const SETTINGS_FILENAME: &str = "settings.json";
+const DEFAULT_SETTINGS_FILENAME: &str = "settings.json";
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
 pub struct Settings {
     pub tcp_socket_binding: String,
     pub tcp_socket_port: u16,
@@ -12,13 +22,191 @@ pub struct Settings {
     pub redb_file_path: String,
     pub default_admin_username: String,
     pub default_admin_password: String,
-    pub default_admin_email: String
+    pub default_admin_email: String,
+    /// Which `AuthProvider`s `SessionController::login` tries, in order.
+    /// `"local"` is always available; `"ldap"` additionally requires `ldap`
+    /// below. An unrecognized entry is logged and skipped at startup.
+    pub auth_providers: Vec<String>,
+    /// Directory connection details for the `"ldap"` provider. Ignored
+    /// unless `"ldap"` is listed in `auth_providers`.
+    pub ldap: Option<LdapSettings>,
+    /// Passphrase `DataContext` derives its encryption-at-rest key from (via
+    /// `blake3::derive_key`). Falls back to `jwt_secret` if unset, so every
+    /// deployment gets an encrypted file even without setting this explicitly.
+    pub data_encryption_key: Option<String>,
+    /// Which `Store` implementation `AppState` is built with.
+    pub storage_backend: StorageBackend,
+    /// Bucket/prefix `ObjectStoreBackend` would read and write under.
+    /// Ignored unless `storage_backend` is `"object_store"`.
+    pub object_store: Option<ObjectStoreSettings>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            tcp_socket_binding: "0.0.0.0".to_string(),
+            tcp_socket_port: 8080,
+            jwt_secret: String::new(),
+            jwt_expiration_in_minutes: 60,
+            redb_file_path: "data.redb".to_string(),
+            default_admin_username: "admin".to_string(),
+            default_admin_password: "admin".to_string(),
+            default_admin_email: "admin@localhost".to_string(),
+            auth_providers: vec!["local".to_string()],
+            ldap: None,
+            data_encryption_key: None,
+            storage_backend: StorageBackend::default(),
+            object_store: None,
+        }
+    }
+}
+
+/// Which [`crate::data_access::store::Store`] implementation `AppState` is
+/// built with. `Redb` is the only backend that persists across restarts
+/// today; `InMemory` is for tests, and `ObjectStore` is not yet implemented
+/// (see `data_access::object_store_backend`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageBackend {
+    Redb,
+    InMemory,
+    ObjectStore,
+}
+
+impl Default for StorageBackend {
+    fn default() -> Self {
+        StorageBackend::Redb
+    }
+}
+
+/// Bucket/prefix an `ObjectStore`-backed `Store` would read and write
+/// objects under, e.g. bucket `"txxt-prod"`, prefix `"data/"`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ObjectStoreSettings {
+    pub bucket: String,
+    pub prefix: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LdapSettings {
+    /// e.g. "ldap://directory.example.com:389".
+    pub url: String,
+    /// Base DN of the directory, e.g. "ou=people,dc=example,dc=com". It is
+    /// not searched; it only forms a provisioned user's email,
+    /// `{username}@{base_dn}`.
+    pub base_dn: String,
+    /// Bind DN with `{username}` substituted in, e.g.
+    /// "uid={username},ou=people,dc=example,dc=com".
+    pub bind_dn_template: String,
+    /// Whether to require `ldaps://`/STARTTLS rather than a plaintext bind.
+    #[serde(default)]
+    pub tls: bool,
 }
 
 impl Settings {
-    pub fn load() -> Result<Settings, Box<dyn Error>> {
-        let content = fs::read_to_string(SETTINGS_FILENAME).expect(format!("Cannot read settings file {}", SETTINGS_FILENAME).as_str());
-        let settings = serde_json::from_str(&content).expect(format!("Cannot parse JSON content from file {}", SETTINGS_FILENAME).as_str());
+    /// Defaults, overlaid with `settings.json`/`settings.toml` if one is
+    /// found (path from `TXXT_SETTINGS_FILE`, default `settings.json`),
+    /// overlaid with `TXXT_*` environment variables, then validated.
+    pub fn load() -> Result<Settings, SettingsError> {
+        let path = env::var("TXXT_SETTINGS_FILE").unwrap_or_else(|_| DEFAULT_SETTINGS_FILENAME.to_string());
+
+        let mut settings = match fs::read_to_string(&path) {
+            Ok(contents) => Self::parse(&path, &contents)?,
+            Err(_) => Settings::default(),
+        };
+
+        settings.apply_env_overrides();
+        settings.validate()?;
         Ok(settings)
     }
-}
\ No newline at end of file
+
+    /// Defaults overlaid with only `TXXT_*` environment variables, for
+    /// container deployments that ship no settings file at all.
+    pub fn from_env_only() -> Result<Settings, SettingsError> {
+        let mut settings = Settings::default();
+        settings.apply_env_overrides();
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    fn parse(path: &str, contents: &str) -> Result<Settings, SettingsError> {
+        if path.ends_with(".toml") {
+            toml::from_str(contents).map_err(|e| SettingsError::Parse(path.to_string(), e.to_string()))
+        } else {
+            serde_json::from_str(contents).map_err(|e| SettingsError::Parse(path.to_string(), e.to_string()))
+        }
+    }
+
+    fn apply_env_overrides(&mut self) {
+        if let Ok(v) = env::var("TXXT_TCP_SOCKET_BINDING") {
+            self.tcp_socket_binding = v;
+        }
+        if let Ok(v) = env::var("TXXT_TCP_SOCKET_PORT") {
+            if let Ok(port) = v.parse() {
+                self.tcp_socket_port = port;
+            }
+        }
+        if let Ok(v) = env::var("TXXT_JWT_SECRET") {
+            self.jwt_secret = v;
+        }
+        if let Ok(v) = env::var("TXXT_JWT_EXPIRATION_IN_MINUTES") {
+            if let Ok(minutes) = v.parse() {
+                self.jwt_expiration_in_minutes = minutes;
+            }
+        }
+        if let Ok(v) = env::var("TXXT_REDB_FILE_PATH") {
+            self.redb_file_path = v;
+        }
+        if let Ok(v) = env::var("TXXT_DEFAULT_ADMIN_USERNAME") {
+            self.default_admin_username = v;
+        }
+        if let Ok(v) = env::var("TXXT_DEFAULT_ADMIN_PASSWORD") {
+            self.default_admin_password = v;
+        }
+        if let Ok(v) = env::var("TXXT_DEFAULT_ADMIN_EMAIL") {
+            self.default_admin_email = v;
+        }
+        if let Ok(v) = env::var("TXXT_DATA_ENCRYPTION_KEY") {
+            self.data_encryption_key = Some(v);
+        }
+        if let Ok(v) = env::var("TXXT_STORAGE_BACKEND") {
+            match v.as_str() {
+                "redb" => self.storage_backend = StorageBackend::Redb,
+                "in_memory" => self.storage_backend = StorageBackend::InMemory,
+                "object_store" => self.storage_backend = StorageBackend::ObjectStore,
+                other => eprintln!("unknown TXXT_STORAGE_BACKEND {other:?}; keeping {:?}", self.storage_backend),
+            }
+        }
+    }
+
+    fn validate(&self) -> Result<(), SettingsError> {
+        if self.jwt_secret.trim().is_empty() {
+            return Err(SettingsError::Validation("jwt_secret must not be empty".to_string()));
+        }
+        if self.tcp_socket_port == 0 {
+            return Err(SettingsError::Validation("tcp_socket_port must not be 0".to_string()));
+        }
+        if self.jwt_expiration_in_minutes == 0 {
+            return Err(SettingsError::Validation("jwt_expiration_in_minutes must not be 0".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Why [`Settings::load`] or [`Settings::from_env_only`] failed.
+#[derive(Debug)]
+pub enum SettingsError {
+    Parse(String, String),
+    Validation(String),
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsError::Parse(path, e) => write!(f, "failed to parse settings file {path}: {e}"),
+            SettingsError::Validation(msg) => write!(f, "invalid settings: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for SettingsError {}
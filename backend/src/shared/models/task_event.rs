@@ -0,0 +1,24 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::tao_task::TaoTask;
+
+/// One entry in a task's append-only audit log, recorded by
+/// [`crate::data_access::data_context::DataContext`] inside the same write
+/// transaction as the mutation it describes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskEvent {
+    pub task_id: Uuid,
+    pub action: TaskAction,
+    pub actor: Uuid,
+    pub occurred_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TaskAction {
+    Created { task: TaoTask },
+    Updated { task: TaoTask },
+    Deleted,
+}
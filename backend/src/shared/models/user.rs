@@ -1,10 +1,29 @@
-use argon2::{Argon2, PasswordHasher, password_hash::{SaltString, rand_core::OsRng}};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::security::hash;
+use crate::security::public_id::PublicId;
 use crate::{user_add_request::UserAddRequest, user_edit_request::UserEditRequest, user_get_response::UserGetResponse};
 
+/// Who owns this user's credential and is allowed to change it.
+/// `Local` means the stored `password_hash` is the credential; anything
+/// else means an [`AuthProvider`](crate::authentication::auth_provider::AuthProvider)
+/// other than `LocalAuthProvider` verifies logins, and `password_hash` is a
+/// random placeholder nothing should ever check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentitySource {
+    Local,
+    Ldap,
+}
+
+impl Default for IdentitySource {
+    fn default() -> Self {
+        IdentitySource::Local
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
     pub id: Uuid,
@@ -12,43 +31,54 @@ pub struct User {
     pub password_hash: String,
     pub email: String,
     pub created_at: DateTime<Utc>,
+    pub has_avatar: bool,
+    #[serde(default)]
+    pub identity_source: IdentitySource,
 }
 
 impl User {
-    pub fn new(request: UserAddRequest) -> Self {
-        Self {
+    pub fn new(request: UserAddRequest) -> Result<Self, argon2::password_hash::Error> {
+        Ok(Self {
             id: Uuid::new_v4(),
             username: request.username,
             email: request.email,
             created_at: Utc::now(),
-            password_hash: User::get_hashed_password(request.password.trim().as_bytes())
-        }
+            password_hash: hash::hash(request.password.trim())?,
+            has_avatar: false,
+            identity_source: IdentitySource::Local,
+        })
     }
 
     pub fn edit(self, request: UserEditRequest) -> Self {
+        let password_hash = match (&self.identity_source, &request.password) {
+            (IdentitySource::Local, Some(new_password)) => {
+                hash::hash(new_password.trim()).unwrap_or(self.password_hash)
+            }
+            _ => self.password_hash,
+        };
         Self {
             id: self.id,
             username: request.username.unwrap_or(self.username),
             email: request.email.unwrap_or(self.email),
-            password_hash: self.password_hash,
-            created_at: self.created_at
+            password_hash,
+            created_at: self.created_at,
+            has_avatar: self.has_avatar,
+            identity_source: self.identity_source,
         }
     }
 
+    pub fn with_avatar(self, has_avatar: bool) -> Self {
+        Self { has_avatar, ..self }
+    }
+
     pub fn to_get_dto(&self) -> UserGetResponse {
+        let public_id = PublicId::encode(self.id);
         UserGetResponse {
-            id: self.id,
+            avatar_url: self.has_avatar.then(|| format!("/api/user/avatar?id={public_id}")),
+            id: public_id,
             username: self.username.clone(),
-            created_at: self.created_at
+            created_at: self.created_at,
         }
     }
 
-    fn get_hashed_password(password_bytes: &[u8]) -> String {
-        let salt = SaltString::generate(&mut OsRng);
-            let argon2 = Argon2::default();
-            argon2
-                .hash_password(password_bytes, &salt)
-                .unwrap()
-                .to_string()
-    }
 }
\ No newline at end of file
@@ -0,0 +1,397 @@
+//! SQL storage backend (feature = "sql").
+//!
+//! One table per entity, each shaped `(id BYTEA PRIMARY KEY, postcard
+//! BYTEA)` — the same postcard encoding the redb backend uses, just in a
+//! shared Postgres database instead of a local file. This is what lets
+//! multiple server instances run against the same World.
+
+use crate::store::{Store, StoreError};
+use crate::world::{Event, JwtKey, RefreshToken, Service, Session, Task, User, World};
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use sqlx::{postgres::PgPoolOptions, PgPool, Row};
+use uuid::Uuid;
+
+const CREATE_TABLES: &str = r#"
+CREATE TABLE IF NOT EXISTS world_tasks    (id BYTEA PRIMARY KEY, postcard BYTEA NOT NULL);
+CREATE TABLE IF NOT EXISTS world_users    (id BYTEA PRIMARY KEY, postcard BYTEA NOT NULL);
+CREATE TABLE IF NOT EXISTS world_services (id BYTEA PRIMARY KEY, postcard BYTEA NOT NULL);
+CREATE TABLE IF NOT EXISTS world_meta     (key TEXT PRIMARY KEY, value BYTEA NOT NULL);
+CREATE TABLE IF NOT EXISTS world_sessions (jti BYTEA PRIMARY KEY, postcard BYTEA NOT NULL);
+CREATE TABLE IF NOT EXISTS world_refresh_tokens (token_hash BYTEA PRIMARY KEY, postcard BYTEA NOT NULL);
+"#;
+
+const UPSERT: &str =
+    "INSERT INTO {} (id, postcard) VALUES ($1, $2) ON CONFLICT (id) DO UPDATE SET postcard = EXCLUDED.postcard";
+
+#[derive(Clone)]
+pub struct SqlStore {
+    pool: PgPool,
+}
+
+impl SqlStore {
+    /// Connect to `database_url` (a Postgres connection string) and ensure
+    /// the entity tables exist.
+    pub fn connect(database_url: &str) -> Result<Self, StoreError> {
+        Self::block_on_new(async {
+            let pool = PgPoolOptions::new()
+                .max_connections(10)
+                .connect(database_url)
+                .await
+                .map_err(|e| StoreError::Sql(e.to_string()))?;
+
+            sqlx::query(CREATE_TABLES)
+                .execute(&pool)
+                .await
+                .map_err(|e| StoreError::Sql(e.to_string()))?;
+
+            Ok(SqlStore { pool })
+        })
+    }
+
+    /// `SaveFile::open` is sync, so the `Store` trait is too — run the
+    /// async sqlx calls to completion on whatever runtime is current.
+    fn block_on<F: std::future::Future>(&self, fut: F) -> F::Output {
+        tokio::task::block_in_place(|| tokio::runtime::Handle::current().block_on(fut))
+    }
+
+    fn block_on_new<F: std::future::Future<Output = Result<Self, StoreError>>>(
+        fut: F,
+    ) -> Result<Self, StoreError> {
+        tokio::task::block_in_place(|| tokio::runtime::Handle::current().block_on(fut))
+    }
+
+    async fn upsert_task(&self, task: &Task) -> Result<(), StoreError> {
+        let bytes = postcard::to_allocvec(task).map_err(|e| StoreError::Sql(e.to_string()))?;
+        sqlx::query(&UPSERT.replace("{}", "world_tasks"))
+            .bind(task.id.as_bytes().as_slice())
+            .bind(&bytes)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| StoreError::Sql(e.to_string()))?;
+        Ok(())
+    }
+}
+
+impl Store for SqlStore {
+    fn load_world(&self) -> Result<World, StoreError> {
+        self.block_on(async {
+            let mut world = World::new();
+
+            for row in sqlx::query("SELECT postcard FROM world_tasks")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| StoreError::Sql(e.to_string()))?
+            {
+                let bytes: Vec<u8> = row.get("postcard");
+                let task: Task =
+                    postcard::from_bytes(&bytes).map_err(|e| StoreError::Sql(e.to_string()))?;
+                world.tasks.insert(task.id, task);
+            }
+
+            for row in sqlx::query("SELECT postcard FROM world_users")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| StoreError::Sql(e.to_string()))?
+            {
+                let bytes: Vec<u8> = row.get("postcard");
+                let user: User =
+                    postcard::from_bytes(&bytes).map_err(|e| StoreError::Sql(e.to_string()))?;
+                world.users.insert(user.id, user);
+            }
+
+            for row in sqlx::query("SELECT postcard FROM world_services")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|e| StoreError::Sql(e.to_string()))?
+            {
+                let bytes: Vec<u8> = row.get("postcard");
+                let service: Service =
+                    postcard::from_bytes(&bytes).map_err(|e| StoreError::Sql(e.to_string()))?;
+                world.services.insert(service.id, service);
+            }
+
+            if let Some(row) = sqlx::query("SELECT value FROM world_meta WHERE key = 'revision'")
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| StoreError::Sql(e.to_string()))?
+            {
+                let bytes: Vec<u8> = row.get("value");
+                if bytes.len() == 8 {
+                    world.revision = u64::from_le_bytes(bytes.try_into().unwrap());
+                }
+            }
+
+            world.rebuild_indexes();
+            Ok(world)
+        })
+    }
+
+    fn flush(&self, world: &World, event: &Event, _user_id: Uuid) -> Result<(), StoreError> {
+        self.block_on(async {
+            match event {
+                Event::TaskCreated { task, .. } => self.upsert_task(task).await?,
+                Event::TaskScheduled { task_id, .. }
+                | Event::TaskMoved { task_id, .. }
+                | Event::TaskUnscheduled { task_id, .. }
+                | Event::TaskCompleted { task_id, .. }
+                | Event::TaskFailed { task_id, .. }
+                | Event::TaskRetried { task_id, .. } => {
+                    self.upsert_task(&world.tasks[task_id]).await?
+                }
+                Event::TaskDeleted { task_id, .. } => {
+                    sqlx::query("DELETE FROM world_tasks WHERE id = $1")
+                        .bind(task_id.as_bytes().as_slice())
+                        .execute(&self.pool)
+                        .await
+                        .map_err(|e| StoreError::Sql(e.to_string()))?;
+                }
+                Event::DependencyAdded { task_id, .. } | Event::DependencyRemoved { task_id, .. } => {
+                    self.upsert_task(&world.tasks[task_id]).await?
+                }
+            }
+
+            sqlx::query(
+                "INSERT INTO world_meta (key, value) VALUES ('revision', $1) \
+                 ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value",
+            )
+            .bind(world.revision.to_le_bytes().as_slice())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| StoreError::Sql(e.to_string()))?;
+
+            Ok(())
+        })
+    }
+
+    fn save_user(&self, user: &User) -> Result<(), StoreError> {
+        self.block_on(async {
+            let bytes = postcard::to_allocvec(user).map_err(|e| StoreError::Sql(e.to_string()))?;
+            sqlx::query(&UPSERT.replace("{}", "world_users"))
+                .bind(user.id.as_bytes().as_slice())
+                .bind(&bytes)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| StoreError::Sql(e.to_string()))?;
+            Ok(())
+        })
+    }
+
+    fn save_service(&self, service: &Service) -> Result<(), StoreError> {
+        self.block_on(async {
+            let bytes =
+                postcard::to_allocvec(service).map_err(|e| StoreError::Sql(e.to_string()))?;
+            sqlx::query(&UPSERT.replace("{}", "world_services"))
+                .bind(service.id.as_bytes().as_slice())
+                .bind(&bytes)
+                .execute(&self.pool)
+                .await
+                .map_err(|e| StoreError::Sql(e.to_string()))?;
+            Ok(())
+        })
+    }
+
+    fn ensure_default_services(&self, world: &mut World) -> Result<usize, StoreError> {
+        if !world.services.is_empty() {
+            return Ok(0);
+        }
+
+        for (id_str, name) in crate::store::DEFAULT_SERVICES {
+            let service = Service {
+                id: Uuid::parse_str(id_str).unwrap(),
+                name: name.to_string(),
+            };
+            self.save_service(&service)?;
+            world.services.insert(service.id, service);
+        }
+
+        Ok(crate::store::DEFAULT_SERVICES.len())
+    }
+
+    fn ensure_default_user(&self, world: &mut World) -> Result<bool, StoreError> {
+        if !world.users.is_empty() {
+            return Ok(false);
+        }
+
+        use argon2::{
+            password_hash::{rand_core::OsRng, SaltString},
+            Argon2, PasswordHasher,
+        };
+
+        let salt = SaltString::generate(&mut OsRng);
+        let password_hash = Argon2::default()
+            .hash_password(b"admin", &salt)
+            .unwrap()
+            .to_string();
+
+        let user = User {
+            id: Uuid::new_v4(),
+            username: "admin".to_string(),
+            password_hash,
+            role: crate::world::Role::Admin,
+        };
+
+        self.save_user(&user)?;
+        world.users.insert(user.id, user);
+        Ok(true)
+    }
+
+    fn save_session(&self, session: &Session) -> Result<(), StoreError> {
+        self.block_on(async {
+            let bytes =
+                postcard::to_allocvec(session).map_err(|e| StoreError::Sql(e.to_string()))?;
+            sqlx::query(
+                "INSERT INTO world_sessions (jti, postcard) VALUES ($1, $2) \
+                 ON CONFLICT (jti) DO UPDATE SET postcard = EXCLUDED.postcard",
+            )
+            .bind(session.jti.as_bytes().as_slice())
+            .bind(&bytes)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| StoreError::Sql(e.to_string()))?;
+            Ok(())
+        })
+    }
+
+    fn get_session(&self, jti: Uuid) -> Result<Option<Session>, StoreError> {
+        self.block_on(async {
+            let row = sqlx::query("SELECT postcard FROM world_sessions WHERE jti = $1")
+                .bind(jti.as_bytes().as_slice())
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| StoreError::Sql(e.to_string()))?;
+
+            match row {
+                Some(row) => {
+                    let bytes: Vec<u8> = row.get("postcard");
+                    let session: Session = postcard::from_bytes(&bytes)
+                        .map_err(|e| StoreError::Sql(e.to_string()))?;
+                    Ok(Some(session))
+                }
+                None => Ok(None),
+            }
+        })
+    }
+
+    fn revoke_session(&self, jti: Uuid) -> Result<bool, StoreError> {
+        let session = self.get_session(jti)?;
+        match session {
+            Some(mut session) => {
+                session.revoked = true;
+                self.save_session(&session)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn load_jwt_keyring(&self) -> Result<Vec<JwtKey>, StoreError> {
+        self.block_on(async {
+            let row = sqlx::query("SELECT value FROM world_meta WHERE key = 'jwt_keyring'")
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| StoreError::Sql(e.to_string()))?;
+
+            match row {
+                Some(row) => {
+                    let bytes: Vec<u8> = row.get("value");
+                    postcard::from_bytes(&bytes).map_err(|e| StoreError::Sql(e.to_string()))
+                }
+                None => Ok(Vec::new()),
+            }
+        })
+    }
+
+    fn rotate_jwt_key(&self) -> Result<JwtKey, StoreError> {
+        self.block_on(async {
+            let row = sqlx::query("SELECT value FROM world_meta WHERE key = 'jwt_keyring'")
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| StoreError::Sql(e.to_string()))?;
+
+            let mut keyring: Vec<JwtKey> = match row {
+                Some(row) => {
+                    let bytes: Vec<u8> = row.get("value");
+                    postcard::from_bytes(&bytes).map_err(|e| StoreError::Sql(e.to_string()))?
+                }
+                None => Vec::new(),
+            };
+            for key in &mut keyring {
+                key.retired = true;
+            }
+
+            let mut secret = vec![0u8; 32];
+            OsRng.fill_bytes(&mut secret);
+            let new_key = JwtKey {
+                kid: Uuid::new_v4(),
+                secret,
+                created_at: chrono::Utc::now().timestamp(),
+                retired: false,
+            };
+            keyring.push(new_key.clone());
+
+            let bytes =
+                postcard::to_allocvec(&keyring).map_err(|e| StoreError::Sql(e.to_string()))?;
+            sqlx::query(
+                "INSERT INTO world_meta (key, value) VALUES ('jwt_keyring', $1) \
+                 ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value",
+            )
+            .bind(&bytes)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| StoreError::Sql(e.to_string()))?;
+
+            Ok(new_key)
+        })
+    }
+
+    fn save_refresh_token(&self, token: &RefreshToken) -> Result<(), StoreError> {
+        self.block_on(async {
+            let bytes =
+                postcard::to_allocvec(token).map_err(|e| StoreError::Sql(e.to_string()))?;
+            sqlx::query(
+                "INSERT INTO world_refresh_tokens (token_hash, postcard) VALUES ($1, $2) \
+                 ON CONFLICT (token_hash) DO UPDATE SET postcard = EXCLUDED.postcard",
+            )
+            .bind(&token.token_hash)
+            .bind(&bytes)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| StoreError::Sql(e.to_string()))?;
+            Ok(())
+        })
+    }
+
+    fn take_refresh_token(&self, token_hash: &[u8]) -> Result<Option<RefreshToken>, StoreError> {
+        self.block_on(async {
+            let mut tx = self
+                .pool
+                .begin()
+                .await
+                .map_err(|e| StoreError::Sql(e.to_string()))?;
+
+            let row = sqlx::query("SELECT postcard FROM world_refresh_tokens WHERE token_hash = $1")
+                .bind(token_hash)
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(|e| StoreError::Sql(e.to_string()))?;
+
+            let found = match row {
+                Some(row) => {
+                    let bytes: Vec<u8> = row.get("postcard");
+                    let token: RefreshToken = postcard::from_bytes(&bytes)
+                        .map_err(|e| StoreError::Sql(e.to_string()))?;
+                    sqlx::query("DELETE FROM world_refresh_tokens WHERE token_hash = $1")
+                        .bind(token_hash)
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(|e| StoreError::Sql(e.to_string()))?;
+                    Some(token)
+                }
+                None => None,
+            };
+
+            tx.commit().await.map_err(|e| StoreError::Sql(e.to_string()))?;
+            Ok(found)
+        })
+    }
+}
@@ -0,0 +1,128 @@
+//! Pluggable persistence backend.
+//!
+//! `World`'s runtime logic doesn't know or care whether its rows live in a
+//! local redb file or a shared SQL database — it only talks to a `Store`
+//! trait object. Select the concrete backend at boot via `store::open`,
+//! driven by the `TXXT_STORE_BACKEND` environment variable. Each backend lives behind its own cargo feature
+//! (`redb`, `sql`) the way bitwarden_rs gates sqlite/mysql/postgresql.
+
+use crate::world::{Event, JwtKey, RefreshToken, Service, Session, User, World};
+use uuid::Uuid;
+
+#[cfg(not(any(feature = "redb", feature = "sql")))]
+compile_error!("enable at least one storage backend feature: \"redb\" or \"sql\"");
+
+/// The catalog of default services seeded into an empty World. Shared by
+/// every backend so they stay in lockstep.
+pub(crate) const DEFAULT_SERVICES: &[(&str, &str)] = &[
+    ("6b3c18d4-2a1d-4f2b-9d4c-0a0c3f0f2f10", "Billing Portal"),
+    ("a8c2f1f0-8b8f-4a62-9d3a-8c1d7b4c2a01", "Customer Support"),
+    ("2e6a7c11-8c39-4d5f-9a0e-6e1a4c7f3b22", "Data Warehouse"),
+    ("d0b74f7e-3c2a-4a58-8b21-5e9d2a1c4f33", "Fraud Detection"),
+    ("f2a1c3b4-5d6e-4f70-8123-4567890abcde", "Identity"),
+    ("0c1d2e3f-4a5b-6c7d-8e9f-0123456789ab", "Internal Tools"),
+    ("11121314-1516-1718-191a-1b1c1d1e1f20", "Mobile App"),
+    ("21222324-2526-2728-292a-2b2c2d2e2f30", "Payments"),
+    ("31323334-3536-3738-393a-3b3c3d3e3f40", "Reporting"),
+    ("41424344-4546-4748-494a-4b4c4d4e4f50", "Search"),
+    ("51525354-5556-5758-595a-5b5c5d5e5f60", "Shipping"),
+    ("61626364-6566-6768-696a-6b6c6d6e6f70", "Web App"),
+];
+
+/// Everything the runtime needs from persistence. Implemented once per
+/// backend; `World` and the game loop only ever see `dyn Store` (as an
+/// `Arc`, once `main` hands it to `AppState`, so auth providers can share
+/// the same handle).
+pub trait Store: Send + Sync {
+    /// Load the entire World from the backend. Called once at boot.
+    fn load_world(&self) -> Result<World, StoreError>;
+
+    /// Persist a single event (and the entity it produced) after `World::apply()`.
+    fn flush(&self, world: &World, event: &Event, user_id: Uuid) -> Result<(), StoreError>;
+
+    /// Write a user to the backend (for seeding / account creation).
+    fn save_user(&self, user: &User) -> Result<(), StoreError>;
+
+    /// Write a service to the backend (for seeding).
+    fn save_service(&self, service: &Service) -> Result<(), StoreError>;
+
+    /// Seed default services if none exist. Returns how many were created.
+    fn ensure_default_services(&self, world: &mut World) -> Result<usize, StoreError>;
+
+    /// Seed default admin user if no users exist. Returns true if created.
+    fn ensure_default_user(&self, world: &mut World) -> Result<bool, StoreError>;
+
+    /// Persist a newly issued session (one per access/refresh token pair).
+    fn save_session(&self, session: &Session) -> Result<(), StoreError>;
+
+    /// Look up a session by its token's `jti`.
+    fn get_session(&self, jti: Uuid) -> Result<Option<Session>, StoreError>;
+
+    /// Mark a session revoked. Returns false if no such session exists.
+    fn revoke_session(&self, jti: Uuid) -> Result<bool, StoreError>;
+
+    /// Load the persisted JWT signing-key keyring (empty if none exists yet).
+    fn load_jwt_keyring(&self) -> Result<Vec<JwtKey>, StoreError>;
+
+    /// Generate a fresh JWT signing key, mark it active, and retire the
+    /// previously-active one (kept around for verification only).
+    fn rotate_jwt_key(&self) -> Result<JwtKey, StoreError>;
+
+    /// Persist a freshly issued refresh token (identified by the hash of the
+    /// opaque value the client holds, never the value itself).
+    fn save_refresh_token(&self, token: &RefreshToken) -> Result<(), StoreError>;
+
+    /// Look up and atomically delete a refresh token by its hash. Consuming
+    /// it on read is what makes rotation-on-use reject replay: once a token
+    /// has been exchanged, the same hash no longer resolves to anything.
+    fn take_refresh_token(&self, token_hash: &[u8]) -> Result<Option<RefreshToken>, StoreError>;
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    Redb(String),
+    Sql(String),
+}
+
+impl std::fmt::Display for StoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoreError::Redb(e) => write!(f, "redb backend: {e}"),
+            StoreError::Sql(e) => write!(f, "sql backend: {e}"),
+        }
+    }
+}
+
+/// Which backend to use, read from `TXXT_STORE_BACKEND` at boot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Redb,
+    Sql,
+}
+
+/// Open the backend selected by `backend`, pointed at `location` (a file
+/// path for `Redb`, a connection string for `Sql`).
+pub fn open(backend: Backend, location: &str) -> Result<Box<dyn Store>, StoreError> {
+    match backend {
+        #[cfg(feature = "redb")]
+        Backend::Redb => {
+            let save_file = crate::persist::SaveFile::open(location)
+                .map_err(|e| StoreError::Redb(e.to_string()))?;
+            Ok(Box::new(save_file))
+        }
+        #[cfg(not(feature = "redb"))]
+        Backend::Redb => Err(StoreError::Redb(
+            "this binary was built without the \"redb\" feature".to_string(),
+        )),
+
+        #[cfg(feature = "sql")]
+        Backend::Sql => {
+            let store = crate::sql_store::SqlStore::connect(location)?;
+            Ok(Box::new(store))
+        }
+        #[cfg(not(feature = "sql"))]
+        Backend::Sql => Err(StoreError::Sql(
+            "this binary was built without the \"sql\" feature".to_string(),
+        )),
+    }
+}
@@ -0,0 +1,357 @@
+//! Taskwarrior import/export bridge.
+//!
+//! Taskwarrior's hook protocol feeds a single JSON task object on stdin for
+//! `on-add`, and two lines (original, then modified) for `on-modify`; the
+//! hook must echo the (possibly transformed) task back out on stdout.
+//! `run_hook` below is the entry point a small `on-add`/`on-modify`
+//! executable would call.
+//!
+//! `World::apply` never lets a caller set a task's internal id directly —
+//! ids are always minted by `Command::CreateTask`. Rather than poke
+//! `world.tasks` directly to force Taskwarrior's `uuid` into `Task.id`
+//! (which would also bypass index/undo bookkeeping that only `World`'s own
+//! methods are allowed to touch), `TaskwarriorBridge` keeps its own
+//! `uuid -> task_id` table and translates through it. Every mutation still
+//! goes through `World::apply`, so the event log, undo stack and secondary
+//! indexes stay correct.
+
+use crate::world::{Command, Event, Priority, World, WorldError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskwarriorError {
+    InvalidJson(String),
+    UnknownStatus(String),
+    Io(String),
+    Rejected(WorldError),
+}
+
+impl std::fmt::Display for TaskwarriorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskwarriorError::InvalidJson(e) => write!(f, "invalid taskwarrior json: {e}"),
+            TaskwarriorError::UnknownStatus(s) => write!(f, "unknown taskwarrior status: {s}"),
+            TaskwarriorError::Io(e) => write!(f, "hook i/o error: {e}"),
+            TaskwarriorError::Rejected(e) => write!(f, "world rejected command: {e:?}"),
+        }
+    }
+}
+
+impl From<WorldError> for TaskwarriorError {
+    fn from(e: WorldError) -> Self {
+        TaskwarriorError::Rejected(e)
+    }
+}
+
+/// A Taskwarrior task, as it appears in `task export`/hook JSON. Only the
+/// fields the bridge actually reads are modeled; Taskwarrior tasks carry
+/// plenty more (`entry`/`modified`/`due`, `tags`, `project`, `urgency`,
+/// `uda`, ...) that round-trip through `task` itself and never need to
+/// reach `World`, which has no matching fields to receive them.
+#[derive(Debug, Serialize, Deserialize)]
+struct TwTask {
+    uuid: Uuid,
+    description: String,
+    status: String,
+}
+
+/// Keeps a Taskwarrior `uuid` stable across a sync session by mapping it to
+/// whatever internal `Task.id` `World::apply` minted for it. Taskwarrior
+/// treats `uuid` as the task's durable identity; `World` mints its own ids
+/// per `CreateTask`, so this table is what keeps the two in lockstep.
+#[derive(Debug, Default, Clone)]
+pub struct TaskwarriorBridge {
+    uuid_to_task: HashMap<Uuid, Uuid>,
+}
+
+impl TaskwarriorBridge {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply one Taskwarrior task JSON object to `world`. `service_id` is
+    /// used for any task the bridge hasn't seen before — Taskwarrior has no
+    /// notion of our `Service`, so every imported task lands under the same
+    /// one. Returns the resulting revision, unchanged if the task was a
+    /// no-op (e.g. deleting a uuid the bridge never saw). A `TaskNotFound`
+    /// from `Command::DeleteTask`, which `World` returns for an unknown
+    /// `task_id`, is also treated as a no-op here.
+    ///
+    /// A `description` change on a task the bridge already knows about
+    /// isn't applied — `World` has no rename command — so the original
+    /// title is kept rather than faked.
+    pub fn import_taskwarrior(
+        &mut self,
+        world: &mut World,
+        json: &str,
+        actor: Uuid,
+        service_id: Uuid,
+    ) -> Result<u64, TaskwarriorError> {
+        let tw: TwTask =
+            serde_json::from_str(json).map_err(|e| TaskwarriorError::InvalidJson(e.to_string()))?;
+
+        match tw.status.as_str() {
+            "deleted" => {
+                if let Some(task_id) = self.uuid_to_task.remove(&tw.uuid) {
+                    match world.apply(Command::DeleteTask { task_id }, actor) {
+                        Ok(_) | Err(WorldError::TaskNotFound) => {}
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+            }
+            "completed" => {
+                let task_id = self.resolve_or_create(world, &tw, actor, service_id)?;
+                match world.apply(Command::CompleteTask { task_id }, actor) {
+                    Ok(_) | Err(WorldError::InvalidTransition) => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            "pending" => {
+                self.resolve_or_create(world, &tw, actor, service_id)?;
+            }
+            other => return Err(TaskwarriorError::UnknownStatus(other.to_string())),
+        }
+
+        Ok(world.revision)
+    }
+
+    /// Return the bridge's internal id for `tw.uuid`, creating a fresh
+    /// `Staged` task via `Command::CreateTask` (and recording the mapping)
+    /// the first time this uuid is seen.
+    fn resolve_or_create(
+        &mut self,
+        world: &mut World,
+        tw: &TwTask,
+        actor: Uuid,
+        service_id: Uuid,
+    ) -> Result<Uuid, TaskwarriorError> {
+        if let Some(&task_id) = self.uuid_to_task.get(&tw.uuid) {
+            return Ok(task_id);
+        }
+
+        let event = world.apply(
+            Command::CreateTask {
+                title: tw.description.clone(),
+                service_id,
+                priority: Priority::Medium,
+                assigned_to: None,
+                date: None,
+                start_time: None,
+                duration: None,
+            },
+            actor,
+        )?;
+        let task_id = match event {
+            Event::TaskCreated { task, .. } => task.id,
+            _ => unreachable!("Command::CreateTask always produces a TaskCreated event"),
+        };
+        self.uuid_to_task.insert(tw.uuid, task_id);
+        Ok(task_id)
+    }
+
+    /// Serialize every task `World` currently knows about back into
+    /// Taskwarrior's JSON shape, suitable for piping into `task import`.
+    /// Tasks the bridge minted itself are emitted with their original
+    /// Taskwarrior `uuid`; tasks created some other way (through the normal
+    /// scheduling UI, say) are emitted under their own internal id, since
+    /// there's no Taskwarrior uuid to recover.
+    pub fn export_taskwarrior(&self, world: &World) -> Vec<serde_json::Value> {
+        let task_to_uuid: HashMap<Uuid, Uuid> = self
+            .uuid_to_task
+            .iter()
+            .map(|(&tw_uuid, &task_id)| (task_id, tw_uuid))
+            .collect();
+
+        world
+            .tasks
+            .values()
+            .map(|task| {
+                let uuid = task_to_uuid.get(&task.id).copied().unwrap_or(task.id);
+                let status = match task.status {
+                    crate::world::TaskStatus::Completed => "completed",
+                    _ => "pending",
+                };
+                serde_json::json!({
+                    "uuid": uuid,
+                    "description": task.title,
+                    "status": status,
+                })
+            })
+            .collect()
+    }
+
+    /// Hook entry point: read the task JSON Taskwarrior feeds on stdin for
+    /// `hook_name` (`"on-add"` gets one line, `"on-modify"` gets two — the
+    /// original, then the modified task we actually care about), apply it,
+    /// and echo the same line back out on stdout as the hook protocol
+    /// requires.
+    pub fn run_hook<R: BufRead, W: Write>(
+        &mut self,
+        world: &mut World,
+        actor: Uuid,
+        service_id: Uuid,
+        hook_name: &str,
+        mut input: R,
+        mut output: W,
+    ) -> Result<(), TaskwarriorError> {
+        let mut line = String::new();
+        input
+            .read_line(&mut line)
+            .map_err(|e| TaskwarriorError::Io(e.to_string()))?;
+
+        if hook_name == "on-modify" {
+            line.clear();
+            input
+                .read_line(&mut line)
+                .map_err(|e| TaskwarriorError::Io(e.to_string()))?;
+        }
+
+        self.import_taskwarrior(world, line.trim_end(), actor, service_id)?;
+
+        output
+            .write_all(line.as_bytes())
+            .map_err(|e| TaskwarriorError::Io(e.to_string()))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::Service;
+
+    fn test_world() -> (World, Uuid) {
+        let mut w = World::new();
+        let service_id = Uuid::nil();
+        w.services.insert(service_id, Service { id: service_id, name: "Test Service".into() });
+        (w, service_id)
+    }
+
+    #[test]
+    fn import_pending_creates_staged_task() {
+        let (mut w, service_id) = test_world();
+        let mut bridge = TaskwarriorBridge::new();
+        let tw_uuid = Uuid::from_bytes([1; 16]);
+        let json = format!(r#"{{"uuid":"{tw_uuid}","description":"Buy milk","status":"pending"}}"#);
+
+        bridge.import_taskwarrior(&mut w, &json, Uuid::nil(), service_id).unwrap();
+
+        assert_eq!(w.tasks.len(), 1);
+        let task = w.tasks.values().next().unwrap();
+        assert_eq!(task.title, "Buy milk");
+        assert_eq!(task.status, crate::world::TaskStatus::Staged);
+    }
+
+    #[test]
+    fn reimporting_same_uuid_reuses_existing_task() {
+        let (mut w, service_id) = test_world();
+        let mut bridge = TaskwarriorBridge::new();
+        let tw_uuid = Uuid::from_bytes([2; 16]);
+        let json = format!(r#"{{"uuid":"{tw_uuid}","description":"Buy milk","status":"pending"}}"#);
+
+        bridge.import_taskwarrior(&mut w, &json, Uuid::nil(), service_id).unwrap();
+        bridge.import_taskwarrior(&mut w, &json, Uuid::nil(), service_id).unwrap();
+
+        assert_eq!(w.tasks.len(), 1);
+    }
+
+    #[test]
+    fn import_completed_marks_task_done() {
+        let (mut w, service_id) = test_world();
+        let mut bridge = TaskwarriorBridge::new();
+        let tw_uuid = Uuid::from_bytes([3; 16]);
+        let pending = format!(r#"{{"uuid":"{tw_uuid}","description":"Buy milk","status":"pending"}}"#);
+        let completed = format!(r#"{{"uuid":"{tw_uuid}","description":"Buy milk","status":"completed"}}"#);
+
+        bridge.import_taskwarrior(&mut w, &pending, Uuid::nil(), service_id).unwrap();
+        bridge.import_taskwarrior(&mut w, &completed, Uuid::nil(), service_id).unwrap();
+
+        let task = w.tasks.values().next().unwrap();
+        assert_eq!(task.status, crate::world::TaskStatus::Completed);
+    }
+
+    #[test]
+    fn import_delete_of_unknown_uuid_is_a_no_op() {
+        let (mut w, service_id) = test_world();
+        let mut bridge = TaskwarriorBridge::new();
+        let rev_before = w.revision;
+        let log_len_before = w.log.len();
+        let tw_uuid = Uuid::from_bytes([4; 16]);
+        let json = format!(r#"{{"uuid":"{tw_uuid}","description":"ghost","status":"deleted"}}"#);
+
+        let rev = bridge.import_taskwarrior(&mut w, &json, Uuid::nil(), service_id).unwrap();
+
+        assert_eq!(rev, w.revision);
+        assert_eq!(w.revision, rev_before);
+        assert_eq!(w.log.len(), log_len_before);
+    }
+
+    #[test]
+    fn import_delete_removes_known_task() {
+        let (mut w, service_id) = test_world();
+        let mut bridge = TaskwarriorBridge::new();
+        let tw_uuid = Uuid::from_bytes([5; 16]);
+        let pending = format!(r#"{{"uuid":"{tw_uuid}","description":"Buy milk","status":"pending"}}"#);
+        let deleted = format!(r#"{{"uuid":"{tw_uuid}","description":"Buy milk","status":"deleted"}}"#);
+
+        bridge.import_taskwarrior(&mut w, &pending, Uuid::nil(), service_id).unwrap();
+        bridge.import_taskwarrior(&mut w, &deleted, Uuid::nil(), service_id).unwrap();
+
+        assert_eq!(w.tasks.len(), 0);
+    }
+
+    #[test]
+    fn export_round_trips_uuid_and_status() {
+        let (mut w, service_id) = test_world();
+        let mut bridge = TaskwarriorBridge::new();
+        let tw_uuid = Uuid::from_bytes([6; 16]);
+        let json = format!(r#"{{"uuid":"{tw_uuid}","description":"Buy milk","status":"pending"}}"#);
+        bridge.import_taskwarrior(&mut w, &json, Uuid::nil(), service_id).unwrap();
+
+        let exported = bridge.export_taskwarrior(&w);
+        assert_eq!(exported.len(), 1);
+        assert_eq!(exported[0]["uuid"], serde_json::json!(tw_uuid));
+        assert_eq!(exported[0]["status"], "pending");
+    }
+
+    #[test]
+    fn run_hook_on_add_echoes_input_and_creates_task() {
+        let (mut w, service_id) = test_world();
+        let mut bridge = TaskwarriorBridge::new();
+        let tw_uuid = Uuid::from_bytes([7; 16]);
+        let line = format!(r#"{{"uuid":"{tw_uuid}","description":"Buy milk","status":"pending"}}"#);
+        let input = format!("{line}\n").into_bytes();
+        let mut output = Vec::new();
+
+        bridge
+            .run_hook(&mut w, Uuid::nil(), service_id, "on-add", input.as_slice(), &mut output)
+            .unwrap();
+
+        assert_eq!(w.tasks.len(), 1);
+        assert_eq!(String::from_utf8(output).unwrap().trim_end(), line);
+    }
+
+    #[test]
+    fn run_hook_on_modify_reads_second_line() {
+        let (mut w, service_id) = test_world();
+        let mut bridge = TaskwarriorBridge::new();
+        let tw_uuid = Uuid::from_bytes([8; 16]);
+        let original = format!(r#"{{"uuid":"{tw_uuid}","description":"Buy milk","status":"pending"}}"#);
+        bridge.import_taskwarrior(&mut w, &original, Uuid::nil(), service_id).unwrap();
+
+        let modified = format!(r#"{{"uuid":"{tw_uuid}","description":"Buy milk","status":"completed"}}"#);
+        let input = format!("{original}\n{modified}\n").into_bytes();
+        let mut output = Vec::new();
+
+        bridge
+            .run_hook(&mut w, Uuid::nil(), service_id, "on-modify", input.as_slice(), &mut output)
+            .unwrap();
+
+        let task = w.tasks.values().next().unwrap();
+        assert_eq!(task.status, crate::world::TaskStatus::Completed);
+        assert_eq!(String::from_utf8(output).unwrap().trim_end(), modified);
+    }
+}
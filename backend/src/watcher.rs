@@ -0,0 +1,245 @@
+//! File-watcher that reparses a `.txxt` source file into a minimal diff of
+//! `Command`s against the live `World`.
+//!
+//! There's no vendored file-notification crate in this tree (no
+//! `Cargo.toml` to pull `notify` into), so this polls the file's modified
+//! time on a background thread instead of subscribing to OS file events —
+//! same end result (reparse on change), coarser latency.
+//!
+//! The `.txxt` format itself is intentionally minimal: one task title per
+//! non-empty line, in any order. That's enough to exercise the diff/dedup
+//! behavior this module exists for without inventing a whole document
+//! grammar; a richer format can grow `parse` without touching the diffing
+//! below it.
+
+use crate::world::{Command, Event, Priority, World};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// How often the watcher re-stats the file for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Parse a `.txxt` document into its task titles, one per non-empty,
+/// trimmed line.
+fn parse(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Diff `new_titles` against `known`, the watcher's own title -> task_id
+/// table from the last successful parse, and return the `Command`s needed
+/// to bring `World` in line. A title present in both produces no command
+/// (`reparsing_identical_contents_produces_no_new_events` checks this: an
+/// unchanged document yields zero new events). `known` is left holding
+/// only the titles still present; `reparse_and_apply` fills the real task
+/// ids for newly created titles back in once `World::apply` mints them.
+fn diff(known: &mut HashMap<String, Uuid>, new_titles: &[String], service_id: Uuid) -> Vec<Command> {
+    let mut still_known: HashMap<String, Uuid> = HashMap::new();
+    let mut commands = Vec::new();
+
+    for title in new_titles {
+        if let Some(&task_id) = known.get(title) {
+            still_known.insert(title.clone(), task_id);
+        } else {
+            commands.push(Command::CreateTask {
+                title: title.clone(),
+                service_id,
+                priority: Priority::Medium,
+                assigned_to: None,
+                date: None,
+                start_time: None,
+                duration: None,
+            });
+        }
+    }
+
+    for (title, &task_id) in known.iter() {
+        if !still_known.contains_key(title) {
+            commands.push(Command::DeleteTask { task_id });
+        }
+    }
+
+    *known = still_known;
+    commands
+}
+
+/// Handle to a running watcher. Dropping it stops the background thread.
+pub struct WatchHandle {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for WatchHandle {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Start watching `path`, applying diffed commands to `world` as `actor`
+/// whenever the file's contents change, with newly created tasks filed
+/// under `service_id`. Returns a handle whose drop stops the watcher.
+pub fn watch(path: String, actor: Uuid, service_id: Uuid, world: Arc<RwLock<World>>) -> WatchHandle {
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_thread = stop.clone();
+
+    let thread = thread::spawn(move || {
+        let mut known: HashMap<String, Uuid> = HashMap::new();
+        let mut last_modified = None;
+
+        while !stop_thread.load(Ordering::SeqCst) {
+            if let Ok(metadata) = fs::metadata(&path) {
+                let modified = metadata.modified().ok();
+                if modified != last_modified {
+                    last_modified = modified;
+                    if let Ok(contents) = fs::read_to_string(&path) {
+                        reparse_and_apply(&world, actor, service_id, &contents, &mut known);
+                    }
+                }
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    });
+
+    WatchHandle { stop, thread: Some(thread) }
+}
+
+/// Reparse `contents`, diff it against `known`, and apply the resulting
+/// commands to `world` one at a time — bumping `revision` and appending to
+/// `log` exactly as `World::apply` does for any other caller. `known` is
+/// updated with the real task ids `World::apply` mints for new titles.
+fn reparse_and_apply(
+    world: &Arc<RwLock<World>>,
+    actor: Uuid,
+    service_id: Uuid,
+    contents: &str,
+    known: &mut HashMap<String, Uuid>,
+) {
+    let titles = parse(contents);
+    let commands = diff(known, &titles, service_id);
+
+    let mut world = world.write().unwrap();
+    for command in commands {
+        let title = match &command {
+            Command::CreateTask { title, .. } => Some(title.clone()),
+            _ => None,
+        };
+        if let Ok(Event::TaskCreated { task, .. }) = world.apply(command, actor) {
+            if let Some(title) = title {
+                known.insert(title, task.id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::{Service, TaskStatus};
+
+    fn test_world() -> (World, Uuid) {
+        let mut w = World::new();
+        let service_id = Uuid::nil();
+        w.services.insert(service_id, Service { id: service_id, name: "Test Service".into() });
+        (w, service_id)
+    }
+
+    #[test]
+    fn parse_splits_nonempty_trimmed_lines() {
+        let titles = parse("  Buy milk  \n\nWalk the dog\n   \nFile taxes\n");
+        assert_eq!(titles, vec!["Buy milk", "Walk the dog", "File taxes"]);
+    }
+
+    #[test]
+    fn diff_creates_tasks_for_new_titles() {
+        let mut known = HashMap::new();
+        let commands = diff(&mut known, &["Buy milk".to_string()], Uuid::nil());
+        assert_eq!(commands.len(), 1);
+        assert!(matches!(&commands[0], Command::CreateTask { title, .. } if title == "Buy milk"));
+    }
+
+    #[test]
+    fn diff_is_empty_for_unchanged_title_set() {
+        let mut known = HashMap::new();
+        known.insert("Buy milk".to_string(), Uuid::from_bytes([1; 16]));
+        let commands = diff(&mut known, &["Buy milk".to_string()], Uuid::nil());
+        assert!(commands.is_empty());
+    }
+
+    #[test]
+    fn diff_deletes_removed_titles() {
+        let mut known = HashMap::new();
+        let existing_id = Uuid::from_bytes([2; 16]);
+        known.insert("Buy milk".to_string(), existing_id);
+        let commands = diff(&mut known, &[], Uuid::nil());
+        assert_eq!(commands, vec![Command::DeleteTask { task_id: existing_id }]);
+        assert!(known.is_empty());
+    }
+
+    #[test]
+    fn reparse_and_apply_creates_exactly_one_task_per_title() {
+        let (w, service_id) = test_world();
+        let world = Arc::new(RwLock::new(w));
+        let mut known = HashMap::new();
+
+        reparse_and_apply(&world, Uuid::nil(), service_id, "Buy milk\nWalk the dog\n", &mut known);
+
+        let w = world.read().unwrap();
+        assert_eq!(w.tasks.len(), 2);
+        assert_eq!(w.revision, 2);
+        assert_eq!(known.len(), 2);
+    }
+
+    #[test]
+    fn reparsing_identical_contents_produces_no_new_events() {
+        let (w, service_id) = test_world();
+        let world = Arc::new(RwLock::new(w));
+        let mut known = HashMap::new();
+
+        reparse_and_apply(&world, Uuid::nil(), service_id, "Buy milk\n", &mut known);
+        let rev_after_first = world.read().unwrap().revision;
+        let log_len_after_first = world.read().unwrap().log.len();
+
+        reparse_and_apply(&world, Uuid::nil(), service_id, "Buy milk\n", &mut known);
+
+        let w = world.read().unwrap();
+        assert_eq!(w.revision, rev_after_first);
+        assert_eq!(w.log.len(), log_len_after_first);
+    }
+
+    #[test]
+    fn reparse_tracks_deletions_across_passes() {
+        let (w, service_id) = test_world();
+        let world = Arc::new(RwLock::new(w));
+        let mut known = HashMap::new();
+
+        reparse_and_apply(&world, Uuid::nil(), service_id, "Buy milk\nWalk the dog\n", &mut known);
+        reparse_and_apply(&world, Uuid::nil(), service_id, "Buy milk\n", &mut known);
+
+        let w = world.read().unwrap();
+        assert_eq!(w.tasks.len(), 1);
+        let remaining = w.tasks.values().next().unwrap();
+        assert_eq!(remaining.title, "Buy milk");
+        assert_eq!(remaining.status, TaskStatus::Staged);
+        assert!(w.log.iter().any(|(_, e)| matches!(e, Event::TaskDeleted { .. })));
+    }
+
+    #[test]
+    fn watch_handle_stops_thread_on_drop() {
+        let (w, service_id) = test_world();
+        let world = Arc::new(RwLock::new(w));
+        let handle = watch("/tmp/txxt_nonexistent_watch_target".to_string(), Uuid::nil(), service_id, world);
+        drop(handle); // must return promptly, not hang
+    }
+}
@@ -0,0 +1,5 @@
+pub mod health_controller;
+pub mod session_controller;
+pub mod task_controller;
+pub mod task_ws_controller;
+pub mod user_controller;
@@ -0,0 +1,98 @@
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    Json,
+};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::{
+    app_state::SharedState, authentication::auth::RequireUser, login_request::LoginRequest,
+    login_response::LoginResponse, security::hash, session::Session, user_get_response::UserGetResponse,
+};
+
+const SESSION_COOKIE: &str = "txxt_session";
+const SESSION_LIFETIME_HOURS: i64 = 24;
+
+pub struct SessionController {}
+
+impl SessionController {
+    pub async fn login(
+        State(state): State<SharedState>,
+        Json(payload): Json<LoginRequest>,
+    ) -> Result<([(header::HeaderName, HeaderValue); 1], Json<LoginResponse>), (StatusCode, String)> {
+        let mut user = None;
+        for provider in &state.auth_providers {
+            if let Some(authenticated) = provider
+                .authenticate(&payload.username, &payload.password)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            {
+                user = Some(authenticated);
+                break;
+            }
+        }
+        let user = user.ok_or((StatusCode::UNAUTHORIZED, "Invalid credentials".to_string()))?;
+
+        let secret = hash::random();
+        let secret_hash = hash::hash(&secret)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        let now = Utc::now();
+        let session = Session {
+            id: Uuid::new_v4(),
+            user_id: user.id,
+            secret_hash,
+            created_at: now,
+            expires_at: now + Duration::hours(SESSION_LIFETIME_HOURS),
+        };
+
+        state
+            .store
+            .create_session(&session)
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        let cookie = HeaderValue::from_str(&format!(
+            "{SESSION_COOKIE}={}.{secret}; Path=/; HttpOnly; SameSite=Strict; Max-Age={}",
+            session.id,
+            SESSION_LIFETIME_HOURS * 3600,
+        ))
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+        Ok((
+            [(header::SET_COOKIE, cookie)],
+            Json(LoginResponse { user: user.to_get_dto() }),
+        ))
+    }
+
+    pub async fn logout(
+        State(state): State<SharedState>,
+        headers: HeaderMap,
+    ) -> Result<StatusCode, (StatusCode, String)> {
+        if let Some((session_id, _)) = parse_session_cookie(&headers) {
+            state
+                .store
+                .delete_session(session_id)
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        }
+        Ok(StatusCode::OK)
+    }
+
+    pub async fn me(RequireUser(user): RequireUser) -> Json<UserGetResponse> {
+        Json(user.to_get_dto())
+    }
+}
+
+/// Re-derived here (rather than re-exported from `authentication::auth`) since only the
+/// session id is needed to destroy a session on logout — `RequireUser` resolves the full
+/// `User`, which `logout` doesn't need and shouldn't fail on if the session already lapsed.
+fn parse_session_cookie(headers: &HeaderMap) -> Option<(Uuid, String)> {
+    let cookie_header = headers.get(header::COOKIE)?.to_str().ok()?;
+    let prefix = format!("{SESSION_COOKIE}=");
+    let raw = cookie_header
+        .split(';')
+        .map(|kv| kv.trim())
+        .find_map(|kv| kv.strip_prefix(prefix.as_str()))?;
+    let (id, secret) = raw.split_once('.')?;
+    Some((Uuid::parse_str(id).ok()?, secret.to_string()))
+}
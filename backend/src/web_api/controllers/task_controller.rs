@@ -0,0 +1,95 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+
+use crate::{
+    app_state::SharedState,
+    data_access::data_context::{TaskFilter, TaskHistory},
+    error::ApiError,
+    task_event::TaskEvent,
+    task_history_query::TaskHistoryQuery,
+    tao_task_action_response::TaoTaskActionResponse,
+    task_query_params::TaskQueryParams,
+};
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 200;
+
+pub struct TaskController {}
+
+impl TaskController {
+    #[utoipa::path(
+        get,
+        path = "/api/tasks",
+        params(
+            ("status" = Option<crate::task_status::TaskStatus>, Query, description = "Filter by task status"),
+            ("assigned_to" = Option<uuid::Uuid>, Query, description = "Filter by assignee"),
+            ("category" = Option<String>, Query, description = "Filter by category"),
+            ("priority" = Option<crate::task_priority::TaskPriority>, Query, description = "Filter by priority"),
+            ("due_before" = Option<chrono::DateTime<chrono::Utc>>, Query, description = "Only tasks due before this timestamp"),
+            ("limit" = Option<usize>, Query, description = "Max results (default 50, capped at 200)"),
+            ("offset" = Option<usize>, Query, description = "Results to skip"),
+        ),
+        responses(
+            (status = 200, description = "Matching tasks", body = [TaoTaskActionResponse]),
+            (status = 500, description = "Internal error"),
+        )
+    )]
+    pub async fn query(
+        State(state): State<SharedState>,
+        Query(params): Query<TaskQueryParams>,
+    ) -> Result<Json<Vec<TaoTaskActionResponse>>, ApiError> {
+        let filter = TaskFilter {
+            status: params.status,
+            assigned_to: params.assigned_to,
+            category: params.category,
+            priority: params.priority,
+            due_before: params.due_before,
+            limit: params.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT),
+            offset: params.offset.unwrap_or(0),
+        };
+
+        let tasks = state.store.query_tasks(filter)?;
+
+        let assignee_ids: Vec<uuid::Uuid> = tasks.iter().filter_map(|t| t.assigned_to).collect();
+        let usernames = state.store.get_usernames(&assignee_ids)?;
+
+        let responses = tasks
+            .into_iter()
+            .map(|task| {
+                let assigned_to_name = task.assigned_to.and_then(|id| usernames.get(&id).cloned());
+                TaoTaskActionResponse {
+                    id: task.id,
+                    title: task.title,
+                    description: task.description,
+                    status: task.status,
+                    priority: task.priority,
+                    category: task.category,
+                    tags: task.tags,
+                    due_date: task.due_date,
+                    created_by: task.created_by,
+                    assigned_to: task.assigned_to,
+                    assigned_to_name,
+                    created_at: task.created_at,
+                    updated_at: task.updated_at,
+                }
+            })
+            .collect();
+
+        Ok(Json(responses))
+    }
+
+    /// `GET /api/tasks/history?id=` — the ordered audit trail for one task.
+    /// Returns `404` when the task id was never seen at all, as opposed to
+    /// `200` with an empty list for a task that simply has no recorded events.
+    pub async fn history(
+        State(state): State<SharedState>,
+        Query(params): Query<TaskHistoryQuery>,
+    ) -> Result<Json<Vec<TaskEvent>>, ApiError> {
+        match state.store.task_history(params.id)? {
+            TaskHistory::Found { events } => Ok(Json(events)),
+            TaskHistory::Empty => Err(ApiError::NotFound("task")),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+//! Task-event WebSocket handler.
+//!
+//! JSON protocol over WebSocket — each frame is a single `TaskEvent`.
+//!
+//! - Client sends one `SubscribeRequest` frame first, naming the last event
+//!   time it's already seen (or none, for a brand-new client).
+//! - Server replays every `TaskEvent` since that time, then forwards
+//!   `AppState::ws_broadcast` as task mutations happen.
+
+use axum::extract::{
+    ws::{CloseFrame, Message, WebSocket, WebSocketUpgrade},
+    State,
+};
+use axum::response::IntoResponse;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::{app_state::SharedState, task_event::TaskEvent};
+
+/// "Service Restart" close code (IANA registry, not in the base RFC), sent
+/// on graceful shutdown so a client knows to reconnect immediately rather
+/// than back off the way it would for an unexpected drop.
+const CLOSE_SERVICE_RESTART: u16 = 1012;
+
+/// First frame a client must send after upgrading, naming the last event
+/// time it's already seen. `since: None` asks for the full event log.
+#[derive(Debug, Deserialize)]
+struct SubscribeRequest {
+    since: Option<DateTime<Utc>>,
+}
+
+pub struct TaskWsController {}
+
+impl TaskWsController {
+    pub async fn ws_handler(ws: WebSocketUpgrade, State(state): State<SharedState>) -> impl IntoResponse {
+        ws.on_upgrade(move |socket| Self::handle_socket(socket, state))
+    }
+
+    async fn handle_socket(mut socket: WebSocket, state: SharedState) {
+        // Subscribe to the broadcast BEFORE replaying, so an event published
+        // while we're still reading the log isn't missed between the two.
+        let mut broadcast_rx = state.ws_broadcast.subscribe();
+        let mut shutdown_rx = state.shutdown.subscribe();
+
+        let since = match socket.recv().await {
+            Some(Ok(Message::Text(data))) => match serde_json::from_str::<SubscribeRequest>(&data) {
+                Ok(req) => req.since,
+                Err(e) => {
+                    eprintln!("bad task-ws subscribe request: {e}");
+                    return;
+                }
+            },
+            _ => return, // client must subscribe first
+        };
+
+        let events = match state.store.task_events_since(since) {
+            Ok(events) => events,
+            Err(e) => {
+                eprintln!("task-ws replay failed: {e}");
+                return;
+            }
+        };
+        // Track the last event time we've actually sent, so a broadcast-channel
+        // lag can be healed by replaying the gap instead of silently skipping it.
+        let mut last_sent = since;
+        for event in &events {
+            if Self::send_event(&mut socket, event).await.is_err() {
+                return;
+            }
+            last_sent = Some(event.occurred_at);
+        }
+
+        loop {
+            tokio::select! {
+                recv = broadcast_rx.recv() => {
+                    match recv {
+                        Ok(json) => match serde_json::from_str::<TaskEvent>(&json) {
+                            Ok(event) => {
+                                if socket.send(Message::Text(json.into())).await.is_err() {
+                                    break;
+                                }
+                                last_sent = Some(event.occurred_at);
+                            }
+                            Err(e) => eprintln!("malformed task-ws broadcast frame: {e}"),
+                        },
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {
+                            match state.store.task_events_since(last_sent) {
+                                Ok(events) => {
+                                    for event in &events {
+                                        if Self::send_event(&mut socket, event).await.is_err() {
+                                            return;
+                                        }
+                                        last_sent = Some(event.occurred_at);
+                                    }
+                                }
+                                Err(e) => {
+                                    eprintln!("task-ws catch-up failed: {e}");
+                                    break;
+                                }
+                            }
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+
+                _ = shutdown_rx.recv() => {
+                    let _ = socket
+                        .send(Message::Close(Some(CloseFrame {
+                            code: CLOSE_SERVICE_RESTART,
+                            reason: "server shutting down, reconnect shortly".into(),
+                        })))
+                        .await;
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn send_event(socket: &mut WebSocket, event: &TaskEvent) -> Result<(), axum::Error> {
+        let json = serde_json::to_string(event).unwrap();
+        socket.send(Message::Text(json.into())).await
+    }
+}
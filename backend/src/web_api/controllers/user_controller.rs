@@ -1,56 +1,261 @@
-use axum::{Json, extract::{Query, State}, http::StatusCode};
-use uuid::Uuid;
+use axum::{
+    body::Bytes,
+    extract::{Multipart, Query, State},
+    http::header,
+    response::IntoResponse,
+    Json,
+};
+use image::{imageops::FilterType, ImageFormat};
 
-use crate::{app_state::SharedState, user::User, user_add_request::UserAddRequest, user_edit_request::UserEditRequest, user_get_response::UserGetResponse};
+use crate::{app_state::SharedState, authentication::auth::RequireUser, data_access::data_context::CascadeMode, error::ApiError, security::public_id::PublicId, user::{IdentitySource, User}, user_add_request::UserAddRequest, user_edit_request::UserEditRequest, user_get_response::UserGetResponse};
+
+const AVATAR_SIZE: u32 = 256;
+const AVATAR_MAX_BYTES: usize = 5 * 1024 * 1024;
 
 pub struct UserController {}
 
 impl UserController {
+    #[utoipa::path(
+        get,
+        path = "/api/user/get",
+        params(("id" = PublicId, Query, description = "Public user id")),
+        responses(
+            (status = 200, description = "User found", body = UserGetResponse),
+            (status = 400, description = "Malformed id"),
+            (status = 404, description = "User not found"),
+            (status = 500, description = "Internal error"),
+        )
+    )]
     pub async fn get(
         State(state): State<SharedState>,
-        Query(id): Query<Uuid>) -> Result<Json<UserGetResponse>, (StatusCode, String)> {
-        match state.data_context.get_user(id) {
-            Ok(Some(user)) => return Ok(Json(user.to_get_dto())),
-            _ => return Err((StatusCode::NOT_FOUND, "User not found".to_string()))
-        }
+        Query(id): Query<PublicId>) -> Result<Json<UserGetResponse>, ApiError> {
+        let user = state.store.get_user(id.decode()?)?
+            .ok_or(ApiError::NotFound("user"))?;
+        Ok(Json(user.to_get_dto()))
     }
 
-    pub async fn get_all(State(state): State<SharedState>) -> Result<Json<Vec<UserGetResponse>>, (StatusCode, String)> {
-        state.data_context.list_users()
-            .map(|vec| Json(vec.into_iter().map(|u| u.to_get_dto()).collect()))
-            .map_err(|e| {
-                (StatusCode::INTERNAL_SERVER_ERROR, format!("Error while getting users: {}", e.to_string()))
-            })
+    #[utoipa::path(
+        get,
+        path = "/api/user/get_all",
+        responses(
+            (status = 200, description = "All users", body = [UserGetResponse]),
+            (status = 500, description = "Internal error"),
+        )
+    )]
+    pub async fn get_all(State(state): State<SharedState>) -> Result<Json<Vec<UserGetResponse>>, ApiError> {
+        let users = state.store.list_users()?;
+        Ok(Json(users.into_iter().map(|u| u.to_get_dto()).collect()))
     }
 
+    #[utoipa::path(
+        post,
+        path = "/api/user/add",
+        request_body = UserAddRequest,
+        responses(
+            (status = 200, description = "User created"),
+            (status = 400, description = "Invalid username, email or password"),
+            (status = 409, description = "Username or email already taken"),
+            (status = 500, description = "Internal error"),
+        )
+    )]
     pub async fn add(
         State(state): State<SharedState>,
-        Json(body): Json<UserAddRequest>) -> Result<(), (StatusCode, String)> {
-        let user = User::new(body);
-        match state.data_context.create_user(&user) {
-            Ok(_) => Ok(()),
-            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Error inserting user: {}", e.to_string())))
+        Json(body): Json<UserAddRequest>) -> Result<(), ApiError> {
+        if body.username.trim().is_empty() {
+            return Err(ApiError::BadRequest("username must not be empty".to_string()));
+        }
+        if body.password.trim().is_empty() {
+            return Err(ApiError::BadRequest("password must not be empty".to_string()));
         }
+        if !is_valid_email(&body.email) {
+            return Err(ApiError::BadRequest("email is not a valid address".to_string()));
+        }
+
+        let user = User::new(body)?;
+        state.store.create_user(&user)?;
+        Ok(())
     }
 
+    #[utoipa::path(
+        delete,
+        path = "/api/user/delete",
+        params(("id" = PublicId, Query, description = "Public user id")),
+        responses(
+            (status = 200, description = "User deleted"),
+            (status = 400, description = "Malformed id"),
+            (status = 404, description = "User not found"),
+            (status = 409, description = "User still has tasks assigned or created"),
+            (status = 500, description = "Internal error"),
+        )
+    )]
     pub async fn delete(
         State(state): State<SharedState>,
-        Query(id): Query<Uuid>) -> Result<(), (StatusCode, String)> {
-        match state.data_context.delete_user(id) {
-            Ok(true) => Ok(()),
-            Ok(false) => Err((StatusCode::NOT_FOUND, "User not found".to_string())),
-            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Error deleting user: {}", e.to_string())))
+        RequireUser(_actor): RequireUser,
+        Query(id): Query<PublicId>) -> Result<(), ApiError> {
+        if state.store.delete_user(id.decode()?, CascadeMode::Refuse)? {
+            Ok(())
+        } else {
+            Err(ApiError::NotFound("user"))
         }
     }
 
+    #[utoipa::path(
+        put,
+        path = "/api/user/edit",
+        params(("id" = PublicId, Query, description = "Public user id")),
+        request_body = UserEditRequest,
+        responses(
+            (status = 200, description = "User updated"),
+            (status = 400, description = "Malformed id"),
+            (status = 404, description = "User not found"),
+            (status = 500, description = "Internal error"),
+        )
+    )]
     pub async fn edit(
         State(state): State<SharedState>,
-        Query(id): Query<Uuid>,
-        Json(body): Json<UserEditRequest>) -> Result<(), (StatusCode, String)> {
-        match state.data_context.edit_user(id, body) {
-            Ok(true) => Ok(()),
-            Ok(false) => Err((StatusCode::NOT_FOUND, "User to update was not found".to_string())),
-            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, format!("Error updating user: {}", e.to_string())))
+        RequireUser(_actor): RequireUser,
+        Query(id): Query<PublicId>,
+        Json(body): Json<UserEditRequest>) -> Result<(), ApiError> {
+        let target_id = id.decode()?;
+        if body.password.is_some() {
+            let target = state.store.get_user(target_id)?.ok_or(ApiError::NotFound("user"))?;
+            if target.identity_source != IdentitySource::Local {
+                return Err(ApiError::BadRequest(
+                    "password is managed by an external identity provider".to_string(),
+                ));
+            }
+        }
+        if state.store.edit_user(target_id, body)? {
+            Ok(())
+        } else {
+            Err(ApiError::NotFound("user"))
+        }
+    }
+
+    pub async fn upload_avatar(
+        State(state): State<SharedState>,
+        RequireUser(actor): RequireUser,
+        mut multipart: Multipart) -> Result<(), ApiError> {
+        let mut field = multipart
+            .next_field()
+            .await
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?
+            .ok_or_else(|| ApiError::BadRequest("missing avatar field".to_string()))?;
+
+        let content_type = field.content_type().unwrap_or_default().to_string();
+        if !content_type.starts_with("image/") {
+            return Err(ApiError::BadRequest(format!("unsupported content type: {content_type}")));
+        }
+
+        let data = field
+            .bytes()
+            .await
+            .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+        if data.len() > AVATAR_MAX_BYTES {
+            return Err(ApiError::BadRequest("avatar exceeds maximum upload size".to_string()));
+        }
+
+        let image = image::load_from_memory(&data)
+            .map_err(|e| ApiError::BadRequest(format!("could not decode image: {e}")))?;
+        let thumbnail = image.resize_to_fill(AVATAR_SIZE, AVATAR_SIZE, FilterType::Lanczos3);
+
+        let mut png_bytes = Vec::new();
+        thumbnail
+            .write_to(&mut std::io::Cursor::new(&mut png_bytes), ImageFormat::Png)
+            .map_err(|e| ApiError::Internal(e.into()))?;
+
+        state.store.put_avatar(actor.id, &png_bytes)?;
+        state.store.mark_user_has_avatar(actor.id)?;
+        Ok(())
+    }
+
+    pub async fn get_avatar(
+        State(state): State<SharedState>,
+        Query(id): Query<PublicId>) -> Result<impl IntoResponse, ApiError> {
+        let bytes = state.store.get_avatar(id.decode()?)?
+            .ok_or(ApiError::NotFound("avatar"))?;
+        let mime = mime_guess::from_ext("png").first_or_octet_stream();
+        Ok(([(header::CONTENT_TYPE, mime.to_string())], Bytes::from(bytes)))
+    }
+}
+
+fn is_valid_email(email: &str) -> bool {
+    let Some((local, domain)) = email.split_once('@') else { return false };
+    !local.is_empty() && domain.contains('.') && !domain.starts_with('.') && !domain.ends_with('.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app_state::AppState, data_access::in_memory_store::InMemoryStore};
+    use axum::extract::Query;
+    use std::sync::Arc;
+
+    /// `InMemoryStore`-backed `AppState`, so a controller test doesn't need a
+    /// redb file on disk — see `data_access::in_memory_store`'s module docs.
+    fn test_state() -> SharedState {
+        Arc::new(AppState {
+            store: Arc::new(InMemoryStore::new()),
+            ws_broadcast: tokio::sync::broadcast::channel(16).0,
+            shutdown: tokio::sync::broadcast::channel(1).0,
+            auth_providers: Vec::new(),
+        })
+    }
+
+    fn add_request(username: &str, email: &str) -> UserAddRequest {
+        UserAddRequest {
+            username: username.to_string(),
+            password: "hunter22".to_string(),
+            email: email.to_string(),
         }
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn add_then_get_round_trips() {
+        let state = test_state();
+        UserController::add(State(state.clone()), Json(add_request("alice", "alice@example.com")))
+            .await
+            .unwrap();
+
+        let id = state.store.get_user_by_username("alice").unwrap().unwrap().id;
+        let response = UserController::get(State(state), Query(PublicId::encode(id))).await.unwrap();
+        assert_eq!(response.username, "alice");
+    }
+
+    #[tokio::test]
+    async fn add_rejects_duplicate_username() {
+        let state = test_state();
+        UserController::add(State(state.clone()), Json(add_request("bob", "bob@example.com")))
+            .await
+            .unwrap();
+
+        let result = UserController::add(State(state), Json(add_request("bob", "bob2@example.com"))).await;
+        assert!(matches!(result, Err(ApiError::Conflict(_))));
+    }
+
+    #[tokio::test]
+    async fn get_unknown_user_is_not_found() {
+        let state = test_state();
+        let result = UserController::get(State(state), Query(PublicId::encode(uuid::Uuid::new_v4()))).await;
+        assert!(matches!(result, Err(ApiError::NotFound("user"))));
+    }
+
+    #[tokio::test]
+    async fn delete_removes_user() {
+        let state = test_state();
+        UserController::add(State(state.clone()), Json(add_request("carol", "carol@example.com")))
+            .await
+            .unwrap();
+        let id = state.store.get_user_by_username("carol").unwrap().unwrap().id;
+
+        UserController::delete(
+            State(state.clone()),
+            RequireUser(state.store.get_user(id).unwrap().unwrap()),
+            Query(PublicId::encode(id)),
+        )
+        .await
+        .unwrap();
+
+        assert!(state.store.get_user(id).unwrap().is_none());
+    }
+}
@@ -0,0 +1,126 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+/// Uniform error type for web_api handlers. Carries enough to render a
+/// consistent `{ status, code, message }` JSON body instead of the
+/// hand-formatted `(StatusCode, String)` pairs the controllers used to return.
+#[derive(Debug)]
+pub enum ApiError {
+    Internal(anyhow::Error),
+    NotFound(&'static str),
+    BadRequest(String),
+    InvalidCredentials,
+    Conflict(String),
+}
+
+#[derive(Serialize)]
+struct ApiErrorBody {
+    status: u16,
+    code: &'static str,
+    message: String,
+}
+
+impl ApiError {
+    fn code(&self) -> &'static str {
+        match self {
+            ApiError::Internal(_) => "internal_error",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::InvalidCredentials => "invalid_credentials",
+            ApiError::Conflict(_) => "conflict",
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::InvalidCredentials => StatusCode::UNAUTHORIZED,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            ApiError::Internal(e) => e.to_string(),
+            ApiError::NotFound(what) => format!("{what} not found"),
+            ApiError::BadRequest(msg) => msg.clone(),
+            ApiError::InvalidCredentials => "invalid credentials".to_string(),
+            ApiError::Conflict(msg) => msg.clone(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let body = ApiErrorBody {
+            status: status.as_u16(),
+            code: self.code(),
+            message: self.message(),
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+impl From<redb::Error> for ApiError {
+    fn from(e: redb::Error) -> Self {
+        ApiError::Internal(e.into())
+    }
+}
+
+impl From<argon2::password_hash::Error> for ApiError {
+    fn from(e: argon2::password_hash::Error) -> Self {
+        ApiError::Internal(anyhow::anyhow!(e.to_string()))
+    }
+}
+
+impl From<crate::data_access::data_context::CreateUserError> for ApiError {
+    fn from(e: crate::data_access::data_context::CreateUserError) -> Self {
+        use crate::data_access::data_context::CreateUserError;
+        match e {
+            CreateUserError::Storage(e) => ApiError::Internal(e.into()),
+            CreateUserError::UsernameTaken => ApiError::Conflict("username already taken".to_string()),
+            CreateUserError::EmailTaken => ApiError::Conflict("email already taken".to_string()),
+        }
+    }
+}
+
+impl From<crate::data_access::data_context::DbError> for ApiError {
+    fn from(e: crate::data_access::data_context::DbError) -> Self {
+        use crate::data_access::data_context::DbError;
+        match e {
+            DbError::Storage(e) => ApiError::Internal(e.into()),
+            DbError::DanglingReference(_) => ApiError::BadRequest(e.to_string()),
+            DbError::HasDependents(_) => ApiError::Conflict(e.to_string()),
+        }
+    }
+}
+
+impl From<crate::data_access::store::StoreError> for ApiError {
+    fn from(e: crate::data_access::store::StoreError) -> Self {
+        use crate::data_access::store::StoreError;
+        match e {
+            StoreError::Storage(msg) => ApiError::Internal(anyhow::anyhow!(msg)),
+            StoreError::UsernameTaken => ApiError::Conflict("username already taken".to_string()),
+            StoreError::EmailTaken => ApiError::Conflict("email already taken".to_string()),
+            StoreError::DanglingReference(_) => ApiError::BadRequest(e.to_string()),
+            StoreError::HasDependents(_) => ApiError::Conflict(e.to_string()),
+        }
+    }
+}
+
+impl From<crate::authentication::auth_provider::AuthError> for ApiError {
+    fn from(e: crate::authentication::auth_provider::AuthError) -> Self {
+        use crate::authentication::auth_provider::AuthError;
+        match e {
+            AuthError::InvalidCredentials => ApiError::InvalidCredentials,
+            AuthError::Internal(e) => ApiError::Internal(e),
+        }
+    }
+}
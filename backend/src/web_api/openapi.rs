@@ -0,0 +1,26 @@
+use utoipa::OpenApi;
+
+use crate::{
+    security::public_id::PublicId, tao_task_action_response::TaoTaskActionResponse,
+    task_priority::TaskPriority, task_status::TaskStatus, user_add_request::UserAddRequest,
+    user_edit_request::UserEditRequest, user_get_response::UserGetResponse,
+};
+
+use super::controllers::{task_controller::TaskController, user_controller::UserController};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        UserController::get,
+        UserController::get_all,
+        UserController::add,
+        UserController::delete,
+        UserController::edit,
+        TaskController::query,
+    ),
+    components(schemas(
+        UserGetResponse, UserAddRequest, UserEditRequest, PublicId,
+        TaoTaskActionResponse, TaskStatus, TaskPriority,
+    ))
+)]
+pub struct ApiDoc;
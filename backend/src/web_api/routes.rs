@@ -1,5 +1,7 @@
 pub mod authentication_routes;
+pub mod docs_routes;
 pub mod health_routes;
+pub mod task_routes;
 pub mod user_routes;
 
 use std::sync::Arc;
@@ -13,4 +15,6 @@ pub fn map_routes(app_state: Arc<AppState>) -> Router {
         .nest(format!("{}", API_PATH).as_str(), health_routes::get_router(app_state.clone()))
         .nest(format!("{}", API_PATH).as_str(), authentication_routes::get_router(app_state.clone()))
         .nest(format!("{}", API_PATH).as_str(), user_routes::get_router(app_state.clone()))
+        .nest(format!("{}", API_PATH).as_str(), task_routes::get_router(app_state.clone()))
+        .merge(docs_routes::get_router(app_state.clone()))
 }
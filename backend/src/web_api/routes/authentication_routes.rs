@@ -1,10 +1,13 @@
 use std::sync::Arc;
-use axum::{Router, routing::{post}};
-use crate::{app_state::AppState, authentication_controller::AuthenticationController};
+use axum::{Router, routing::{get, post}};
+use crate::{app_state::AppState, session_controller::SessionController};
 
 pub const ROUTER_PATH: &str = "/authentication";
 
 pub fn get_router(app_state:Arc<AppState>) -> Router {
     Router::new()
-        .route(format!("{}/login", ROUTER_PATH).as_str(), post(AuthenticationController::login)).with_state(app_state)
-}
\ No newline at end of file
+        .route(format!("{}/login", ROUTER_PATH).as_str(), post(SessionController::login))
+        .route(format!("{}/logout", ROUTER_PATH).as_str(), post(SessionController::logout))
+        .route(format!("{}/me", ROUTER_PATH).as_str(), get(SessionController::me))
+        .with_state(app_state)
+}
@@ -0,0 +1,11 @@
+use std::sync::Arc;
+use axum::Router;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+use crate::app_state::AppState;
+
+use super::super::openapi::ApiDoc;
+
+pub fn get_router(_app_state: Arc<AppState>) -> Router {
+    Router::new().merge(SwaggerUi::new("/api-docs/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
+}
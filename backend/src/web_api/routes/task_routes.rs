@@ -0,0 +1,16 @@
+use std::sync::Arc;
+use axum::{Router, middleware, routing::get};
+use crate::{app_state::AppState, authentication::auth::auth_middleware, task_controller::TaskController, task_ws_controller::TaskWsController};
+
+pub const ROUTER_PATH: &str = "/tasks";
+pub const HISTORY_ROUTER_PATH: &str = "/tasks/history";
+pub const WS_ROUTER_PATH: &str = "/tasks/ws";
+
+pub fn get_router(app_state: Arc<AppState>) -> Router {
+    Router::new()
+        .route(ROUTER_PATH, get(TaskController::query))
+        .route(HISTORY_ROUTER_PATH, get(TaskController::history))
+        .route(WS_ROUTER_PATH, get(TaskWsController::ws_handler))
+        .layer(middleware::from_fn_with_state(app_state.clone(), auth_middleware))
+        .with_state(app_state.clone())
+}
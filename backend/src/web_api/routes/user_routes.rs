@@ -11,6 +11,8 @@ pub fn get_router(app_state:Arc<AppState>) -> Router {
         .route(format!("{}/add", ROUTER_PATH).as_str(), post(UserController::add))
         .route(format!("{}/delete", ROUTER_PATH).as_str(), delete(UserController::delete))
         .route(format!("{}/edit", ROUTER_PATH).as_str(), put(UserController::edit))
+        .route(format!("{}/avatar", ROUTER_PATH).as_str(), post(UserController::upload_avatar))
+        .route(format!("{}/avatar", ROUTER_PATH).as_str(), get(UserController::get_avatar))
         .layer(middleware::from_fn_with_state(app_state.clone(), auth_middleware))
         .with_state(app_state.clone())
 }
\ No newline at end of file
@@ -10,12 +10,18 @@
 use crate::world::{Command, Event, Priority, Task, Service, World};
 use uuid::Uuid;
 
+#[cfg(feature = "wire-auth")]
+pub mod auth;
+
 // ── Layout constants ───────────────────────────────────────────
 // These are shared knowledge between server and client.
 // The JS side hardcodes the same values.
 
 // Message types (first byte of every WS frame)
 pub mod msg {
+    // Handshake (both directions)
+    pub const HELLO: u8           = 0x00;
+
     // Server → Client
     pub const SNAPSHOT: u8        = 0x01;
     pub const TASK_CREATED: u8    = 0x02;
@@ -24,6 +30,12 @@ pub mod msg {
     pub const TASK_UNSCHEDULED: u8 = 0x05;
     pub const TASK_COMPLETED: u8  = 0x06;
     pub const TASK_DELETED: u8    = 0x07;
+    pub const BATCH: u8           = 0x08;
+    pub const SNAPSHOT_COMPRESSED: u8 = 0x09;
+    pub const DEPENDENCY_ADDED: u8   = 0x0A;
+    pub const DEPENDENCY_REMOVED: u8 = 0x0B;
+    pub const TASK_FAILED: u8     = 0x0C;
+    pub const TASK_RETRIED: u8    = 0x0D;
     pub const ERROR: u8           = 0xFF;
 
     // Client → Server
@@ -33,6 +45,14 @@ pub mod msg {
     pub const CMD_UNSCHEDULE_TASK: u8 = 0x13;
     pub const CMD_COMPLETE_TASK: u8  = 0x14;
     pub const CMD_DELETE_TASK: u8    = 0x15;
+    pub const CMD_BATCH: u8          = 0x16;
+    pub const CMD_RESYNC: u8         = 0x17;
+    pub const CMD_ADD_DEPENDENCY: u8    = 0x18;
+    pub const CMD_REMOVE_DEPENDENCY: u8 = 0x19;
+    pub const CMD_UNDO: u8 = 0x1A;
+    pub const CMD_REDO: u8 = 0x1B;
+    pub const CMD_FAIL_TASK: u8  = 0x1C;
+    pub const CMD_RETRY_TASK: u8 = 0x1D;
 }
 
 /// Task record stride (bytes).
@@ -47,10 +67,14 @@ pub mod msg {
 /// [24..40]   service_id (UUID, 16 bytes)
 /// [40..56]   assigned_to (UUID, 16 bytes, zeroed = unassigned)
 /// [56..184]  title (128 bytes, UTF-8, zero-padded)
-/// [184..192] _reserved
+/// [184..186] retry_count (u16 LE)
+/// [186..250] failure reason (64 bytes, UTF-8, zero-padded; meaningful only
+///            when status == Failed)
+/// [250..256] _reserved
 /// ```
-pub const TASK_STRIDE: usize = 192;
+pub const TASK_STRIDE: usize = 256;
 pub const TITLE_MAX: usize = 128;
+pub const FAILURE_MAX: usize = 64;
 
 /// Service record stride (bytes).
 ///
@@ -61,6 +85,55 @@ pub const TITLE_MAX: usize = 128;
 pub const SERVICE_STRIDE: usize = 80;
 pub const SERVICE_NAME_MAX: usize = 64;
 
+/// Wire protocol version. Bumped whenever a layout or message type changes
+/// in a way that would misparse under an older/newer peer's assumptions.
+pub const PROTOCOL_VERSION: u16 = 2;
+
+/// Capability bits negotiated in the `HELLO` handshake. A peer only uses a
+/// feature (authenticated frames, batching, compressed snapshots) if both
+/// sides advertised it, so old and new peers degrade gracefully instead of
+/// misinterpreting a record under the wrong schema.
+pub mod capabilities {
+    pub const AUTH_FRAMES: u32         = 1 << 0;
+    pub const BATCHING: u32            = 1 << 1;
+    pub const COMPRESSED_SNAPSHOTS: u32 = 1 << 2;
+}
+
+/// Capability bits this build actually honors. `handle_socket` ANDs this
+/// with whatever the client's `HELLO` requested and echoes back the
+/// intersection, so a client never believes it negotiated a feature (e.g.
+/// `AUTH_FRAMES` without the `wire-auth` feature compiled in) that this
+/// server never acts on.
+pub fn supported_capabilities() -> u32 {
+    let mut caps = capabilities::BATCHING | capabilities::COMPRESSED_SNAPSHOTS;
+    #[cfg(feature = "wire-auth")]
+    {
+        caps |= capabilities::AUTH_FRAMES;
+    }
+    caps
+}
+
+/// `HELLO` frame size (bytes).
+///
+/// ```text
+/// [0]        msg type (0x00)
+/// [1..3]     protocol_version (u16 LE)
+/// [3..5]     task_stride (u16 LE)
+/// [5..7]     service_stride (u16 LE)
+/// [7..11]    capabilities (u32 LE bitmap)
+/// ```
+pub const HELLO_LEN: usize = 11;
+
+/// A parsed `HELLO` handshake frame, sent by the client and echoed by the
+/// server once capabilities have been negotiated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hello {
+    pub protocol_version: u16,
+    pub task_stride: u16,
+    pub service_stride: u16,
+    pub capabilities: u32,
+}
+
 /// Snapshot header size (bytes).
 ///
 /// ```text
@@ -82,40 +155,273 @@ pub const SNAPSHOT_HEADER: usize = 17;
 /// ```
 pub const EVENT_HEADER: usize = 25;
 
+// ── Handshake ───────────────────────────────────────────────────
+
+/// Pack a `HELLO` frame advertising this peer's protocol version, record
+/// strides, and negotiated capability bitmap. Sent first by the client;
+/// echoed by the server once it has validated the client's `HELLO`.
+pub fn pack_hello(capabilities: u32) -> Vec<u8> {
+    let mut buf = vec![0u8; HELLO_LEN];
+    buf[0] = msg::HELLO;
+    buf[1..3].copy_from_slice(&PROTOCOL_VERSION.to_le_bytes());
+    buf[3..5].copy_from_slice(&(TASK_STRIDE as u16).to_le_bytes());
+    buf[5..7].copy_from_slice(&(SERVICE_STRIDE as u16).to_le_bytes());
+    buf[7..11].copy_from_slice(&capabilities.to_le_bytes());
+    buf
+}
+
+/// Unpack a `HELLO` frame.
+pub fn unpack_hello(data: &[u8]) -> Result<Hello, WireError> {
+    if data.len() < HELLO_LEN || data[0] != msg::HELLO {
+        return Err(WireError::TooShort);
+    }
+    Ok(Hello {
+        protocol_version: u16::from_le_bytes([data[1], data[2]]),
+        task_stride: u16::from_le_bytes([data[3], data[4]]),
+        service_stride: u16::from_le_bytes([data[5], data[6]]),
+        capabilities: u32::from_le_bytes([data[7], data[8], data[9], data[10]]),
+    })
+}
+
+/// Validate a peer's `HELLO` against our own protocol version and record
+/// layout, rejecting anything that would otherwise be silently misparsed.
+pub fn validate_hello(hello: &Hello) -> Result<(), WireError> {
+    if hello.protocol_version != PROTOCOL_VERSION {
+        return Err(WireError::VersionMismatch {
+            expected: PROTOCOL_VERSION,
+            got: hello.protocol_version,
+        });
+    }
+    if hello.task_stride as usize != TASK_STRIDE || hello.service_stride as usize != SERVICE_STRIDE {
+        return Err(WireError::InvalidField("stride"));
+    }
+    Ok(())
+}
+
 // ── Packing (Server → Client) ──────────────────────────────────
+//
+// Every `pack_*_into` function below writes into a caller-provided buffer
+// and allocates nothing; the `snapshot_len`/`event_len` helpers let a
+// caller size that buffer up front. The plain `pack_*` functions are thin
+// `Vec`-allocating wrappers kept for convenience on the `std` server side —
+// the same `_into` core is what a `no_std`/WASM build of this module would
+// call directly to avoid ever allocating on the hot path.
+
+/// Bytes required to hold `pack_snapshot_into`'s output for this world.
+pub fn snapshot_len(world: &World) -> usize {
+    SNAPSHOT_HEADER + world.tasks.len() * TASK_STRIDE + world.services.len() * SERVICE_STRIDE
+}
 
-/// Pack a full world snapshot into a binary frame.
-pub fn pack_snapshot(world: &World) -> Vec<u8> {
+/// Pack a full world snapshot into `buf`, returning the number of bytes
+/// written. `buf` must be at least `snapshot_len(world)` bytes.
+pub fn pack_snapshot_into(world: &World, buf: &mut [u8]) -> usize {
     let task_count = world.tasks.len();
     let service_count = world.services.len();
-    let size = SNAPSHOT_HEADER
-        + task_count * TASK_STRIDE
-        + service_count * SERVICE_STRIDE;
 
-    let mut buf = vec![0u8; size];
-
-    // Header
     buf[0] = msg::SNAPSHOT;
     buf[1..9].copy_from_slice(&world.revision.to_le_bytes());
     buf[9..13].copy_from_slice(&(task_count as u32).to_le_bytes());
     buf[13..17].copy_from_slice(&(service_count as u32).to_le_bytes());
 
-    // Task records
     let mut offset = SNAPSHOT_HEADER;
     for task in world.tasks.values() {
         pack_task(&mut buf[offset..offset + TASK_STRIDE], task);
         offset += TASK_STRIDE;
     }
-
-    // Service records
     for service in world.services.values() {
         pack_service(&mut buf[offset..offset + SERVICE_STRIDE], service);
         offset += SERVICE_STRIDE;
     }
 
+    offset
+}
+
+/// Pack a full world snapshot into a freshly allocated binary frame.
+pub fn pack_snapshot(world: &World) -> Vec<u8> {
+    let mut buf = vec![0u8; snapshot_len(world)];
+    pack_snapshot_into(world, &mut buf);
     buf
 }
 
+// ── Column-transposed, RLE-compressed snapshot mode ────────────
+//
+// `pack_snapshot`'s row-major records are mostly zero padding (reserved
+// bytes, nil `assigned_to`, zero-padded titles). Transposing into column
+// order clusters those zero runs so a byte-level RLE pass shrinks large
+// snapshots substantially. The decoder reverses both steps to recover the
+// exact bytes `pack_snapshot` would have produced, so a caller parses it
+// with the same fixed offsets either way.
+
+/// `(offset, len)` spans partitioning `TASK_STRIDE` into columns, in the
+/// order they're written in `pack_task`.
+const TASK_COLUMNS: &[(usize, usize)] = &[
+    (0, 16),   // id
+    (16, 1),   // status
+    (17, 1),   // priority
+    (18, 2),   // date
+    (20, 2),   // start_time
+    (22, 2),   // duration
+    (24, 16),  // service_id
+    (40, 16),  // assigned_to
+    (56, 128), // title
+    (184, 2),  // retry_count
+    (186, 64), // failure reason
+    (250, 6),  // reserved
+];
+
+/// `(offset, len)` spans partitioning `SERVICE_STRIDE` into columns.
+const SERVICE_COLUMNS: &[(usize, usize)] = &[
+    (0, 16), // id
+    (16, 64), // name
+];
+
+/// Header size for the `SNAPSHOT_COMPRESSED` frame (bytes).
+///
+/// ```text
+/// [0]        msg type (0x09)
+/// [1..9]     revision (u64 LE)
+/// [9..13]    task_count (u32 LE)
+/// [13..17]   service_count (u32 LE)
+/// [17..21]   uncompressed_len (u32 LE) — size of the transposed body before RLE
+/// [21..]     RLE-compressed, column-transposed task then service records
+/// ```
+pub const COMPRESSED_SNAPSHOT_HEADER: usize = 21;
+
+fn transpose_rows(rows: &[u8], stride: usize, count: usize, columns: &[(usize, usize)]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(rows.len());
+    for &(offset, len) in columns {
+        for i in 0..count {
+            out.extend_from_slice(&rows[i * stride + offset..i * stride + offset + len]);
+        }
+    }
+    out
+}
+
+fn untranspose_rows(columns_buf: &[u8], stride: usize, count: usize, columns: &[(usize, usize)]) -> Vec<u8> {
+    let mut out = vec![0u8; stride * count];
+    let mut cursor = 0;
+    for &(offset, len) in columns {
+        for i in 0..count {
+            out[i * stride + offset..i * stride + offset + len]
+                .copy_from_slice(&columns_buf[cursor..cursor + len]);
+            cursor += len;
+        }
+    }
+    out
+}
+
+/// Encode a byte run as `[0x00][zero_run_len: u16 LE]` for runs of zero
+/// bytes, or `[literal_count: u8][literal bytes]` for runs of non-zero
+/// bytes, whichever the span actually is.
+fn rle_encode(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        if data[i] == 0 {
+            let start = i;
+            while i < data.len() && data[i] == 0 && i - start < u16::MAX as usize {
+                i += 1;
+            }
+            out.push(0x00);
+            out.extend_from_slice(&((i - start) as u16).to_le_bytes());
+        } else {
+            let start = i;
+            while i < data.len() && data[i] != 0 && i - start < 255 {
+                i += 1;
+            }
+            out.push((i - start) as u8);
+            out.extend_from_slice(&data[start..i]);
+        }
+    }
+    out
+}
+
+fn rle_decode(data: &[u8], expected_len: usize) -> Result<Vec<u8>, WireError> {
+    let mut out = Vec::with_capacity(expected_len);
+    let mut i = 0;
+    while i < data.len() {
+        let marker = data[i];
+        i += 1;
+        if marker == 0 {
+            if i + 2 > data.len() {
+                return Err(WireError::TooShort);
+            }
+            let run_len = u16::from_le_bytes([data[i], data[i + 1]]) as usize;
+            i += 2;
+            out.resize(out.len() + run_len, 0);
+        } else {
+            let run_len = marker as usize;
+            if i + run_len > data.len() {
+                return Err(WireError::TooShort);
+            }
+            out.extend_from_slice(&data[i..i + run_len]);
+            i += run_len;
+        }
+    }
+    if out.len() != expected_len {
+        return Err(WireError::InvalidField("rle_length"));
+    }
+    Ok(out)
+}
+
+/// Pack a column-transposed, RLE-compressed snapshot. Falls back to the
+/// plain `pack_snapshot` frame when compression doesn't actually shrink it
+/// (small worlds, where header + RLE markers outweigh the savings).
+pub fn pack_snapshot_compressed(world: &World) -> Vec<u8> {
+    let uncompressed = pack_snapshot(world);
+    let task_count = world.tasks.len();
+    let service_count = world.services.len();
+
+    let task_rows = &uncompressed[SNAPSHOT_HEADER..SNAPSHOT_HEADER + task_count * TASK_STRIDE];
+    let service_rows = &uncompressed[SNAPSHOT_HEADER + task_count * TASK_STRIDE..];
+
+    let mut transposed = transpose_rows(task_rows, TASK_STRIDE, task_count, TASK_COLUMNS);
+    transposed.extend(transpose_rows(service_rows, SERVICE_STRIDE, service_count, SERVICE_COLUMNS));
+
+    let rle = rle_encode(&transposed);
+
+    let mut compressed = Vec::with_capacity(COMPRESSED_SNAPSHOT_HEADER + rle.len());
+    compressed.push(msg::SNAPSHOT_COMPRESSED);
+    compressed.extend_from_slice(&world.revision.to_le_bytes());
+    compressed.extend_from_slice(&(task_count as u32).to_le_bytes());
+    compressed.extend_from_slice(&(service_count as u32).to_le_bytes());
+    compressed.extend_from_slice(&(transposed.len() as u32).to_le_bytes());
+    compressed.extend_from_slice(&rle);
+
+    if compressed.len() < uncompressed.len() {
+        compressed
+    } else {
+        uncompressed
+    }
+}
+
+/// Reverse RLE and un-transposition, yielding the exact byte layout
+/// `pack_snapshot` would have produced for the same world.
+pub fn unpack_snapshot_compressed(data: &[u8]) -> Result<Vec<u8>, WireError> {
+    if data.len() < COMPRESSED_SNAPSHOT_HEADER || data[0] != msg::SNAPSHOT_COMPRESSED {
+        return Err(WireError::TooShort);
+    }
+    let revision = u64::from_le_bytes(data[1..9].try_into().unwrap());
+    let task_count = u32::from_le_bytes(data[9..13].try_into().unwrap()) as usize;
+    let service_count = u32::from_le_bytes(data[13..17].try_into().unwrap()) as usize;
+    let uncompressed_len = u32::from_le_bytes(data[17..21].try_into().unwrap()) as usize;
+
+    let transposed = rle_decode(&data[COMPRESSED_SNAPSHOT_HEADER..], uncompressed_len)?;
+
+    let task_columns_len = task_count * TASK_STRIDE;
+    let task_rows = untranspose_rows(&transposed[..task_columns_len], TASK_STRIDE, task_count, TASK_COLUMNS);
+    let service_rows = untranspose_rows(&transposed[task_columns_len..], SERVICE_STRIDE, service_count, SERVICE_COLUMNS);
+
+    let mut out = Vec::with_capacity(SNAPSHOT_HEADER + task_rows.len() + service_rows.len());
+    out.push(msg::SNAPSHOT);
+    out.extend_from_slice(&revision.to_le_bytes());
+    out.extend_from_slice(&(task_count as u32).to_le_bytes());
+    out.extend_from_slice(&(service_count as u32).to_le_bytes());
+    out.extend_from_slice(&task_rows);
+    out.extend_from_slice(&service_rows);
+    Ok(out)
+}
+
 /// Pack a single task into a fixed-stride record.
 fn pack_task(buf: &mut [u8], task: &Task) {
     buf[0..16].copy_from_slice(task.id.as_bytes());
@@ -132,6 +438,13 @@ fn pack_task(buf: &mut [u8], task: &Task) {
     let title_bytes = task.title.as_bytes();
     let len = title_bytes.len().min(TITLE_MAX);
     buf[56..56 + len].copy_from_slice(&title_bytes[..len]);
+    buf[184..186].copy_from_slice(&task.retry_count.to_le_bytes());
+    // Failure reason: truncate to FAILURE_MAX, zero-pad
+    if let Some(failure) = &task.failure {
+        let failure_bytes = failure.as_bytes();
+        let len = failure_bytes.len().min(FAILURE_MAX);
+        buf[186..186 + len].copy_from_slice(&failure_bytes[..len]);
+    }
     // Rest is already zeroed (vec![0u8; ...])
 }
 
@@ -143,67 +456,307 @@ fn pack_service(buf: &mut [u8], service: &Service) {
     buf[16..16 + len].copy_from_slice(&name_bytes[..len]);
 }
 
-/// Pack an event into a binary frame.
-pub fn pack_event(event: &Event) -> Vec<u8> {
+/// Bytes required to hold `pack_event_into`'s output for this event.
+pub fn event_len(event: &Event) -> usize {
+    match event {
+        Event::TaskCreated { .. } => 1 + 8 + TASK_STRIDE,
+        Event::TaskScheduled { .. } | Event::TaskMoved { .. } => EVENT_HEADER + 6,
+        Event::TaskUnscheduled { .. } | Event::TaskCompleted { .. } | Event::TaskDeleted { .. } => EVENT_HEADER,
+        Event::DependencyAdded { .. } | Event::DependencyRemoved { .. } => EVENT_HEADER + 16,
+        Event::TaskFailed { reason, .. } => EVENT_HEADER + reason.len(),
+        Event::TaskRetried { .. } => EVENT_HEADER + 2,
+    }
+}
+
+/// Pack an event into `buf`, returning the number of bytes written. `buf`
+/// must be at least `event_len(event)` bytes.
+pub fn pack_event_into(event: &Event, buf: &mut [u8]) -> usize {
     match event {
         Event::TaskCreated { revision, task } => {
-            let mut buf = vec![0u8; 1 + 8 + TASK_STRIDE];
             buf[0] = msg::TASK_CREATED;
             buf[1..9].copy_from_slice(&revision.to_le_bytes());
             pack_task(&mut buf[9..9 + TASK_STRIDE], task);
-            buf
         }
 
         Event::TaskScheduled { revision, task_id, date, start_time, duration } => {
-            let mut buf = vec![0u8; EVENT_HEADER + 6];
             buf[0] = msg::TASK_SCHEDULED;
             buf[1..9].copy_from_slice(&revision.to_le_bytes());
             buf[9..25].copy_from_slice(task_id.as_bytes());
             buf[25..27].copy_from_slice(&date.to_le_bytes());
             buf[27..29].copy_from_slice(&start_time.to_le_bytes());
             buf[29..31].copy_from_slice(&duration.to_le_bytes());
-            buf
         }
 
         Event::TaskMoved { revision, task_id, date, start_time, duration } => {
-            let mut buf = vec![0u8; EVENT_HEADER + 6];
             buf[0] = msg::TASK_MOVED;
             buf[1..9].copy_from_slice(&revision.to_le_bytes());
             buf[9..25].copy_from_slice(task_id.as_bytes());
             buf[25..27].copy_from_slice(&date.to_le_bytes());
             buf[27..29].copy_from_slice(&start_time.to_le_bytes());
             buf[29..31].copy_from_slice(&duration.to_le_bytes());
-            buf
         }
 
         Event::TaskUnscheduled { revision, task_id } => {
-            let mut buf = vec![0u8; EVENT_HEADER];
             buf[0] = msg::TASK_UNSCHEDULED;
             buf[1..9].copy_from_slice(&revision.to_le_bytes());
             buf[9..25].copy_from_slice(task_id.as_bytes());
-            buf
         }
 
         Event::TaskCompleted { revision, task_id } => {
-            let mut buf = vec![0u8; EVENT_HEADER];
             buf[0] = msg::TASK_COMPLETED;
             buf[1..9].copy_from_slice(&revision.to_le_bytes());
             buf[9..25].copy_from_slice(task_id.as_bytes());
-            buf
         }
 
         Event::TaskDeleted { revision, task_id } => {
-            let mut buf = vec![0u8; EVENT_HEADER];
             buf[0] = msg::TASK_DELETED;
             buf[1..9].copy_from_slice(&revision.to_le_bytes());
             buf[9..25].copy_from_slice(task_id.as_bytes());
-            buf
+        }
+
+        Event::DependencyAdded { revision, task_id, depends_on } => {
+            buf[0] = msg::DEPENDENCY_ADDED;
+            buf[1..9].copy_from_slice(&revision.to_le_bytes());
+            buf[9..25].copy_from_slice(task_id.as_bytes());
+            buf[25..41].copy_from_slice(depends_on.as_bytes());
+        }
+
+        Event::DependencyRemoved { revision, task_id, depends_on } => {
+            buf[0] = msg::DEPENDENCY_REMOVED;
+            buf[1..9].copy_from_slice(&revision.to_le_bytes());
+            buf[9..25].copy_from_slice(task_id.as_bytes());
+            buf[25..41].copy_from_slice(depends_on.as_bytes());
+        }
+
+        Event::TaskFailed { revision, task_id, reason } => {
+            buf[0] = msg::TASK_FAILED;
+            buf[1..9].copy_from_slice(&revision.to_le_bytes());
+            buf[9..25].copy_from_slice(task_id.as_bytes());
+            let reason_bytes = reason.as_bytes();
+            buf[25..25 + reason_bytes.len()].copy_from_slice(reason_bytes);
+        }
+
+        Event::TaskRetried { revision, task_id, retry_count } => {
+            buf[0] = msg::TASK_RETRIED;
+            buf[1..9].copy_from_slice(&revision.to_le_bytes());
+            buf[9..25].copy_from_slice(task_id.as_bytes());
+            buf[25..27].copy_from_slice(&retry_count.to_le_bytes());
+        }
+    }
+    event_len(event)
+}
+
+/// Pack an event into a freshly allocated binary frame.
+pub fn pack_event(event: &Event) -> Vec<u8> {
+    let mut buf = vec![0u8; event_len(event)];
+    pack_event_into(event, &mut buf);
+    buf
+}
+
+/// Pack several delta events into a single `BATCH` frame under one trailing
+/// revision, so a multi-task mutation is broadcast as one WS message.
+///
+/// ```text
+/// [0]        msg type (0x08)
+/// [1..9]     revision (u64 LE, the last event's revision)
+/// [9..13]    event_count (u32 LE)
+/// [13..]     event_count sub-frames, each [len: u16 LE][pack_event(event) bytes]
+/// ```
+pub fn pack_batch(events: &[Event]) -> Vec<u8> {
+    let revision = events.last().map(event_revision).unwrap_or(0);
+
+    let mut buf = Vec::with_capacity(13 + events.len() * (EVENT_HEADER + 8));
+    buf.push(msg::BATCH);
+    buf.extend_from_slice(&revision.to_le_bytes());
+    buf.extend_from_slice(&(events.len() as u32).to_le_bytes());
+
+    for event in events {
+        let frame = pack_event(event);
+        buf.extend_from_slice(&(frame.len() as u16).to_le_bytes());
+        buf.extend_from_slice(&frame);
+    }
+
+    buf
+}
+
+fn event_revision(event: &Event) -> u64 {
+    match event {
+        Event::TaskCreated { revision, .. }
+        | Event::TaskScheduled { revision, .. }
+        | Event::TaskMoved { revision, .. }
+        | Event::TaskUnscheduled { revision, .. }
+        | Event::TaskCompleted { revision, .. }
+        | Event::TaskDeleted { revision, .. }
+        | Event::DependencyAdded { revision, .. }
+        | Event::DependencyRemoved { revision, .. }
+        | Event::TaskFailed { revision, .. }
+        | Event::TaskRetried { revision, .. } => *revision,
+    }
+}
+
+/// Read the revision a broadcast frame carries, without fully unpacking it.
+/// A single-event frame and a `BATCH` frame both put it at the same offset
+/// (`pack_event`'s `[1..9]`, `pack_batch`'s trailing-event revision at the
+/// same spot) — this is what lets a WS handler track "last revision this
+/// client has seen" purely from the bytes it already forwarded, so it can
+/// ask `World::events_since` to fill the gap after a broadcast-channel lag.
+///
+/// Returns 0 for a frame too short to carry one (e.g. a snapshot's own
+/// header uses the same offset for its own revision, so this also works
+/// there, but callers with a `World` in hand should just read `world.revision`).
+pub fn frame_revision(frame: &[u8]) -> u64 {
+    if frame.len() < 9 {
+        return 0;
+    }
+    u64::from_le_bytes(frame[1..9].try_into().unwrap())
+}
+
+// ── Packing (Client → Server) ──────────────────────────────────
+//
+// The server only ever unpacks commands, but a no_std/WASM build of this
+// module is meant to be the client's encoder too, so packing is provided
+// symmetrically with the same `_into`/`_len` shape as the server→client side.
+
+/// Bytes required to hold `pack_command_into`'s output for this command.
+pub fn command_len(cmd: &Command) -> usize {
+    match cmd {
+        Command::CreateTask { title, .. } => 40 + title.len(),
+        Command::ScheduleTask { .. } => 23,
+        Command::MoveTask { .. } => 24,
+        Command::UnscheduleTask { .. } | Command::CompleteTask { .. } | Command::DeleteTask { .. } => 17,
+        Command::CreateRecurringTask { .. } => unreachable!(
+            "CreateRecurringTask isn't part of the wire protocol — it's issued server-side, never packed as a client command"
+        ),
+        Command::AddDependency { .. } | Command::RemoveDependency { .. } => 33,
+        Command::Undo | Command::Redo => 1,
+        Command::FailTask { reason, .. } => 17 + reason.len(),
+        Command::RetryTask { .. } => 17,
+    }
+}
+
+/// Pack a client command into `buf`, returning the number of bytes written.
+/// `buf` must be at least `command_len(cmd)` bytes.
+pub fn pack_command_into(cmd: &Command, buf: &mut [u8]) -> usize {
+    match cmd {
+        Command::CreateTask { title, service_id, priority, assigned_to, date, start_time, duration } => {
+            buf[0] = msg::CMD_CREATE_TASK;
+            buf[1] = *priority as u8;
+            buf[2..18].copy_from_slice(service_id.as_bytes());
+            buf[18..34].copy_from_slice(assigned_to.unwrap_or(Uuid::nil()).as_bytes());
+            buf[34..36].copy_from_slice(&date.unwrap_or(0xFFFF).to_le_bytes());
+            buf[36..38].copy_from_slice(&start_time.unwrap_or(0).to_le_bytes());
+            buf[38..40].copy_from_slice(&duration.unwrap_or(0).to_le_bytes());
+            let title_bytes = title.as_bytes();
+            buf[40..40 + title_bytes.len()].copy_from_slice(title_bytes);
+        }
+
+        Command::ScheduleTask { task_id, date, start_time, duration } => {
+            buf[0] = msg::CMD_SCHEDULE_TASK;
+            buf[1..17].copy_from_slice(task_id.as_bytes());
+            buf[17..19].copy_from_slice(&date.to_le_bytes());
+            buf[19..21].copy_from_slice(&start_time.to_le_bytes());
+            buf[21..23].copy_from_slice(&duration.to_le_bytes());
+        }
+
+        Command::MoveTask { task_id, date, start_time, duration, allow_overlap } => {
+            buf[0] = msg::CMD_MOVE_TASK;
+            buf[1..17].copy_from_slice(task_id.as_bytes());
+            buf[17..19].copy_from_slice(&date.to_le_bytes());
+            buf[19..21].copy_from_slice(&start_time.to_le_bytes());
+            buf[21..23].copy_from_slice(&duration.to_le_bytes());
+            buf[23] = *allow_overlap as u8;
+        }
+
+        Command::UnscheduleTask { task_id } => {
+            buf[0] = msg::CMD_UNSCHEDULE_TASK;
+            buf[1..17].copy_from_slice(task_id.as_bytes());
+        }
+
+        Command::CompleteTask { task_id } => {
+            buf[0] = msg::CMD_COMPLETE_TASK;
+            buf[1..17].copy_from_slice(task_id.as_bytes());
+        }
+
+        Command::DeleteTask { task_id } => {
+            buf[0] = msg::CMD_DELETE_TASK;
+            buf[1..17].copy_from_slice(task_id.as_bytes());
+        }
+
+        Command::CreateRecurringTask { .. } => unreachable!(
+            "CreateRecurringTask isn't part of the wire protocol — it's issued server-side, never packed as a client command"
+        ),
+
+        Command::AddDependency { task_id, depends_on } => {
+            buf[0] = msg::CMD_ADD_DEPENDENCY;
+            buf[1..17].copy_from_slice(task_id.as_bytes());
+            buf[17..33].copy_from_slice(depends_on.as_bytes());
+        }
+
+        Command::RemoveDependency { task_id, depends_on } => {
+            buf[0] = msg::CMD_REMOVE_DEPENDENCY;
+            buf[1..17].copy_from_slice(task_id.as_bytes());
+            buf[17..33].copy_from_slice(depends_on.as_bytes());
+        }
+
+        Command::Undo => buf[0] = msg::CMD_UNDO,
+        Command::Redo => buf[0] = msg::CMD_REDO,
+
+        Command::FailTask { task_id, reason } => {
+            buf[0] = msg::CMD_FAIL_TASK;
+            buf[1..17].copy_from_slice(task_id.as_bytes());
+            let reason_bytes = reason.as_bytes();
+            buf[17..17 + reason_bytes.len()].copy_from_slice(reason_bytes);
+        }
+
+        Command::RetryTask { task_id } => {
+            buf[0] = msg::CMD_RETRY_TASK;
+            buf[1..17].copy_from_slice(task_id.as_bytes());
         }
     }
+    command_len(cmd)
+}
+
+/// Pack a client command into a freshly allocated binary frame.
+pub fn pack_command(cmd: &Command) -> Vec<u8> {
+    let mut buf = vec![0u8; command_len(cmd)];
+    pack_command_into(cmd, &mut buf);
+    buf
 }
 
 // ── Unpacking (Client → Server) ────────────────────────────────
 
+/// Unpack a `CMD_BATCH` payload (everything after the leading message-type
+/// byte) into its constituent client commands, delegating each sub-frame to
+/// [`unpack_command`].
+///
+/// ```text
+/// [0..2]   count (u16 LE)
+/// [2..]    count sub-frames, each [len: u16 LE][command frame bytes]
+/// ```
+pub fn unpack_batch(data: &[u8]) -> Result<Vec<Command>, WireError> {
+    if data.len() < 2 {
+        return Err(WireError::TooShort);
+    }
+    let count = u16::from_le_bytes([data[0], data[1]]) as usize;
+
+    let mut commands = Vec::with_capacity(count);
+    let mut offset = 2;
+    for _ in 0..count {
+        if data.len() < offset + 2 {
+            return Err(WireError::TooShort);
+        }
+        let len = u16::from_le_bytes([data[offset], data[offset + 1]]) as usize;
+        offset += 2;
+        if data.len() < offset + len {
+            return Err(WireError::TooShort);
+        }
+        commands.push(unpack_command(&data[offset..offset + len])?);
+        offset += len;
+    }
+
+    Ok(commands)
+}
+
 /// Unpack a binary command frame from the client.
 pub fn unpack_command(data: &[u8]) -> Result<Command, WireError> {
     if data.is_empty() {
@@ -262,16 +815,18 @@ pub fn unpack_command(data: &[u8]) -> Result<Command, WireError> {
         }
 
         msg::CMD_MOVE_TASK => {
-            // Same layout as ScheduleTask
-            if data.len() < 23 {
+            // Same layout as ScheduleTask, plus a trailing allow_overlap flag.
+            // [23] allow_overlap (u8, 0/1) — bypass World::find_conflict
+            if data.len() < 24 {
                 return Err(WireError::TooShort);
             }
             let task_id = uuid_from_bytes(&data[1..17]);
             let date = u16::from_le_bytes([data[17], data[18]]);
             let start_time = u16::from_le_bytes([data[19], data[20]]);
             let duration = u16::from_le_bytes([data[21], data[22]]);
+            let allow_overlap = data[23] != 0;
 
-            Ok(Command::MoveTask { task_id, date, start_time, duration })
+            Ok(Command::MoveTask { task_id, date, start_time, duration, allow_overlap })
         }
 
         msg::CMD_UNSCHEDULE_TASK => {
@@ -300,10 +855,77 @@ pub fn unpack_command(data: &[u8]) -> Result<Command, WireError> {
             Ok(Command::DeleteTask { task_id })
         }
 
+        msg::CMD_ADD_DEPENDENCY => {
+            // [0]      msg type
+            // [1..17]  task_id (UUID)
+            // [17..33] depends_on (UUID)
+            if data.len() < 33 {
+                return Err(WireError::TooShort);
+            }
+            let task_id = uuid_from_bytes(&data[1..17]);
+            let depends_on = uuid_from_bytes(&data[17..33]);
+            Ok(Command::AddDependency { task_id, depends_on })
+        }
+
+        msg::CMD_REMOVE_DEPENDENCY => {
+            // Same layout as CMD_ADD_DEPENDENCY
+            if data.len() < 33 {
+                return Err(WireError::TooShort);
+            }
+            let task_id = uuid_from_bytes(&data[1..17]);
+            let depends_on = uuid_from_bytes(&data[17..33]);
+            Ok(Command::RemoveDependency { task_id, depends_on })
+        }
+
+        msg::CMD_UNDO => Ok(Command::Undo),
+        msg::CMD_REDO => Ok(Command::Redo),
+
+        msg::CMD_FAIL_TASK => {
+            // [0]      msg type
+            // [1..17]  task_id (UUID)
+            // [17..]   reason (rest of frame, UTF-8, trimmed)
+            if data.len() < 17 {
+                return Err(WireError::TooShort);
+            }
+            let task_id = uuid_from_bytes(&data[1..17]);
+            let reason = string_from_bytes(&data[17..])?;
+            Ok(Command::FailTask { task_id, reason })
+        }
+
+        msg::CMD_RETRY_TASK => {
+            if data.len() < 17 {
+                return Err(WireError::TooShort);
+            }
+            let task_id = uuid_from_bytes(&data[1..17]);
+            Ok(Command::RetryTask { task_id })
+        }
+
         other => Err(WireError::UnknownMessage(other)),
     }
 }
 
+/// Pack a `CMD_RESYNC` request: "send me everything after this revision."
+///
+/// ```text
+/// [0]    msg type (0x17)
+/// [1..9] last_seen_revision (u64 LE)
+/// ```
+pub fn pack_resync(last_seen_revision: u64) -> Vec<u8> {
+    let mut buf = vec![0u8; 9];
+    buf[0] = msg::CMD_RESYNC;
+    buf[1..9].copy_from_slice(&last_seen_revision.to_le_bytes());
+    buf
+}
+
+/// Unpack a `CMD_RESYNC` payload (everything after the leading message-type
+/// byte) into the client's last-seen revision.
+pub fn unpack_resync(data: &[u8]) -> Result<u64, WireError> {
+    if data.len() < 8 {
+        return Err(WireError::TooShort);
+    }
+    Ok(u64::from_le_bytes(data[..8].try_into().unwrap()))
+}
+
 // ── Helpers ────────────────────────────────────────────────────
 
 fn uuid_from_bytes(b: &[u8]) -> Uuid {
@@ -336,6 +958,8 @@ pub enum WireError {
     UnknownMessage(u8),
     InvalidField(&'static str),
     InvalidUtf8,
+    AuthFailed,
+    VersionMismatch { expected: u16, got: u16 },
 }
 
 impl std::fmt::Display for WireError {
@@ -345,6 +969,10 @@ impl std::fmt::Display for WireError {
             WireError::UnknownMessage(b) => write!(f, "unknown message type: 0x{b:02X}"),
             WireError::InvalidField(name) => write!(f, "invalid field: {name}"),
             WireError::InvalidUtf8 => write!(f, "invalid UTF-8 in string field"),
+            WireError::AuthFailed => write!(f, "frame authentication tag mismatch"),
+            WireError::VersionMismatch { expected, got } => {
+                write!(f, "protocol version mismatch: expected {expected}, got {got}")
+            }
         }
     }
 }
@@ -371,6 +999,11 @@ mod tests {
             date: Some(D),
             start_time: Some(540),
             duration: Some(90),
+            recurrence: None,
+            parent_id: None,
+            depends_on: Vec::new(),
+            failure: None,
+            retry_count: 0,
         }
     }
 
@@ -381,6 +1014,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn pack_task_writes_retry_count_and_failure_reason() {
+        let mut task = make_task();
+        task.status = TaskStatus::Failed;
+        task.retry_count = 2;
+        task.failure = Some("disk full".into());
+
+        let mut buf = vec![0u8; TASK_STRIDE];
+        pack_task(&mut buf, &task);
+
+        let retry_count = u16::from_le_bytes([buf[184], buf[185]]);
+        assert_eq!(retry_count, 2);
+        assert_eq!(&buf[186..186 + "disk full".len()], b"disk full");
+        // Rest of the failure field stays zero-padded.
+        assert!(buf[186 + "disk full".len()..250].iter().all(|&b| b == 0));
+    }
+
     #[test]
     fn snapshot_round_trip_layout() {
         let mut world = World::new();
@@ -473,6 +1123,39 @@ mod tests {
         assert_eq!(&buf[9..25], &[0xBB; 16]);
     }
 
+    #[test]
+    fn event_pack_task_failed() {
+        let event = Event::TaskFailed {
+            revision: 12,
+            task_id: Uuid::from_bytes([0x44; 16]),
+            reason: "timeout".into(),
+        };
+
+        let buf = pack_event(&event);
+        assert_eq!(buf[0], msg::TASK_FAILED);
+        let rev = u64::from_le_bytes(buf[1..9].try_into().unwrap());
+        assert_eq!(rev, 12);
+        assert_eq!(&buf[9..25], &[0x44; 16]);
+        assert_eq!(buf.len(), EVENT_HEADER + "timeout".len());
+        assert_eq!(&buf[EVENT_HEADER..], b"timeout");
+    }
+
+    #[test]
+    fn event_pack_task_retried() {
+        let event = Event::TaskRetried {
+            revision: 13,
+            task_id: Uuid::from_bytes([0x55; 16]),
+            retry_count: 3,
+        };
+
+        let buf = pack_event(&event);
+        assert_eq!(buf[0], msg::TASK_RETRIED);
+        assert_eq!(&buf[9..25], &[0x55; 16]);
+        assert_eq!(buf.len(), EVENT_HEADER + 2);
+        let retry_count = u16::from_le_bytes([buf[EVENT_HEADER], buf[EVENT_HEADER + 1]]);
+        assert_eq!(retry_count, 3);
+    }
+
     #[test]
     fn unpack_move_task_command() {
         let task_id = Uuid::from_bytes([0xCC; 16]);
@@ -481,14 +1164,16 @@ mod tests {
         data.extend_from_slice(&D.to_le_bytes()); // date (u16 LE)
         data.extend_from_slice(&600u16.to_le_bytes()); // start_time (10:00)
         data.extend_from_slice(&45u16.to_le_bytes());  // duration
+        data.push(0); // allow_overlap = false
 
         let cmd = unpack_command(&data).unwrap();
         match cmd {
-            Command::MoveTask { task_id: id, date, start_time, duration } => {
+            Command::MoveTask { task_id: id, date, start_time, duration, allow_overlap } => {
                 assert_eq!(id, task_id);
                 assert_eq!(date, D);
                 assert_eq!(start_time, 600);
                 assert_eq!(duration, 45);
+                assert!(!allow_overlap);
             }
             _ => panic!("expected MoveTask"),
         }
@@ -560,6 +1245,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn fail_task_command_round_trips_with_trailing_reason() {
+        let task_id = Uuid::from_bytes([0xEE; 16]);
+        let cmd = Command::FailTask { task_id, reason: "connection refused".into() };
+
+        let frame = pack_command(&cmd);
+        assert_eq!(frame[0], msg::CMD_FAIL_TASK);
+
+        let unpacked = unpack_command(&frame).unwrap();
+        match unpacked {
+            Command::FailTask { task_id: id, reason } => {
+                assert_eq!(id, task_id);
+                assert_eq!(reason, "connection refused");
+            }
+            _ => panic!("expected FailTask"),
+        }
+    }
+
+    #[test]
+    fn retry_task_command_round_trips() {
+        let task_id = Uuid::from_bytes([0xFE; 16]);
+        let mut data = vec![msg::CMD_RETRY_TASK];
+        data.extend_from_slice(task_id.as_bytes());
+
+        let cmd = unpack_command(&data).unwrap();
+        match cmd {
+            Command::RetryTask { task_id: id } => assert_eq!(id, task_id),
+            _ => panic!("expected RetryTask"),
+        }
+    }
+
     #[test]
     fn unpack_rejects_garbage() {
         assert_eq!(unpack_command(&[]).unwrap_err(), WireError::TooShort);
@@ -567,6 +1283,215 @@ mod tests {
         assert_eq!(unpack_command(&[msg::CMD_MOVE_TASK, 0]).unwrap_err(), WireError::TooShort);
     }
 
+    #[test]
+    fn undo_and_redo_commands_round_trip() {
+        for cmd in [Command::Undo, Command::Redo] {
+            let frame = pack_command(&cmd);
+            assert_eq!(frame.len(), 1);
+            let unpacked = unpack_command(&frame).unwrap();
+            match (&cmd, &unpacked) {
+                (Command::Undo, Command::Undo) | (Command::Redo, Command::Redo) => {}
+                _ => panic!("round-trip mismatch"),
+            }
+        }
+    }
+
+    #[test]
+    fn compressed_snapshot_round_trips_bit_identical() {
+        let mut world = World::new();
+        for i in 0..20u8 {
+            let mut task = make_task();
+            task.id = Uuid::from_bytes([i; 16]);
+            world.tasks.insert(task.id, task);
+        }
+        world.services.insert(make_service().id, make_service());
+        world.revision = 123;
+
+        let uncompressed = pack_snapshot(&world);
+        let compressed = pack_snapshot_compressed(&world);
+        assert_eq!(compressed[0], msg::SNAPSHOT_COMPRESSED);
+        assert!(compressed.len() < uncompressed.len(), "RLE should shrink a 20-task world");
+
+        let recovered = unpack_snapshot_compressed(&compressed).unwrap();
+        assert_eq!(recovered, uncompressed);
+    }
+
+    #[test]
+    fn compressed_snapshot_falls_back_when_not_smaller() {
+        // A tiny, non-sparse world: compression overhead isn't worth it.
+        let mut world = World::new();
+        let mut task = make_task();
+        // Fill the variable-width fields so the record has few zero runs.
+        task.title = "x".repeat(TITLE_MAX);
+        task.failure = Some("y".repeat(FAILURE_MAX));
+        task.retry_count = 0x0101;
+        task.start_time = Some(0x0101);
+        task.duration = Some(0x0101);
+        world.tasks.insert(task.id, task);
+        world.revision = 1;
+
+        let out = pack_snapshot_compressed(&world);
+        assert_eq!(out[0], msg::SNAPSHOT, "should fall back to the uncompressed frame");
+        assert_eq!(out, pack_snapshot(&world));
+    }
+
+    #[test]
+    fn rle_round_trips_mixed_runs() {
+        let data = [0u8, 0, 0, 1, 2, 3, 0, 0, 255, 255];
+        let encoded = rle_encode(&data);
+        let decoded = rle_decode(&encoded, data.len()).unwrap();
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn snapshot_len_matches_pack_snapshot_output() {
+        let mut world = World::new();
+        let task = make_task();
+        world.tasks.insert(task.id, task.clone());
+        assert_eq!(snapshot_len(&world), pack_snapshot(&world).len());
+    }
+
+    #[test]
+    fn event_len_matches_pack_event_output() {
+        let event = Event::TaskCreated { revision: 1, task: make_task() };
+        assert_eq!(event_len(&event), pack_event(&event).len());
+    }
+
+    #[test]
+    fn resync_round_trips() {
+        let frame = pack_resync(42);
+        assert_eq!(frame[0], msg::CMD_RESYNC);
+        assert_eq!(unpack_resync(&frame[1..]).unwrap(), 42);
+    }
+
+    #[test]
+    fn resync_rejects_truncated_payload() {
+        let frame = pack_resync(42);
+        assert_eq!(unpack_resync(&frame[1..frame.len() - 1]).unwrap_err(), WireError::TooShort);
+    }
+
+    #[test]
+    fn frame_revision_reads_single_event_frame() {
+        let event = Event::TaskCompleted { revision: 7, task_id: Uuid::from_bytes([1; 16]) };
+        assert_eq!(frame_revision(&pack_event(&event)), 7);
+    }
+
+    #[test]
+    fn frame_revision_reads_batch_frame() {
+        let events = vec![
+            Event::TaskCompleted { revision: 5, task_id: Uuid::from_bytes([1; 16]) },
+            Event::TaskDeleted { revision: 6, task_id: Uuid::from_bytes([2; 16]) },
+        ];
+        assert_eq!(frame_revision(&pack_batch(&events)), 6);
+    }
+
+    #[test]
+    fn frame_revision_of_too_short_frame_is_zero() {
+        assert_eq!(frame_revision(&[msg::BATCH]), 0);
+    }
+
+    #[test]
+    fn pack_command_round_trips_through_unpack_command() {
+        let cmd = Command::CreateTask {
+            title: "Write the docs".into(),
+            service_id: Uuid::from_bytes([9; 16]),
+            priority: Priority::Low,
+            assigned_to: None,
+            date: Some(D),
+            start_time: Some(480),
+            duration: Some(15),
+        };
+        let buf = pack_command(&cmd);
+        assert_eq!(buf.len(), command_len(&cmd));
+
+        match unpack_command(&buf).unwrap() {
+            Command::CreateTask { title, service_id, priority, date, start_time, duration, .. } => {
+                assert_eq!(title, "Write the docs");
+                assert_eq!(service_id, Uuid::from_bytes([9; 16]));
+                assert_eq!(priority, Priority::Low);
+                assert_eq!(date, Some(D));
+                assert_eq!(start_time, Some(480));
+                assert_eq!(duration, Some(15));
+            }
+            _ => panic!("expected CreateTask"),
+        }
+    }
+
+    #[test]
+    fn hello_round_trips_and_validates() {
+        let packed = pack_hello(capabilities::BATCHING | capabilities::AUTH_FRAMES);
+        let hello = unpack_hello(&packed).unwrap();
+        assert_eq!(hello.protocol_version, PROTOCOL_VERSION);
+        assert_eq!(hello.task_stride as usize, TASK_STRIDE);
+        assert_eq!(hello.service_stride as usize, SERVICE_STRIDE);
+        assert_eq!(hello.capabilities, capabilities::BATCHING | capabilities::AUTH_FRAMES);
+        assert!(validate_hello(&hello).is_ok());
+    }
+
+    #[test]
+    fn hello_version_mismatch_is_rejected() {
+        let mut hello = unpack_hello(&pack_hello(0)).unwrap();
+        hello.protocol_version += 1;
+        assert_eq!(
+            validate_hello(&hello).unwrap_err(),
+            WireError::VersionMismatch { expected: PROTOCOL_VERSION, got: PROTOCOL_VERSION + 1 }
+        );
+    }
+
+    #[test]
+    fn pack_batch_trailing_revision_is_last_event() {
+        let events = vec![
+            Event::TaskCompleted { revision: 10, task_id: Uuid::from_bytes([1; 16]) },
+            Event::TaskDeleted { revision: 11, task_id: Uuid::from_bytes([2; 16]) },
+        ];
+        let buf = pack_batch(&events);
+        assert_eq!(buf[0], msg::BATCH);
+        let rev = u64::from_le_bytes(buf[1..9].try_into().unwrap());
+        assert_eq!(rev, 11);
+        let count = u32::from_le_bytes(buf[9..13].try_into().unwrap());
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn unpack_batch_round_trips_commands() {
+        let task_a = Uuid::from_bytes([0xAA; 16]);
+        let task_b = Uuid::from_bytes([0xBB; 16]);
+        let frame_a = {
+            let mut d = vec![msg::CMD_COMPLETE_TASK];
+            d.extend_from_slice(task_a.as_bytes());
+            d
+        };
+        let frame_b = {
+            let mut d = vec![msg::CMD_DELETE_TASK];
+            d.extend_from_slice(task_b.as_bytes());
+            d
+        };
+
+        let mut data = (2u16).to_le_bytes().to_vec();
+        data.extend_from_slice(&(frame_a.len() as u16).to_le_bytes());
+        data.extend_from_slice(&frame_a);
+        data.extend_from_slice(&(frame_b.len() as u16).to_le_bytes());
+        data.extend_from_slice(&frame_b);
+
+        let commands = unpack_batch(&data).unwrap();
+        assert_eq!(commands.len(), 2);
+        match &commands[0] {
+            Command::CompleteTask { task_id } => assert_eq!(*task_id, task_a),
+            _ => panic!("expected CompleteTask"),
+        }
+        match &commands[1] {
+            Command::DeleteTask { task_id } => assert_eq!(*task_id, task_b),
+            _ => panic!("expected DeleteTask"),
+        }
+    }
+
+    #[test]
+    fn unpack_batch_rejects_truncated_sub_frame() {
+        let mut data = (1u16).to_le_bytes().to_vec();
+        data.extend_from_slice(&(20u16).to_le_bytes()); // claims 20 bytes, supplies none
+        assert_eq!(unpack_batch(&data).unwrap_err(), WireError::TooShort);
+    }
+
     #[test]
     fn staged_task_date_is_0xffff() {
         let task = Task {
@@ -580,6 +1505,11 @@ mod tests {
             date: None,
             start_time: None,
             duration: None,
+            recurrence: None,
+            parent_id: None,
+            depends_on: Vec::new(),
+            failure: None,
+            retry_count: 0,
         };
 
         let mut buf = vec![0u8; TASK_STRIDE];
@@ -1,22 +1,26 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
 // ── Entity types ──────────────────────────────────────────────
 
-/// Task status lifecycle: Staged → Scheduled → Active → Completed
+/// Task status lifecycle: Staged → Scheduled → Active → Completed, with a
+/// Failed/retry loop off of Scheduled/Active back to Staged.
 ///
 /// Staged    = exists but has no time slot (lives in the staging queue)
 /// Scheduled = has a day + time slot on the grid
 /// Active    = being worked right now
 /// Completed = done
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Failed    = blew up mid-work; `Command::RetryTask` returns it to Staged,
+///             `fang`/Backie-style, instead of it sitting stale as Active
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum TaskStatus {
     Staged = 0,
     Scheduled = 1,
     Active = 2,
     Completed = 3,
+    Failed = 4,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -48,6 +52,55 @@ pub struct Task {
     pub start_time: Option<u16>,
     /// Duration in minutes, snapped to 15-min grid. None if Staged.
     pub duration: Option<u16>,
+    /// Present on a recurring template task — never on an ordinary one, and
+    /// never on a task `materialize_recurring` produced from a template.
+    /// The template itself stays Staged; it never occupies a grid slot.
+    pub recurrence: Option<RecurrenceRule>,
+    /// Set when this task was materialized from a recurring template, to
+    /// the template's id. `(parent_id, date)` is the dedup key
+    /// `materialize_recurring` uses to avoid creating the same occurrence
+    /// twice. `None` for ordinary tasks and for templates themselves.
+    pub parent_id: Option<Uuid>,
+    /// Other tasks that must be `Completed` before this one can be scheduled,
+    /// taskwarrior-style (task-hookrs' `depends`). Checked by `ScheduleTask`
+    /// and `MoveTask`; `World::ready_queue` is the staging queue filtered down
+    /// to tasks where every entry here is already done.
+    pub depends_on: Vec<Uuid>,
+    /// Why `Command::FailTask` last failed this task. Set when entering
+    /// `Failed`; left as-is by `RetryTask` so the last failure stays visible
+    /// even after the task is back in the staging queue.
+    pub failure: Option<String>,
+    /// How many times this task has gone through the Failed→Staged retry
+    /// loop. Never reset — `Command::RetryTask`'s `max_retries` check reads
+    /// this to refuse yet another retry once a task has churned too long.
+    pub retry_count: u16,
+}
+
+/// A cron-style recurrence rule on a 15-minute grid, carried on a template
+/// task's `recurrence` field. `World::materialize_recurring` walks it and
+/// lays down one concrete child `Task` per matching weekday inside the
+/// horizon, the same way fang/Backie walk a cron schedule to enqueue jobs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecurrenceRule {
+    /// Weekdays this rule fires on, in this file's day convention:
+    /// 0=Mon .. 6=Sun, i.e. `(date + 3) % 7`.
+    pub weekdays: Vec<u8>,
+    /// Minutes from midnight for each occurrence, snapped to the 15-min grid.
+    pub start_time: u16,
+    /// Duration in minutes for each occurrence, snapped to the 15-min grid.
+    pub duration: u16,
+    /// How many days past "today" `materialize_recurring` should lay down
+    /// occurrences for.
+    pub horizon_days: u16,
+}
+
+/// A user's authorization level. What each role can actually do is defined
+/// by `auth::Permissions` — this enum just names the tiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Role {
+    Admin,
+    Member,
+    Viewer,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +108,7 @@ pub struct User {
     pub id: Uuid,
     pub username: String,
     pub password_hash: String,
+    pub role: Role,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,6 +117,41 @@ pub struct Service {
     pub name: String,
 }
 
+/// A server-side record of an issued JWT, keyed by its `jti`. A JWT's
+/// signature proves it was issued by us, but not that it's still wanted —
+/// this is what lets `auth::logout` revoke one before it expires.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub jti: Uuid,
+    pub user_id: Uuid,
+    pub issued_at: i64,
+    pub revoked: bool,
+}
+
+/// A long-lived, opaque refresh token. The token the client holds is never
+/// stored — only a SHA-256 hash of it, so a leaked save file doesn't hand
+/// over usable tokens. Looked up and deleted atomically on every refresh
+/// (`Store::take_refresh_token`), which is what makes reuse after rotation
+/// fail closed instead of silently succeeding.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefreshToken {
+    pub token_hash: Vec<u8>,
+    pub user_id: Uuid,
+    pub expires_at: i64,
+}
+
+/// One JWT signing key in the keyring. Exactly one entry should have
+/// `retired == false` at a time — that's the key `create_token` signs new
+/// tokens with. Retired keys are kept around purely so tokens they already
+/// signed keep verifying until they expire naturally after a rotation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwtKey {
+    pub kid: Uuid,
+    pub secret: Vec<u8>,
+    pub created_at: i64,
+    pub retired: bool,
+}
+
 // ── Commands (client → server) ────────────────────────────────
 
 /// A command is something a client wants to happen.
@@ -91,6 +180,9 @@ pub enum Command {
         date: u16,
         start_time: u16,
         duration: u16,
+        /// Escape hatch for an intentional double-booking — skips
+        /// `World::find_conflict` instead of failing with `SlotConflict`.
+        allow_overlap: bool,
     },
     UnscheduleTask {
         task_id: Uuid,
@@ -101,6 +193,52 @@ pub enum Command {
     DeleteTask {
         task_id: Uuid,
     },
+    /// Lay down a recurring template task. It's created Staged and never
+    /// scheduled itself — `World::materialize_recurring` is what turns it
+    /// into concrete, scheduled child tasks on the grid.
+    CreateRecurringTask {
+        title: String,
+        service_id: Uuid,
+        priority: Priority,
+        /// 5-field cron syntax (minute hour dom month dow). The minute/hour
+        /// fields must be valid and on the 15-min grid, but only the weekday
+        /// (`dow`) field is used — see `parse_cron`. `start_time` below is
+        /// the already grid-validated source of truth for the time-of-day;
+        /// cron's own minute/hour values are not used.
+        cron: String,
+        start_time: u16,
+        duration: u16,
+        horizon_days: u16,
+    },
+    /// Mark `task_id` as blocked on `depends_on` until it's `Completed`.
+    /// Rejected with `WorldError::DependencyCycle` if `depends_on` can
+    /// already reach `task_id` through the existing dependency graph.
+    AddDependency {
+        task_id: Uuid,
+        depends_on: Uuid,
+    },
+    RemoveDependency {
+        task_id: Uuid,
+        depends_on: Uuid,
+    },
+    /// Mark a `Scheduled`/`Active` task as blown up. Records `reason` and
+    /// moves it to `Failed`, out of everyone's way until someone retries it.
+    FailTask {
+        task_id: Uuid,
+        reason: String,
+    },
+    /// Send a `Failed` task back to the staging queue, incrementing
+    /// `Task.retry_count`. Rejected with `WorldError::RetryExhausted` once
+    /// `World`'s `max_retries` cap (if set) is reached.
+    RetryTask {
+        task_id: Uuid,
+    },
+    /// Invert the issuing user's most recent undoable command, pushing a
+    /// *new* forward event rather than rewinding `revision` — see the
+    /// `undo`/`redo` doc comments on `World`.
+    Undo,
+    /// Re-apply the issuing user's most recently undone command.
+    Redo,
 }
 
 // ── Events (server → clients) ─────────────────────────────────
@@ -139,6 +277,26 @@ pub enum Event {
         revision: u64,
         task_id: Uuid,
     },
+    DependencyAdded {
+        revision: u64,
+        task_id: Uuid,
+        depends_on: Uuid,
+    },
+    DependencyRemoved {
+        revision: u64,
+        task_id: Uuid,
+        depends_on: Uuid,
+    },
+    TaskFailed {
+        revision: u64,
+        task_id: Uuid,
+        reason: String,
+    },
+    TaskRetried {
+        revision: u64,
+        task_id: Uuid,
+        retry_count: u16,
+    },
 }
 
 // ── Errors ─────────────────────────────────────────────────────
@@ -152,6 +310,77 @@ pub enum WorldError {
     InvalidDuration,
     /// Task is already in the requested state
     InvalidTransition,
+    /// `CreateRecurringTask`'s cron expression didn't parse — wrong field
+    /// count, an out-of-range minute/hour/weekday, or a minute/hour pair off
+    /// the 15-min grid.
+    InvalidCron,
+    /// `ScheduleTask`/`MoveTask` on a task with an incomplete dependency.
+    BlockedByDependency,
+    /// `AddDependency` would make the dependency graph reachable from itself.
+    DependencyCycle,
+    /// `Undo` with nothing left on the issuing user's undo stack.
+    NothingToUndo,
+    /// `Redo` with nothing left on the issuing user's redo stack.
+    NothingToRedo,
+    /// `ScheduleTask`/`MoveTask`/a scheduled `CreateTask` would put its
+    /// assignee in two places at once. `conflicting` is the task already
+    /// occupying (part of) the requested slot.
+    SlotConflict { conflicting: Uuid },
+    /// `RetryTask` on a task whose `retry_count` has already reached
+    /// `World`'s configured `max_retries`.
+    RetryExhausted,
+}
+
+/// One entry on a user's undo stack: the event a command produced, plus
+/// whatever task state existed just before that command mutated it.
+/// `pre_image` is `None` for `TaskCreated` (there was nothing to restore);
+/// every other undoable event carries the task's prior snapshot.
+#[derive(Debug, Clone)]
+struct Undoable {
+    event: Event,
+    pre_image: Option<Task>,
+}
+
+/// Default number of trailing events `World::log` retains for reconnect
+/// replay. A client whose last-seen revision has fallen out of this window
+/// is too far behind to replay and must be sent a full snapshot instead.
+pub const DEFAULT_LOG_CAPACITY: usize = 256;
+
+pub type Revision = u64;
+
+/// Materialized `tasks`/`users`/`services` as they stood at `base_revision`,
+/// produced by `World::compact`. Lets a client that's fallen behind the
+/// compaction boundary re-bootstrap from here and replay whatever's left
+/// in `log` after `base_revision`, instead of needing events that no
+/// longer exist.
+#[derive(Debug, Clone)]
+pub struct WorldSnapshot {
+    pub base_revision: Revision,
+    pub tasks: HashMap<Uuid, Task>,
+    pub users: HashMap<Uuid, User>,
+    pub services: HashMap<Uuid, Service>,
+}
+
+/// `events_since` couldn't serve the request from `log` alone — either
+/// `World::compact` or the older `log_capacity` ring-buffer trim dropped
+/// the events the caller needed. Carries the most recent snapshot plus
+/// every `log` entry after it, so the caller can apply the snapshot and
+/// then replay `tail` to reach `World::revision`.
+#[derive(Debug, Clone)]
+pub struct NeedsSnapshot {
+    pub snapshot: WorldSnapshot,
+    pub tail: Vec<(Revision, Event)>,
+}
+
+/// A filter for `World::query`. Every `Some` field narrows the result;
+/// `TaskFilter::default()` matches every task.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TaskFilter {
+    pub status: Option<TaskStatus>,
+    pub assignee: Option<Uuid>,
+    /// Inclusive `(start, end)` epoch-day range. Can't be served by an
+    /// index — `query` always filters this one by hand.
+    pub date_range: Option<(u16, u16)>,
 }
 
 // ── The World ──────────────────────────────────────────────────
@@ -164,8 +393,32 @@ pub struct World {
     pub users: HashMap<Uuid, User>,
     pub services: HashMap<Uuid, Service>,
     pub revision: u64,
-    /// Recent event log for reconnect replay and undo.
+    /// Recent event log for reconnect replay. Bounded to `log_capacity`
+    /// entries — a ring buffer over the tail of history.
     pub log: Vec<(u64, Event)>,
+    log_capacity: usize,
+    /// Per-user undo history, most recent last. `Command::Undo` pops here
+    /// and pushes the popped entry onto `redo_stacks`; any other successful
+    /// mutation from that user clears their redo stack. Kept separate from
+    /// `log` (which every client replays) since undo/redo is local to the
+    /// user who issued the original command, not a property of the event.
+    undo_stacks: HashMap<Uuid, Vec<Undoable>>,
+    redo_stacks: HashMap<Uuid, Vec<Undoable>>,
+    /// Task ids grouped by `status`, kept in sync by every mutating `apply`
+    /// arm (and `invert`/`replay_forward`) so `query` never has to scan
+    /// `tasks` to answer a status filter. MeiliSearch's index-scheduler
+    /// keeps the same kind of per-status job-id sets for the same reason.
+    by_status: HashMap<TaskStatus, HashSet<Uuid>>,
+    /// Task ids grouped by `assigned_to`, maintained the same way as
+    /// `by_status`. Unassigned tasks have no entry in here.
+    by_assignee: HashMap<Uuid, HashSet<Uuid>>,
+    /// Cap on `Task.retry_count` that `Command::RetryTask` enforces. `None`
+    /// (the default) means unlimited retries.
+    max_retries: Option<u16>,
+    /// Most recent compaction boundary, set by `compact`. `None` until the
+    /// first call — `events_since` falls back to a snapshot of the live
+    /// current state when it needs one but none has been taken yet.
+    snapshot: Option<WorldSnapshot>,
 }
 
 impl World {
@@ -176,9 +429,104 @@ impl World {
             services: HashMap::new(),
             revision: 0,
             log: Vec::new(),
+            log_capacity: DEFAULT_LOG_CAPACITY,
+            undo_stacks: HashMap::new(),
+            redo_stacks: HashMap::new(),
+            by_status: HashMap::new(),
+            by_assignee: HashMap::new(),
+            max_retries: None,
+            snapshot: None,
+        }
+    }
+
+    /// Add `task` to `by_status`/`by_assignee`. Call after inserting it (or
+    /// mutating its `status`/`assigned_to`) into `tasks`.
+    fn index_insert(&mut self, task: &Task) {
+        self.by_status.entry(task.status).or_default().insert(task.id);
+        if let Some(assignee) = task.assigned_to {
+            self.by_assignee.entry(assignee).or_default().insert(task.id);
+        }
+    }
+
+    /// Remove `task` from `by_status`/`by_assignee`. Call with the task's
+    /// state *before* a mutation (or before removing it from `tasks`).
+    fn index_remove(&mut self, task: &Task) {
+        if let Some(set) = self.by_status.get_mut(&task.status) {
+            set.remove(&task.id);
+        }
+        if let Some(assignee) = task.assigned_to {
+            if let Some(set) = self.by_assignee.get_mut(&assignee) {
+                set.remove(&task.id);
+            }
+        }
+    }
+
+    /// Rebuild `by_status`/`by_assignee` from scratch against the current
+    /// `tasks` map. Call after loading a `World` from storage — boot-time
+    /// loaders populate `tasks` directly (or via `apply_event`, which is a
+    /// trusted replay and doesn't touch the indexes either), so this is
+    /// what guarantees the indexes match the persisted tasks before the
+    /// first query.
+    pub fn rebuild_indexes(&mut self) {
+        self.by_status.clear();
+        self.by_assignee.clear();
+        for task in self.tasks.values() {
+            self.by_status.entry(task.status).or_default().insert(task.id);
+            if let Some(assignee) = task.assigned_to {
+                self.by_assignee.entry(assignee).or_default().insert(task.id);
+            }
+        }
+    }
+
+    /// Override how many trailing events `log` retains. Useful for tests
+    /// that want to exercise the "client too far behind" resync path
+    /// without creating hundreds of events.
+    pub fn set_log_capacity(&mut self, capacity: usize) {
+        self.log_capacity = capacity;
+        self.trim_log();
+    }
+
+    /// Cap how many times `Command::RetryTask` will send a `Failed` task
+    /// back to staging. `None` (the default) leaves retries unlimited.
+    pub fn set_max_retries(&mut self, max_retries: Option<u16>) {
+        self.max_retries = max_retries;
+    }
+
+    /// Append an event to the log under the current revision, trimming the
+    /// oldest entries if the log has grown past its capacity.
+    fn record(&mut self, event: Event) -> Event {
+        self.log.push((self.revision, event.clone()));
+        self.trim_log();
+        event
+    }
+
+    fn trim_log(&mut self) {
+        if self.log.len() > self.log_capacity {
+            let excess = self.log.len() - self.log_capacity;
+            self.log.drain(..excess);
         }
     }
 
+    /// Invalidate `user_id`'s redo stack — any fresh mutation from that user
+    /// makes whatever they previously undid stale, since redoing it now
+    /// could resurrect a change on top of state it was never applied
+    /// against. Called by every successful `apply` arm, not just the ones
+    /// that go through `push_undo`: `CreateRecurringTask`, `AddDependency`
+    /// and `RemoveDependency` aren't themselves undoable (see the
+    /// `unreachable!` in `invert`), but they still count as "any other
+    /// successful mutation" for the purposes of this invariant.
+    fn clear_redo(&mut self, user_id: Uuid) {
+        self.redo_stacks.remove(&user_id);
+    }
+
+    /// Record a successful mutation on `user_id`'s undo stack and clear
+    /// their redo stack — any fresh mutation invalidates whatever was
+    /// previously undone.
+    fn push_undo(&mut self, user_id: Uuid, event: Event, pre_image: Option<Task>) {
+        self.undo_stacks.entry(user_id).or_default().push(Undoable { event, pre_image });
+        self.clear_redo(user_id);
+    }
+
     /// Apply a command to the world. Returns the resulting Event on success.
     /// This is THE mutation codepath — every state change goes through here.
     pub fn apply(&mut self, cmd: Command, user_id: Uuid) -> Result<Event, WorldError> {
@@ -193,6 +541,11 @@ impl World {
                 let (status, date, start_time, duration) = match (date, start_time, duration) {
                     (Some(d), Some(st), Some(dur)) => {
                         validate_scheduling(d, st, dur)?;
+                        if let Some(assignee) = assigned_to {
+                            if let Some(conflicting) = self.find_conflict(d, st, dur, assignee, Uuid::nil()) {
+                                return Err(WorldError::SlotConflict { conflicting });
+                            }
+                        }
                         (TaskStatus::Scheduled, Some(d), Some(st), Some(dur))
                     }
                     _ => (TaskStatus::Staged, None, None, None),
@@ -209,6 +562,49 @@ impl World {
                     date,
                     start_time,
                     duration,
+                    recurrence: None,
+                    parent_id: None,
+                    depends_on: Vec::new(),
+                    failure: None,
+                    retry_count: 0,
+                };
+
+                self.revision += 1;
+                let event = Event::TaskCreated {
+                    revision: self.revision,
+                    task: task.clone(),
+                };
+                self.index_insert(&task);
+                self.tasks.insert(task.id, task);
+                let event = self.record(event);
+                self.push_undo(user_id, event.clone(), None);
+                Ok(event)
+            }
+
+            Command::CreateRecurringTask { title, service_id, priority, cron, start_time, duration, horizon_days } => {
+                if !self.services.contains_key(&service_id) {
+                    return Err(WorldError::ServiceNotFound);
+                }
+
+                let (_, weekdays) = parse_cron(&cron)?;
+                validate_time_slot(start_time, duration)?;
+
+                let task = Task {
+                    id: Uuid::new_v4(),
+                    title,
+                    status: TaskStatus::Staged,
+                    priority,
+                    service_id,
+                    created_by: user_id,
+                    assigned_to: None,
+                    date: None,
+                    start_time: None,
+                    duration: None,
+                    recurrence: Some(RecurrenceRule { weekdays, start_time, duration, horizon_days }),
+                    parent_id: None,
+                    depends_on: Vec::new(),
+                    failure: None,
+                    retry_count: 0,
                 };
 
                 self.revision += 1;
@@ -216,26 +612,41 @@ impl World {
                     revision: self.revision,
                     task: task.clone(),
                 };
+                self.index_insert(&task);
                 self.tasks.insert(task.id, task);
-                self.log.push((self.revision, event.clone()));
+                let event = self.record(event);
+                self.clear_redo(user_id);
                 Ok(event)
             }
 
             Command::ScheduleTask { task_id, date, start_time, duration } => {
                 validate_scheduling(date, start_time, duration)?;
+                self.check_dependencies_complete(task_id)?;
 
-                let task = self.tasks.get_mut(&task_id)
-                    .ok_or(WorldError::TaskNotFound)?;
-
+                let (status, assigned_to) = {
+                    let task = self.tasks.get(&task_id).ok_or(WorldError::TaskNotFound)?;
+                    (task.status, task.assigned_to)
+                };
                 // Can only schedule a Staged task
-                if task.status != TaskStatus::Staged {
+                if status != TaskStatus::Staged {
                     return Err(WorldError::InvalidTransition);
                 }
+                if let Some(assignee) = assigned_to {
+                    if let Some(conflicting) = self.find_conflict(date, start_time, duration, assignee, task_id) {
+                        return Err(WorldError::SlotConflict { conflicting });
+                    }
+                }
+
+                let task = self.tasks.get_mut(&task_id).unwrap();
+                let pre_image = task.clone();
 
                 task.status = TaskStatus::Scheduled;
                 task.date = Some(date);
                 task.start_time = Some(start_time);
                 task.duration = Some(duration);
+                let post_image = task.clone();
+                self.index_remove(&pre_image);
+                self.index_insert(&post_image);
 
                 self.revision += 1;
                 let event = Event::TaskScheduled {
@@ -245,24 +656,40 @@ impl World {
                     start_time,
                     duration,
                 };
-                self.log.push((self.revision, event.clone()));
+                let event = self.record(event);
+                self.push_undo(user_id, event.clone(), Some(pre_image));
                 Ok(event)
             }
 
-            Command::MoveTask { task_id, date, start_time, duration } => {
+            Command::MoveTask { task_id, date, start_time, duration, allow_overlap } => {
                 validate_scheduling(date, start_time, duration)?;
+                self.check_dependencies_complete(task_id)?;
 
-                let task = self.tasks.get_mut(&task_id)
-                    .ok_or(WorldError::TaskNotFound)?;
-
+                let (status, assigned_to) = {
+                    let task = self.tasks.get(&task_id).ok_or(WorldError::TaskNotFound)?;
+                    (task.status, task.assigned_to)
+                };
                 // Can only move a Scheduled or Active task (something on the grid)
-                if task.status != TaskStatus::Scheduled && task.status != TaskStatus::Active {
+                if status != TaskStatus::Scheduled && status != TaskStatus::Active {
                     return Err(WorldError::InvalidTransition);
                 }
+                if !allow_overlap {
+                    if let Some(assignee) = assigned_to {
+                        if let Some(conflicting) = self.find_conflict(date, start_time, duration, assignee, task_id) {
+                            return Err(WorldError::SlotConflict { conflicting });
+                        }
+                    }
+                }
+
+                let task = self.tasks.get_mut(&task_id).unwrap();
+                let pre_image = task.clone();
 
                 task.date = Some(date);
                 task.start_time = Some(start_time);
                 task.duration = Some(duration);
+                let post_image = task.clone();
+                self.index_remove(&pre_image);
+                self.index_insert(&post_image);
 
                 self.revision += 1;
                 let event = Event::TaskMoved {
@@ -272,7 +699,8 @@ impl World {
                     start_time,
                     duration,
                 };
-                self.log.push((self.revision, event.clone()));
+                let event = self.record(event);
+                self.push_undo(user_id, event.clone(), Some(pre_image));
                 Ok(event)
             }
 
@@ -284,18 +712,23 @@ impl World {
                 if task.status != TaskStatus::Scheduled && task.status != TaskStatus::Active {
                     return Err(WorldError::InvalidTransition);
                 }
+                let pre_image = task.clone();
 
                 task.status = TaskStatus::Staged;
                 task.date = None;
                 task.start_time = None;
                 task.duration = None;
+                let post_image = task.clone();
+                self.index_remove(&pre_image);
+                self.index_insert(&post_image);
 
                 self.revision += 1;
                 let event = Event::TaskUnscheduled {
                     revision: self.revision,
                     task_id,
                 };
-                self.log.push((self.revision, event.clone()));
+                let event = self.record(event);
+                self.push_undo(user_id, event.clone(), Some(pre_image));
                 Ok(event)
             }
 
@@ -307,472 +740,2222 @@ impl World {
                 if task.status != TaskStatus::Scheduled && task.status != TaskStatus::Active {
                     return Err(WorldError::InvalidTransition);
                 }
+                let pre_image = task.clone();
 
                 task.status = TaskStatus::Completed;
+                let post_image = task.clone();
+                self.index_remove(&pre_image);
+                self.index_insert(&post_image);
 
                 self.revision += 1;
                 let event = Event::TaskCompleted {
                     revision: self.revision,
                     task_id,
                 };
-                self.log.push((self.revision, event.clone()));
+                let event = self.record(event);
+                self.push_undo(user_id, event.clone(), Some(pre_image));
                 Ok(event)
             }
 
-            Command::DeleteTask { task_id } => {
-                if self.tasks.remove(&task_id).is_none() {
-                    return Err(WorldError::TaskNotFound);
+            Command::FailTask { task_id, reason } => {
+                let task = self.tasks.get_mut(&task_id)
+                    .ok_or(WorldError::TaskNotFound)?;
+
+                // Can only fail something that was actually being worked
+                if task.status != TaskStatus::Scheduled && task.status != TaskStatus::Active {
+                    return Err(WorldError::InvalidTransition);
                 }
+                let pre_image = task.clone();
+
+                task.status = TaskStatus::Failed;
+                task.failure = Some(reason.clone());
+                let post_image = task.clone();
+                self.index_remove(&pre_image);
+                self.index_insert(&post_image);
 
                 self.revision += 1;
-                let event = Event::TaskDeleted {
+                let event = Event::TaskFailed {
                     revision: self.revision,
                     task_id,
+                    reason,
                 };
-                self.log.push((self.revision, event.clone()));
+                let event = self.record(event);
+                self.push_undo(user_id, event.clone(), Some(pre_image));
                 Ok(event)
             }
-        }
-    }
 
-    /// Look up a user by username (linear scan — fine for 5-20 users).
-    pub fn get_user_by_username(&self, username: &str) -> Option<&User> {
-        self.users.values().find(|u| u.username == username)
-    }
+            Command::RetryTask { task_id } => {
+                let task = self.tasks.get_mut(&task_id)
+                    .ok_or(WorldError::TaskNotFound)?;
 
-    /// Get all Staged tasks, sorted by priority (highest first).
-    /// This is the staging queue that IRONCLAD renders as a sidebar list.
-    pub fn staging_queue(&self) -> Vec<&Task> {
-        let mut staged: Vec<&Task> = self.tasks.values()
-            .filter(|t| t.status == TaskStatus::Staged)
-            .collect();
-        // Sort by priority descending (Urgent first, Low last)
-        staged.sort_by(|a, b| b.priority.cmp(&a.priority));
-        staged
-    }
+                // Can only retry something that actually failed
+                if task.status != TaskStatus::Failed {
+                    return Err(WorldError::InvalidTransition);
+                }
+                if let Some(max) = self.max_retries {
+                    if task.retry_count >= max {
+                        return Err(WorldError::RetryExhausted);
+                    }
+                }
+                let pre_image = task.clone();
 
-    /// Get all events since a given revision (for reconnect replay).
-    /// Returns None if the revision is too old (caller should send full snapshot).
-    pub fn events_since(&self, since_rev: u64) -> Option<&[(u64, Event)]> {
-        // Find the first log entry after since_rev
-        let start = self.log.iter().position(|(rev, _)| *rev > since_rev);
-        match start {
-            Some(idx) => Some(&self.log[idx..]),
-            None if since_rev >= self.revision => Some(&[]), // up to date
-            None => None, // too old, log was trimmed
-        }
-    }
-}
+                task.status = TaskStatus::Staged;
+                task.date = None;
+                task.start_time = None;
+                task.duration = None;
+                task.retry_count += 1;
+                let retry_count = task.retry_count;
+                let post_image = task.clone();
+                self.index_remove(&pre_image);
+                self.index_insert(&post_image);
 
-// ── Validation helpers ─────────────────────────────────────────
+                self.revision += 1;
+                let event = Event::TaskRetried {
+                    revision: self.revision,
+                    task_id,
+                    retry_count,
+                };
+                let event = self.record(event);
+                self.push_undo(user_id, event.clone(), Some(pre_image));
+                Ok(event)
+            }
 
-/// Validate scheduling fields.
-///
-/// date: epoch days (any value except 0xFFFF which is the staged sentinel)
-/// start_time: minutes from midnight, must be on 15-min grid
-/// duration: minutes, must be on 15-min grid, must not overflow past midnight
-fn validate_scheduling(date: u16, start_time: u16, duration: u16) -> Result<(), WorldError> {
-    if date == 0xFFFF {
-        return Err(WorldError::InvalidDate);
-    }
-    // 24 hours = 1440 minutes. Must be on 15-min grid.
-    if start_time >= 1440 || start_time % 15 != 0 {
-        return Err(WorldError::InvalidTime);
-    }
-    // Duration: at least 15 min, on 15-min grid, doesn't go past midnight
-    if duration == 0 || duration % 15 != 0 || start_time + duration > 1440 {
-        return Err(WorldError::InvalidDuration);
-    }
-    Ok(())
-}
+            Command::DeleteTask { task_id } => {
+                let pre_image = self.tasks.remove(&task_id).ok_or(WorldError::TaskNotFound)?;
+                self.index_remove(&pre_image);
 
-// ── Tests ──────────────────────────────────────────────────────
+                self.revision += 1;
+                let event = Event::TaskDeleted {
+                    revision: self.revision,
+                    task_id,
+                };
+                let event = self.record(event);
+                self.push_undo(user_id, event.clone(), Some(pre_image));
+                Ok(event)
+            }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+            Command::AddDependency { task_id, depends_on } => {
+                if !self.tasks.contains_key(&task_id) || !self.tasks.contains_key(&depends_on) {
+                    return Err(WorldError::TaskNotFound);
+                }
+                if self.reaches(depends_on, task_id) {
+                    return Err(WorldError::DependencyCycle);
+                }
 
-    // A known Wednesday (2026-02-11). Use this as a representative test date.
-    const D: u16 = 20495;
-    const D2: u16 = 20496; // Thursday 2026-02-12
+                let task = self.tasks.get_mut(&task_id).unwrap();
+                if !task.depends_on.contains(&depends_on) {
+                    task.depends_on.push(depends_on);
+                }
 
-    fn test_world() -> World {
-        let mut w = World::new();
-        w.services.insert(
-            Uuid::nil(),
-            Service { id: Uuid::nil(), name: "Test Service".into() },
-        );
-        w
-    }
+                self.revision += 1;
+                let event = Event::DependencyAdded {
+                    revision: self.revision,
+                    task_id,
+                    depends_on,
+                };
+                let event = self.record(event);
+                self.clear_redo(user_id);
+                Ok(event)
+            }
 
-    fn create_task(w: &mut World) -> Uuid {
-        let event = w.apply(
-            Command::CreateTask {
-                title: "Fix the thing".into(),
-                service_id: Uuid::nil(),
-                priority: Priority::Medium,
-                assigned_to: None,
-                date: None,
-                start_time: None,
-                duration: None,
-            },
-            Uuid::nil(),
-        ).unwrap();
+            Command::RemoveDependency { task_id, depends_on } => {
+                let task = self.tasks.get_mut(&task_id)
+                    .ok_or(WorldError::TaskNotFound)?;
+                task.depends_on.retain(|&id| id != depends_on);
 
-        match event {
-            Event::TaskCreated { task, .. } => task.id,
-            _ => panic!("expected TaskCreated"),
-        }
-    }
+                self.revision += 1;
+                let event = Event::DependencyRemoved {
+                    revision: self.revision,
+                    task_id,
+                    depends_on,
+                };
+                let event = self.record(event);
+                self.clear_redo(user_id);
+                Ok(event)
+            }
 
-    #[test]
-    fn create_task_starts_staged() {
-        let mut w = test_world();
-        let id = create_task(&mut w);
+            Command::Undo => {
+                let entry = self.undo_stacks.get_mut(&user_id)
+                    .and_then(Vec::pop)
+                    .ok_or(WorldError::NothingToUndo)?;
+                let event = self.invert(&entry)?;
+                self.redo_stacks.entry(user_id).or_default().push(entry);
+                Ok(event)
+            }
 
-        let task = &w.tasks[&id];
-        assert_eq!(task.status, TaskStatus::Staged);
-        assert_eq!(task.date, None);
-        assert_eq!(task.start_time, None);
-        assert_eq!(w.revision, 1);
+            Command::Redo => {
+                let entry = self.redo_stacks.get_mut(&user_id)
+                    .and_then(Vec::pop)
+                    .ok_or(WorldError::NothingToRedo)?;
+                let event = self.replay_forward(&entry)?;
+                self.undo_stacks.entry(user_id).or_default().push(entry);
+                Ok(event)
+            }
+        }
     }
 
-    #[test]
-    fn create_task_with_scheduling() {
+    /// Compute and apply the compensating mutation for `entry.event`, bump
+    /// the revision, and record the result as a brand-new forward event —
+    /// undo never rewinds `revision`, so the broadcast log stays append-only
+    /// and reconnect replay keeps working.
+    fn invert(&mut self, entry: &Undoable) -> Result<Event, WorldError> {
+        self.revision += 1;
+        let revision = self.revision;
+
+        let event = match &entry.event {
+            Event::TaskCreated { task, .. } => {
+                if let Some(removed) = self.tasks.remove(&task.id) {
+                    self.index_remove(&removed);
+                }
+                Event::TaskDeleted { revision, task_id: task.id }
+            }
+
+            Event::TaskScheduled { task_id, .. } => {
+                let pre = entry.pre_image.as_ref().ok_or(WorldError::TaskNotFound)?;
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    let before = task.clone();
+                    task.status = pre.status;
+                    task.date = pre.date;
+                    task.start_time = pre.start_time;
+                    task.duration = pre.duration;
+                    let after = task.clone();
+                    self.index_remove(&before);
+                    self.index_insert(&after);
+                }
+                Event::TaskUnscheduled { revision, task_id: *task_id }
+            }
+
+            Event::TaskMoved { task_id, .. } => {
+                let pre = entry.pre_image.as_ref().ok_or(WorldError::TaskNotFound)?;
+                let date = pre.date.ok_or(WorldError::TaskNotFound)?;
+                let start_time = pre.start_time.ok_or(WorldError::TaskNotFound)?;
+                let duration = pre.duration.ok_or(WorldError::TaskNotFound)?;
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    let before = task.clone();
+                    task.date = Some(date);
+                    task.start_time = Some(start_time);
+                    task.duration = Some(duration);
+                    let after = task.clone();
+                    self.index_remove(&before);
+                    self.index_insert(&after);
+                }
+                Event::TaskMoved { revision, task_id: *task_id, date, start_time, duration }
+            }
+
+            Event::TaskUnscheduled { task_id, .. } => {
+                let pre = entry.pre_image.as_ref().ok_or(WorldError::TaskNotFound)?;
+                let date = pre.date.ok_or(WorldError::TaskNotFound)?;
+                let start_time = pre.start_time.ok_or(WorldError::TaskNotFound)?;
+                let duration = pre.duration.ok_or(WorldError::TaskNotFound)?;
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    let before = task.clone();
+                    task.status = pre.status;
+                    task.date = Some(date);
+                    task.start_time = Some(start_time);
+                    task.duration = Some(duration);
+                    let after = task.clone();
+                    self.index_remove(&before);
+                    self.index_insert(&after);
+                }
+                Event::TaskScheduled { revision, task_id: *task_id, date, start_time, duration }
+            }
+
+            Event::TaskCompleted { task_id, .. } => {
+                let pre = entry.pre_image.as_ref().ok_or(WorldError::TaskNotFound)?;
+                let date = pre.date.ok_or(WorldError::TaskNotFound)?;
+                let start_time = pre.start_time.ok_or(WorldError::TaskNotFound)?;
+                let duration = pre.duration.ok_or(WorldError::TaskNotFound)?;
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    let before = task.clone();
+                    task.status = pre.status;
+                    let after = task.clone();
+                    self.index_remove(&before);
+                    self.index_insert(&after);
+                }
+                // `pre.status` is always Scheduled in practice (nothing in
+                // this codebase ever produces Active), so re-emitting
+                // TaskScheduled — which `apply_event` replays as status =
+                // Scheduled — reconstructs the right state.
+                Event::TaskScheduled { revision, task_id: *task_id, date, start_time, duration }
+            }
+
+            Event::TaskDeleted { .. } => {
+                let pre = entry.pre_image.clone().ok_or(WorldError::TaskNotFound)?;
+                self.index_insert(&pre);
+                self.tasks.insert(pre.id, pre.clone());
+                Event::TaskCreated { revision, task: pre }
+            }
+
+            Event::TaskFailed { task_id, .. } => {
+                let pre = entry.pre_image.as_ref().ok_or(WorldError::TaskNotFound)?;
+                let date = pre.date.ok_or(WorldError::TaskNotFound)?;
+                let start_time = pre.start_time.ok_or(WorldError::TaskNotFound)?;
+                let duration = pre.duration.ok_or(WorldError::TaskNotFound)?;
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    let before = task.clone();
+                    task.status = pre.status;
+                    task.failure = pre.failure.clone();
+                    let after = task.clone();
+                    self.index_remove(&before);
+                    self.index_insert(&after);
+                }
+                // Same reasoning as `TaskCompleted`'s invert — `pre.status`
+                // is always Scheduled in practice.
+                Event::TaskScheduled { revision, task_id: *task_id, date, start_time, duration }
+            }
+
+            Event::TaskRetried { task_id, .. } => {
+                let pre = entry.pre_image.as_ref().ok_or(WorldError::TaskNotFound)?;
+                let reason = pre.failure.clone().unwrap_or_default();
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    let before = task.clone();
+                    task.status = pre.status;
+                    task.date = pre.date;
+                    task.start_time = pre.start_time;
+                    task.duration = pre.duration;
+                    task.retry_count = pre.retry_count;
+                    let after = task.clone();
+                    self.index_remove(&before);
+                    self.index_insert(&after);
+                }
+                Event::TaskFailed { revision, task_id: *task_id, reason }
+            }
+
+            // Dependency and recurring-task events aren't pushed onto the
+            // undo stack (see `push_undo`'s call sites), so they never
+            // appear here.
+            other => unreachable!("event {other:?} is never pushed onto an undo stack"),
+        };
+
+        Ok(self.record(event))
+    }
+
+    /// Re-apply `entry.event`'s original mutation at a fresh revision — the
+    /// inverse of `invert`, used by `Command::Redo`.
+    fn replay_forward(&mut self, entry: &Undoable) -> Result<Event, WorldError> {
+        self.revision += 1;
+        let revision = self.revision;
+
+        let event = match &entry.event {
+            Event::TaskCreated { task, .. } => {
+                self.index_insert(task);
+                self.tasks.insert(task.id, task.clone());
+                Event::TaskCreated { revision, task: task.clone() }
+            }
+
+            Event::TaskScheduled { task_id, date, start_time, duration, .. } => {
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    let before = task.clone();
+                    task.status = TaskStatus::Scheduled;
+                    task.date = Some(*date);
+                    task.start_time = Some(*start_time);
+                    task.duration = Some(*duration);
+                    let after = task.clone();
+                    self.index_remove(&before);
+                    self.index_insert(&after);
+                }
+                Event::TaskScheduled { revision, task_id: *task_id, date: *date, start_time: *start_time, duration: *duration }
+            }
+
+            Event::TaskMoved { task_id, date, start_time, duration, .. } => {
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    let before = task.clone();
+                    task.date = Some(*date);
+                    task.start_time = Some(*start_time);
+                    task.duration = Some(*duration);
+                    let after = task.clone();
+                    self.index_remove(&before);
+                    self.index_insert(&after);
+                }
+                Event::TaskMoved { revision, task_id: *task_id, date: *date, start_time: *start_time, duration: *duration }
+            }
+
+            Event::TaskUnscheduled { task_id, .. } => {
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    let before = task.clone();
+                    task.status = TaskStatus::Staged;
+                    task.date = None;
+                    task.start_time = None;
+                    task.duration = None;
+                    let after = task.clone();
+                    self.index_remove(&before);
+                    self.index_insert(&after);
+                }
+                Event::TaskUnscheduled { revision, task_id: *task_id }
+            }
+
+            Event::TaskCompleted { task_id, .. } => {
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    let before = task.clone();
+                    task.status = TaskStatus::Completed;
+                    let after = task.clone();
+                    self.index_remove(&before);
+                    self.index_insert(&after);
+                }
+                Event::TaskCompleted { revision, task_id: *task_id }
+            }
+
+            Event::TaskDeleted { task_id, .. } => {
+                if let Some(removed) = self.tasks.remove(task_id) {
+                    self.index_remove(&removed);
+                }
+                Event::TaskDeleted { revision, task_id: *task_id }
+            }
+
+            Event::TaskFailed { task_id, reason, .. } => {
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    let before = task.clone();
+                    task.status = TaskStatus::Failed;
+                    task.failure = Some(reason.clone());
+                    let after = task.clone();
+                    self.index_remove(&before);
+                    self.index_insert(&after);
+                }
+                Event::TaskFailed { revision, task_id: *task_id, reason: reason.clone() }
+            }
+
+            Event::TaskRetried { task_id, .. } => {
+                let retry_count = if let Some(task) = self.tasks.get_mut(task_id) {
+                    let before = task.clone();
+                    task.status = TaskStatus::Staged;
+                    task.date = None;
+                    task.start_time = None;
+                    task.duration = None;
+                    task.retry_count += 1;
+                    let after = task.clone();
+                    self.index_remove(&before);
+                    self.index_insert(&after);
+                    after.retry_count
+                } else {
+                    0
+                };
+                Event::TaskRetried { revision, task_id: *task_id, retry_count }
+            }
+
+            other => unreachable!("event {other:?} is never pushed onto an undo stack"),
+        };
+
+        Ok(self.record(event))
+    }
+
+    /// Apply every command in `cmds` atomically under a single borrow of
+    /// `self`: either all of them succeed and their events are appended
+    /// under consecutive revisions, or the first failure rolls back every
+    /// mutation this batch already made, leaving `revision`, `tasks`, and
+    /// the issuing user's undo/redo history exactly as they were.
+    ///
+    /// Used for drag-operations that move several tasks at once — the
+    /// client must never observe a half-applied batch.
+    pub fn apply_batch(&mut self, cmds: Vec<Command>, user_id: Uuid) -> Result<Vec<Event>, WorldError> {
+        let revision_before = self.revision;
+        let log_len_before = self.log.len();
+        let undo_stack_before = self.undo_stacks.get(&user_id).cloned();
+        let redo_stack_before = self.redo_stacks.get(&user_id).cloned();
+
+        // Clone-on-write snapshot of every task this batch has touched so
+        // far, keyed by id; `None` means the id didn't exist before the
+        // batch (a task created mid-batch, to be dropped on rollback).
+        let mut touched: HashMap<Uuid, Option<Task>> = HashMap::new();
+        let mut events = Vec::with_capacity(cmds.len());
+
+        for cmd in cmds {
+            if let Some(id) = self.peek_affected_task_id(&cmd, user_id) {
+                touched.entry(id).or_insert_with(|| self.tasks.get(&id).cloned());
+            }
+
+            match self.apply(cmd, user_id) {
+                Ok(event) => {
+                    if let Some(id) = event_task_id(&event) {
+                        touched.entry(id).or_insert(None);
+                    }
+                    events.push(event);
+                }
+                Err(err) => {
+                    for (id, pre) in touched {
+                        match pre {
+                            Some(task) => { self.tasks.insert(id, task); }
+                            None => { self.tasks.remove(&id); }
+                        }
+                    }
+                    // Rewinding `tasks` directly bypasses index_insert/remove,
+                    // so resync by_status/by_assignee from scratch rather than
+                    // trying to replay every touched id's index delta.
+                    self.rebuild_indexes();
+                    self.revision = revision_before;
+                    self.log.truncate(log_len_before);
+                    match undo_stack_before {
+                        Some(stack) => { self.undo_stacks.insert(user_id, stack); }
+                        None => { self.undo_stacks.remove(&user_id); }
+                    }
+                    match redo_stack_before {
+                        Some(stack) => { self.redo_stacks.insert(user_id, stack); }
+                        None => { self.redo_stacks.remove(&user_id); }
+                    }
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// The task id `cmd` is about to mutate, if any — looked up *before*
+    /// calling [`World::apply`] so `apply_batch` can snapshot its pre-image.
+    /// `Undo`/`Redo` peek the top of the relevant stack without popping it.
+    fn peek_affected_task_id(&self, cmd: &Command, user_id: Uuid) -> Option<Uuid> {
+        match cmd {
+            Command::ScheduleTask { task_id, .. }
+            | Command::MoveTask { task_id, .. }
+            | Command::UnscheduleTask { task_id }
+            | Command::CompleteTask { task_id }
+            | Command::DeleteTask { task_id }
+            | Command::FailTask { task_id, .. }
+            | Command::RetryTask { task_id }
+            | Command::AddDependency { task_id, .. }
+            | Command::RemoveDependency { task_id, .. } => Some(*task_id),
+            Command::Undo => self.undo_stacks.get(&user_id)
+                .and_then(|stack| stack.last())
+                .and_then(|entry| event_task_id(&entry.event)),
+            Command::Redo => self.redo_stacks.get(&user_id)
+                .and_then(|stack| stack.last())
+                .and_then(|entry| event_task_id(&entry.event)),
+            Command::CreateTask { .. } | Command::CreateRecurringTask { .. } => None,
+        }
+    }
+
+    /// `true` if `start` can reach `target` by following `depends_on` edges
+    /// (DFS over the dependency graph). Used by `AddDependency` to reject an
+    /// edge that would close a cycle: adding `task_id -> depends_on` is only
+    /// safe if `depends_on` can't already get back to `task_id`.
+    fn reaches(&self, start: Uuid, target: Uuid) -> bool {
+        let mut stack = vec![start];
+        let mut seen = std::collections::HashSet::new();
+        while let Some(id) = stack.pop() {
+            if id == target {
+                return true;
+            }
+            if !seen.insert(id) {
+                continue;
+            }
+            if let Some(task) = self.tasks.get(&id) {
+                stack.extend(task.depends_on.iter().copied());
+            }
+        }
+        false
+    }
+
+    /// Err(BlockedByDependency) if `task_id` has any dependency that isn't
+    /// `Completed` yet.
+    fn check_dependencies_complete(&self, task_id: Uuid) -> Result<(), WorldError> {
+        let task = self.tasks.get(&task_id).ok_or(WorldError::TaskNotFound)?;
+        let blocked = task.depends_on.iter().any(|dep_id| {
+            !matches!(self.tasks.get(dep_id), Some(dep) if dep.status == TaskStatus::Completed)
+        });
+        if blocked {
+            return Err(WorldError::BlockedByDependency);
+        }
+        Ok(())
+    }
+
+    /// Look up a user by username (linear scan — fine for 5-20 users).
+    pub fn get_user_by_username(&self, username: &str) -> Option<&User> {
+        self.users.values().find(|u| u.username == username)
+    }
+
+    /// The id of a non-completed task already assigned to `assignee` on
+    /// `date` whose `[start, start + dur)` interval overlaps the requested
+    /// one, if any. `exclude_id` is left out of the scan — the task being
+    /// scheduled or moved, which is allowed to overlap itself.
+    ///
+    /// Two half-open intervals `[a.start, a.end)` and `[b.start, b.end)`
+    /// overlap iff `a.start < b.end && b.start < a.end`.
+    fn find_conflict(&self, date: u16, start: u16, dur: u16, assignee: Uuid, exclude_id: Uuid) -> Option<Uuid> {
+        let end = start + dur;
+        self.tasks.values().find_map(|t| {
+            if t.id == exclude_id
+                || t.assigned_to != Some(assignee)
+                || t.date != Some(date)
+                || t.status == TaskStatus::Completed
+            {
+                return None;
+            }
+            let t_start = t.start_time?;
+            let t_end = t_start + t.duration?;
+            if start < t_end && t_start < end {
+                Some(t.id)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// `assignee`'s scheduled tasks on `date`, sorted by `start_time` —
+    /// the conflict-free view IRONCLAD renders as a day column.
+    pub fn day_agenda(&self, date: u16, assignee: Uuid) -> Vec<&Task> {
+        let mut agenda: Vec<&Task> = self.tasks.values()
+            .filter(|t| t.date == Some(date) && t.assigned_to == Some(assignee))
+            .collect();
+        agenda.sort_by_key(|t| t.start_time.unwrap_or(0));
+        agenda
+    }
+
+    /// Get all Staged tasks, sorted by priority (highest first).
+    /// This is the staging queue that IRONCLAD renders as a sidebar list.
+    pub fn staging_queue(&self) -> Vec<&Task> {
+        let mut staged = self.staged_tasks();
+        // Sort by priority descending (Urgent first, Low last)
+        staged.sort_by(|a, b| b.priority.cmp(&a.priority));
+        staged
+    }
+
+    /// Staged tasks with no incomplete dependency — the subset of
+    /// `staging_queue` that's actually unblocked right now, so IRONCLAD can
+    /// render it as a distinct "ready" sidebar. Sorted by priority (highest
+    /// first), same as `staging_queue`.
+    pub fn ready_queue(&self) -> Vec<&Task> {
+        let mut ready: Vec<&Task> = self.staged_tasks().into_iter()
+            .filter(|t| {
+                t.depends_on.iter().all(|dep_id| {
+                    matches!(self.tasks.get(dep_id), Some(dep) if dep.status == TaskStatus::Completed)
+                })
+            })
+            .collect();
+        ready.sort_by(|a, b| b.priority.cmp(&a.priority));
+        ready
+    }
+
+    /// `by_status[Staged]` materialized into task references, unsorted.
+    fn staged_tasks(&self) -> Vec<&Task> {
+        self.by_status.get(&TaskStatus::Staged)
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.tasks.get(id))
+            .collect()
+    }
+
+    /// The id-set `query` should materialize from, or `None` to fall back
+    /// to a full scan of `tasks` — only `date_range` can't be served by an
+    /// index, so a filter with neither `status` nor `assignee` has nothing
+    /// to intersect.
+    fn candidate_ids(&self, filter: &TaskFilter) -> Option<HashSet<Uuid>> {
+        match (filter.status, filter.assignee) {
+            (Some(status), Some(assignee)) => {
+                let by_status = self.by_status.get(&status);
+                let by_assignee = self.by_assignee.get(&assignee);
+                Some(match (by_status, by_assignee) {
+                    (Some(a), Some(b)) => a.intersection(b).copied().collect(),
+                    _ => HashSet::new(),
+                })
+            }
+            (Some(status), None) => Some(self.by_status.get(&status).cloned().unwrap_or_default()),
+            (None, Some(assignee)) => Some(self.by_assignee.get(&assignee).cloned().unwrap_or_default()),
+            (None, None) => None,
+        }
+    }
+
+    /// Filter tasks by status/assignee/date-range, intersecting the
+    /// relevant `by_status`/`by_assignee` id-sets before materializing any
+    /// `Task` — a dashboard filtering "my active tasks this week" never
+    /// re-scans the whole task map. Unsorted; callers impose their own order.
+    pub fn query(&self, filter: TaskFilter) -> Vec<&Task> {
+        let in_range = |t: &&Task| match filter.date_range {
+            Some((start, end)) => matches!(t.date, Some(d) if d >= start && d <= end),
+            None => true,
+        };
+
+        match self.candidate_ids(&filter) {
+            Some(ids) => ids.iter()
+                .filter_map(|id| self.tasks.get(id))
+                .filter(in_range)
+                .collect(),
+            None => self.tasks.values().filter(in_range).collect(),
+        }
+    }
+
+    /// Walk every recurring template task and, for each weekday occurrence
+    /// in `[today, today + horizon_days]` that hasn't already been
+    /// materialized, create the concrete child task and push the usual
+    /// `TaskCreated` event so reconnecting clients replay it like any other
+    /// creation. `(parent_id, date)` is the dedup key, so calling this
+    /// repeatedly (e.g. once per day at boot) is a no-op for occurrences
+    /// that already exist.
+    pub fn materialize_recurring(&mut self, today: u16) {
+        let templates: Vec<(Uuid, RecurrenceRule, Uuid, String, Priority, Uuid)> = self
+            .tasks
+            .values()
+            .filter_map(|t| {
+                t.recurrence.clone().map(|rule| (t.id, rule, t.created_by, t.title.clone(), t.priority, t.service_id))
+            })
+            .collect();
+
+        for (parent_id, rule, created_by, title, priority, service_id) in templates {
+            for date in today..=today.saturating_add(rule.horizon_days) {
+                let weekday = ((date + 3) % 7) as u8;
+                if !rule.weekdays.contains(&weekday) {
+                    continue;
+                }
+
+                let already_materialized = self
+                    .tasks
+                    .values()
+                    .any(|t| t.parent_id == Some(parent_id) && t.date == Some(date));
+                if already_materialized {
+                    continue;
+                }
+
+                let task = Task {
+                    id: Uuid::new_v4(),
+                    title: title.clone(),
+                    status: TaskStatus::Scheduled,
+                    priority,
+                    service_id,
+                    created_by,
+                    assigned_to: None,
+                    date: Some(date),
+                    start_time: Some(rule.start_time),
+                    duration: Some(rule.duration),
+                    recurrence: None,
+                    parent_id: Some(parent_id),
+                    depends_on: Vec::new(),
+                    failure: None,
+                    retry_count: 0,
+                };
+
+                self.revision += 1;
+                let event = Event::TaskCreated { revision: self.revision, task: task.clone() };
+                self.index_insert(&task);
+                self.tasks.insert(task.id, task);
+                self.record(event);
+            }
+        }
+    }
+
+    /// Fold an already-applied `Event` into this World's state without
+    /// re-validating the command that produced it, re-emitting side effects,
+    /// or touching the replay log. Used to replay the journal from
+    /// `SaveFile::load_world_at` — the event's own `revision` is trusted.
+    pub fn apply_event(&mut self, event: &Event) {
+        match event {
+            Event::TaskCreated { revision, task } => {
+                self.tasks.insert(task.id, task.clone());
+                self.revision = *revision;
+            }
+            Event::TaskScheduled { revision, task_id, date, start_time, duration } => {
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    task.status = TaskStatus::Scheduled;
+                    task.date = Some(*date);
+                    task.start_time = Some(*start_time);
+                    task.duration = Some(*duration);
+                }
+                self.revision = *revision;
+            }
+            Event::TaskMoved { revision, task_id, date, start_time, duration } => {
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    task.date = Some(*date);
+                    task.start_time = Some(*start_time);
+                    task.duration = Some(*duration);
+                }
+                self.revision = *revision;
+            }
+            Event::TaskUnscheduled { revision, task_id } => {
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    task.status = TaskStatus::Staged;
+                    task.date = None;
+                    task.start_time = None;
+                    task.duration = None;
+                }
+                self.revision = *revision;
+            }
+            Event::TaskCompleted { revision, task_id } => {
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    task.status = TaskStatus::Completed;
+                }
+                self.revision = *revision;
+            }
+            Event::TaskDeleted { revision, task_id } => {
+                self.tasks.remove(task_id);
+                self.revision = *revision;
+            }
+            Event::DependencyAdded { revision, task_id, depends_on } => {
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    if !task.depends_on.contains(depends_on) {
+                        task.depends_on.push(*depends_on);
+                    }
+                }
+                self.revision = *revision;
+            }
+            Event::DependencyRemoved { revision, task_id, depends_on } => {
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    task.depends_on.retain(|id| id != depends_on);
+                }
+                self.revision = *revision;
+            }
+            Event::TaskFailed { revision, task_id, reason } => {
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    task.status = TaskStatus::Failed;
+                    task.failure = Some(reason.clone());
+                }
+                self.revision = *revision;
+            }
+            Event::TaskRetried { revision, task_id, retry_count } => {
+                if let Some(task) = self.tasks.get_mut(task_id) {
+                    task.status = TaskStatus::Staged;
+                    task.date = None;
+                    task.start_time = None;
+                    task.duration = None;
+                    task.retry_count = *retry_count;
+                }
+                self.revision = *revision;
+            }
+        }
+    }
+
+    /// Get all events since a given revision (for reconnect replay).
+    /// `Err(NeedsSnapshot)` if the revision is too old for `log` to serve
+    /// directly — the caller should bootstrap from the snapshot it carries
+    /// and replay its `tail` instead.
+    pub fn events_since(&self, since_rev: Revision) -> Result<&[(Revision, Event)], NeedsSnapshot> {
+        // Find the first log entry after since_rev
+        let start = self.log.iter().position(|(rev, _)| *rev > since_rev);
+        match start {
+            Some(0) if self.log[0].0 > since_rev + 1 => Err(self.needs_snapshot()), // gap: earlier events were trimmed
+            Some(idx) => Ok(&self.log[idx..]),
+            None if since_rev >= self.revision => Ok(&[]), // up to date
+            None => Err(self.needs_snapshot()), // too old, log was trimmed
+        }
+    }
+
+    /// Build the `NeedsSnapshot` payload for `events_since`'s error path:
+    /// the most recent `compact` snapshot plus the log tail after it, or —
+    /// if `compact` has never been called — a snapshot of the live current
+    /// state (with an empty tail, since nothing postdates "now").
+    fn needs_snapshot(&self) -> NeedsSnapshot {
+        match &self.snapshot {
+            Some(snapshot) => NeedsSnapshot {
+                snapshot: snapshot.clone(),
+                tail: self
+                    .log
+                    .iter()
+                    .filter(|(rev, _)| *rev > snapshot.base_revision)
+                    .cloned()
+                    .collect(),
+            },
+            None => NeedsSnapshot {
+                snapshot: WorldSnapshot {
+                    base_revision: self.revision,
+                    tasks: self.tasks.clone(),
+                    users: self.users.clone(),
+                    services: self.services.clone(),
+                },
+                tail: Vec::new(),
+            },
+        }
+    }
+
+    /// Fold every event up to `up_to` (clamped to `self.revision`) into a
+    /// fresh `WorldSnapshot`, then drop those entries from `log`. Replays
+    /// forward from the previous snapshot (or from empty state, the first
+    /// time) via `apply_event` on a scratch `World` — `World` only ever
+    /// holds its live current state, not a history of past ones, so this
+    /// is how it reconstructs "state as of `up_to`" without re-deriving it
+    /// from `self`, which may already be ahead of `up_to`.
+    pub fn compact(&mut self, up_to: Revision) {
+        let up_to = up_to.min(self.revision);
+        let (base_revision, tasks, users, services) = match &self.snapshot {
+            Some(s) => (s.base_revision, s.tasks.clone(), s.users.clone(), s.services.clone()),
+            None => (0, HashMap::new(), HashMap::new(), HashMap::new()),
+        };
+        if up_to <= base_revision {
+            return; // nothing new to fold in
+        }
+
+        let mut scratch = World { tasks, users, services, revision: base_revision, ..World::new() };
+        for (_, event) in self.log.iter().filter(|(rev, _)| *rev > base_revision && *rev <= up_to) {
+            scratch.apply_event(event);
+        }
+
+        self.snapshot = Some(WorldSnapshot {
+            base_revision: up_to,
+            tasks: scratch.tasks,
+            users: scratch.users,
+            services: scratch.services,
+        });
+        self.log.retain(|(rev, _)| *rev > up_to);
+    }
+}
+
+/// The task id an event mutated, if any — `apply_batch`'s rollback uses
+/// this to work out which task an `Undo`/`Redo` command (which carries no
+/// task id of its own) just touched.
+fn event_task_id(event: &Event) -> Option<Uuid> {
+    match event {
+        Event::TaskCreated { task, .. } => Some(task.id),
+        Event::TaskScheduled { task_id, .. }
+        | Event::TaskMoved { task_id, .. }
+        | Event::TaskUnscheduled { task_id, .. }
+        | Event::TaskCompleted { task_id, .. }
+        | Event::TaskDeleted { task_id, .. }
+        | Event::DependencyAdded { task_id, .. }
+        | Event::DependencyRemoved { task_id, .. }
+        | Event::TaskFailed { task_id, .. }
+        | Event::TaskRetried { task_id, .. } => Some(*task_id),
+    }
+}
+
+// ── Validation helpers ─────────────────────────────────────────
+
+/// Validate scheduling fields.
+///
+/// date: epoch days (any value except 0xFFFF which is the staged sentinel)
+/// start_time: minutes from midnight, must be on 15-min grid
+/// duration: minutes, must be on 15-min grid, must not overflow past midnight
+fn validate_scheduling(date: u16, start_time: u16, duration: u16) -> Result<(), WorldError> {
+    if date == 0xFFFF {
+        return Err(WorldError::InvalidDate);
+    }
+    validate_time_slot(start_time, duration)
+}
+
+/// The start_time/duration half of `validate_scheduling`, usable on its own
+/// by callers (e.g. `CreateRecurringTask`) that don't have a `date` to check.
+fn validate_time_slot(start_time: u16, duration: u16) -> Result<(), WorldError> {
+    // 24 hours = 1440 minutes. Must be on 15-min grid.
+    if start_time >= 1440 || start_time % 15 != 0 {
+        return Err(WorldError::InvalidTime);
+    }
+    // Duration: at least 15 min, on 15-min grid, doesn't go past midnight
+    if duration == 0 || duration % 15 != 0 || start_time + duration > 1440 {
+        return Err(WorldError::InvalidDuration);
+    }
+    Ok(())
+}
+
+/// Parse a 5-field cron expression (`minute hour dom month dow`) into
+/// `(minute_of_day, weekdays)`. Only the minute, hour, and weekday fields are
+/// meaningful here — `dom`/`month` aren't supported (every occurrence is
+/// weekday-driven) but must still be present for the expression to parse.
+///
+/// `dow` uses this file's day convention (0=Mon..6=Sun, matching
+/// `(date + 3) % 7`), not Unix cron's 0=Sun — and `*` means every day.
+fn parse_cron(cron: &str) -> Result<(u16, Vec<u8>), WorldError> {
+    let fields: Vec<&str> = cron.split_whitespace().collect();
+    if fields.len() != 5 {
+        return Err(WorldError::InvalidCron);
+    }
+
+    let minute: u16 = fields[0].parse().map_err(|_| WorldError::InvalidCron)?;
+    let hour: u16 = fields[1].parse().map_err(|_| WorldError::InvalidCron)?;
+    if minute >= 60 || hour >= 24 {
+        return Err(WorldError::InvalidCron);
+    }
+    let minute_of_day = hour * 60 + minute;
+    if minute_of_day % 15 != 0 {
+        return Err(WorldError::InvalidCron);
+    }
+
+    let weekdays = if fields[4] == "*" {
+        (0..7).collect()
+    } else {
+        fields[4]
+            .split(',')
+            .map(|s| s.parse::<u8>().map_err(|_| WorldError::InvalidCron))
+            .collect::<Result<Vec<u8>, WorldError>>()?
+    };
+    if weekdays.iter().any(|&d| d > 6) {
+        return Err(WorldError::InvalidCron);
+    }
+
+    Ok((minute_of_day, weekdays))
+}
+
+// ── Tests ──────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A known Wednesday (2026-02-11). Use this as a representative test date.
+    const D: u16 = 20495;
+    const D2: u16 = 20496; // Thursday 2026-02-12
+
+    fn test_world() -> World {
+        let mut w = World::new();
+        w.services.insert(
+            Uuid::nil(),
+            Service { id: Uuid::nil(), name: "Test Service".into() },
+        );
+        w
+    }
+
+    fn create_task(w: &mut World) -> Uuid {
+        let event = w.apply(
+            Command::CreateTask {
+                title: "Fix the thing".into(),
+                service_id: Uuid::nil(),
+                priority: Priority::Medium,
+                assigned_to: None,
+                date: None,
+                start_time: None,
+                duration: None,
+            },
+            Uuid::nil(),
+        ).unwrap();
+
+        match event {
+            Event::TaskCreated { task, .. } => task.id,
+            _ => panic!("expected TaskCreated"),
+        }
+    }
+
+    #[test]
+    fn create_task_starts_staged() {
+        let mut w = test_world();
+        let id = create_task(&mut w);
+
+        let task = &w.tasks[&id];
+        assert_eq!(task.status, TaskStatus::Staged);
+        assert_eq!(task.date, None);
+        assert_eq!(task.start_time, None);
+        assert_eq!(w.revision, 1);
+    }
+
+    #[test]
+    fn create_task_with_scheduling() {
+        let mut w = test_world();
+        let event = w.apply(
+            Command::CreateTask {
+                title: "New task".into(),
+                service_id: Uuid::nil(),
+                priority: Priority::Medium,
+                assigned_to: None,
+                date: Some(D),
+                start_time: Some(540),
+                duration: Some(30),
+            },
+            Uuid::nil(),
+        ).unwrap();
+
+        let id = match event {
+            Event::TaskCreated { task, .. } => task.id,
+            _ => panic!("expected TaskCreated"),
+        };
+
+        let task = &w.tasks[&id];
+        assert_eq!(task.status, TaskStatus::Scheduled);
+        assert_eq!(task.date, Some(D));
+        assert_eq!(task.start_time, Some(540));
+        assert_eq!(task.duration, Some(30));
+    }
+
+    #[test]
+    fn create_task_with_staged_sentinel_rejected() {
+        // 0xFFFF is the staged sentinel — passing it as a date is invalid
+        let mut w = test_world();
+        let result = w.apply(
+            Command::CreateTask {
+                title: "Bad".into(),
+                service_id: Uuid::nil(),
+                priority: Priority::Medium,
+                assigned_to: None,
+                date: Some(0xFFFF),
+                start_time: Some(540),
+                duration: Some(30),
+            },
+            Uuid::nil(),
+        );
+        assert_eq!(result.unwrap_err(), WorldError::InvalidDate);
+    }
+
+    #[test]
+    fn create_task_requires_valid_service() {
+        let mut w = World::new(); // no services
+        let result = w.apply(
+            Command::CreateTask {
+                title: "Orphan".into(),
+                service_id: Uuid::new_v4(),
+                priority: Priority::Low,
+                assigned_to: None,
+                date: None,
+                start_time: None,
+                duration: None,
+            },
+            Uuid::nil(),
+        );
+        assert_eq!(result.unwrap_err(), WorldError::ServiceNotFound);
+        assert_eq!(w.revision, 0); // nothing changed
+    }
+
+    #[test]
+    fn schedule_staged_task() {
+        let mut w = test_world();
+        let id = create_task(&mut w);
+
+        w.apply(
+            Command::ScheduleTask { task_id: id, date: D, start_time: 540, duration: 60 },
+            Uuid::nil(),
+        ).unwrap();
+
+        let task = &w.tasks[&id];
+        assert_eq!(task.status, TaskStatus::Scheduled);
+        assert_eq!(task.date, Some(D));
+        assert_eq!(task.start_time, Some(540)); // 9:00 AM
+        assert_eq!(task.duration, Some(60));    // 1 hour
+        assert_eq!(w.revision, 2);
+    }
+
+    #[test]
+    fn cannot_schedule_already_scheduled() {
+        let mut w = test_world();
+        let id = create_task(&mut w);
+
+        w.apply(
+            Command::ScheduleTask { task_id: id, date: D, start_time: 480, duration: 30 },
+            Uuid::nil(),
+        ).unwrap();
+
+        let result = w.apply(
+            Command::ScheduleTask { task_id: id, date: D2, start_time: 600, duration: 30 },
+            Uuid::nil(),
+        );
+        assert_eq!(result.unwrap_err(), WorldError::InvalidTransition);
+    }
+
+    #[test]
+    fn move_scheduled_task() {
+        let mut w = test_world();
+        let id = create_task(&mut w);
+
+        w.apply(
+            Command::ScheduleTask { task_id: id, date: D, start_time: 480, duration: 60 },
+            Uuid::nil(),
+        ).unwrap();
+
+        w.apply(
+            Command::MoveTask { task_id: id, date: D2, start_time: 840, duration: 90, allow_overlap: false },
+            Uuid::nil(),
+        ).unwrap();
+
+        let task = &w.tasks[&id];
+        assert_eq!(task.date, Some(D2));
+        assert_eq!(task.start_time, Some(840)); // 2:00 PM
+        assert_eq!(task.duration, Some(90));    // 1.5 hours
+        assert_eq!(w.revision, 3);
+    }
+
+    #[test]
+    fn cannot_move_staged_task() {
+        let mut w = test_world();
+        let id = create_task(&mut w);
+
+        let result = w.apply(
+            Command::MoveTask { task_id: id, date: D, start_time: 480, duration: 60, allow_overlap: false },
+            Uuid::nil(),
+        );
+        assert_eq!(result.unwrap_err(), WorldError::InvalidTransition);
+    }
+
+    #[test]
+    fn unschedule_puts_task_back_in_staging() {
+        let mut w = test_world();
+        let id = create_task(&mut w);
+
+        w.apply(
+            Command::ScheduleTask { task_id: id, date: D, start_time: 600, duration: 30 },
+            Uuid::nil(),
+        ).unwrap();
+
+        w.apply(Command::UnscheduleTask { task_id: id }, Uuid::nil()).unwrap();
+
+        let task = &w.tasks[&id];
+        assert_eq!(task.status, TaskStatus::Staged);
+        assert_eq!(task.date, None);
+        assert_eq!(task.start_time, None);
+        assert_eq!(task.duration, None);
+    }
+
+    #[test]
+    fn complete_scheduled_task() {
+        let mut w = test_world();
+        let id = create_task(&mut w);
+
+        w.apply(
+            Command::ScheduleTask { task_id: id, date: D, start_time: 480, duration: 60 },
+            Uuid::nil(),
+        ).unwrap();
+
+        w.apply(Command::CompleteTask { task_id: id }, Uuid::nil()).unwrap();
+
+        assert_eq!(w.tasks[&id].status, TaskStatus::Completed);
+    }
+
+    #[test]
+    fn cannot_complete_staged_task() {
+        let mut w = test_world();
+        let id = create_task(&mut w);
+
+        let result = w.apply(Command::CompleteTask { task_id: id }, Uuid::nil());
+        assert_eq!(result.unwrap_err(), WorldError::InvalidTransition);
+    }
+
+    #[test]
+    fn delete_task() {
+        let mut w = test_world();
+        let id = create_task(&mut w);
+
+        w.apply(Command::DeleteTask { task_id: id }, Uuid::nil()).unwrap();
+
+        assert!(!w.tasks.contains_key(&id));
+    }
+
+    #[test]
+    fn delete_nonexistent_task() {
+        let mut w = test_world();
+        let result = w.apply(
+            Command::DeleteTask { task_id: Uuid::new_v4() },
+            Uuid::nil(),
+        );
+        assert_eq!(result.unwrap_err(), WorldError::TaskNotFound);
+    }
+
+    #[test]
+    fn staging_queue_sorted_by_priority() {
+        let mut w = test_world();
+        let user = Uuid::nil();
+
+        w.apply(Command::CreateTask {
+            title: "Low".into(), service_id: Uuid::nil(),
+            priority: Priority::Low, assigned_to: None,
+            date: None, start_time: None, duration: None,
+        }, user).unwrap();
+
+        w.apply(Command::CreateTask {
+            title: "Urgent".into(), service_id: Uuid::nil(),
+            priority: Priority::Urgent, assigned_to: None,
+            date: None, start_time: None, duration: None,
+        }, user).unwrap();
+
+        w.apply(Command::CreateTask {
+            title: "High".into(), service_id: Uuid::nil(),
+            priority: Priority::High, assigned_to: None,
+            date: None, start_time: None, duration: None,
+        }, user).unwrap();
+
+        let queue = w.staging_queue();
+        assert_eq!(queue.len(), 3);
+        assert_eq!(queue[0].priority, Priority::Urgent);
+        assert_eq!(queue[1].priority, Priority::High);
+        assert_eq!(queue[2].priority, Priority::Low);
+    }
+
+    #[test]
+    fn scheduling_validation() {
+        let mut w = test_world();
+        let id = create_task(&mut w);
+
+        // Staged sentinel (0xFFFF) is not a valid date
+        let r = w.apply(
+            Command::ScheduleTask { task_id: id, date: 0xFFFF, start_time: 480, duration: 60 },
+            Uuid::nil(),
+        );
+        assert_eq!(r.unwrap_err(), WorldError::InvalidDate);
+
+        // Time not on 15-min grid
+        let r = w.apply(
+            Command::ScheduleTask { task_id: id, date: D, start_time: 487, duration: 60 },
+            Uuid::nil(),
+        );
+        assert_eq!(r.unwrap_err(), WorldError::InvalidTime);
+
+        // Duration zero
+        let r = w.apply(
+            Command::ScheduleTask { task_id: id, date: D, start_time: 480, duration: 0 },
+            Uuid::nil(),
+        );
+        assert_eq!(r.unwrap_err(), WorldError::InvalidDuration);
+
+        // Goes past midnight
+        let r = w.apply(
+            Command::ScheduleTask { task_id: id, date: D, start_time: 1380, duration: 120 },
+            Uuid::nil(),
+        );
+        assert_eq!(r.unwrap_err(), WorldError::InvalidDuration);
+    }
+
+    #[test]
+    fn revision_increments_on_every_mutation() {
+        let mut w = test_world();
+        assert_eq!(w.revision, 0);
+
+        let id = create_task(&mut w);
+        assert_eq!(w.revision, 1);
+
+        w.apply(
+            Command::ScheduleTask { task_id: id, date: D, start_time: 480, duration: 60 },
+            Uuid::nil(),
+        ).unwrap();
+        assert_eq!(w.revision, 2);
+
+        w.apply(
+            Command::MoveTask { task_id: id, date: D2, start_time: 600, duration: 30, allow_overlap: false },
+            Uuid::nil(),
+        ).unwrap();
+        assert_eq!(w.revision, 3);
+
+        w.apply(Command::CompleteTask { task_id: id }, Uuid::nil()).unwrap();
+        assert_eq!(w.revision, 4);
+    }
+
+    #[test]
+    fn event_log_tracks_history() {
+        let mut w = test_world();
+        let id = create_task(&mut w);
+
+        w.apply(
+            Command::ScheduleTask { task_id: id, date: D, start_time: 480, duration: 60 },
+            Uuid::nil(),
+        ).unwrap();
+
+        assert_eq!(w.log.len(), 2);
+        assert_eq!(w.log[0].0, 1); // rev 1 = create
+        assert_eq!(w.log[1].0, 2); // rev 2 = schedule
+    }
+
+    #[test]
+    fn events_since_for_reconnect() {
+        let mut w = test_world();
+        create_task(&mut w); // rev 1
+        create_task(&mut w); // rev 2
+        create_task(&mut w); // rev 3
+
+        // Client last saw rev 1, needs events 2 and 3
+        let events = w.events_since(1).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].0, 2);
+        assert_eq!(events[1].0, 3);
+
+        // Client is up to date
+        let events = w.events_since(3).unwrap();
+        assert_eq!(events.len(), 0);
+
+        // Client at rev 0, needs everything
+        let events = w.events_since(0).unwrap();
+        assert_eq!(events.len(), 3);
+    }
+
+    #[test]
+    fn log_is_trimmed_to_capacity() {
+        let mut w = test_world();
+        w.set_log_capacity(2);
+
+        w.apply(Command::CreateTask {
+            title: "A".into(), service_id: Uuid::nil(),
+            priority: Priority::Low, assigned_to: None,
+            date: None, start_time: None, duration: None,
+        }, Uuid::nil()).unwrap(); // rev 1, trimmed away
+
+        w.apply(Command::CreateTask {
+            title: "B".into(), service_id: Uuid::nil(),
+            priority: Priority::Low, assigned_to: None,
+            date: None, start_time: None, duration: None,
+        }, Uuid::nil()).unwrap(); // rev 2
+
+        w.apply(Command::CreateTask {
+            title: "C".into(), service_id: Uuid::nil(),
+            priority: Priority::Low, assigned_to: None,
+            date: None, start_time: None, duration: None,
+        }, Uuid::nil()).unwrap(); // rev 3
+
+        assert_eq!(w.log.len(), 2);
+        assert_eq!(w.log[0].0, 2);
+        assert_eq!(w.log[1].0, 3);
+
+        // Client at rev 1 has no gap (rev 2 follows directly) — replay works.
+        assert_eq!(w.events_since(1).unwrap().len(), 2);
+        // Client at rev 0 is missing the trimmed rev-1 event — demand a snapshot.
+        // `compact` was never called, so the snapshot is synthesized from the
+        // live current state (all 3 tasks, since trim_log only drops replay
+        // history, not the tasks themselves).
+        let needs = w.events_since(0).unwrap_err();
+        assert_eq!(needs.snapshot.base_revision, 3);
+        assert_eq!(needs.snapshot.tasks.len(), 3);
+        assert!(needs.tail.is_empty());
+    }
+
+    #[test]
+    fn failed_commands_dont_change_state() {
+        let mut w = test_world();
+        let rev_before = w.revision;
+        let log_len_before = w.log.len();
+
+        // Try to delete a task that doesn't exist
+        let _ = w.apply(
+            Command::DeleteTask { task_id: Uuid::new_v4() },
+            Uuid::nil(),
+        );
+
+        assert_eq!(w.revision, rev_before);
+        assert_eq!(w.log.len(), log_len_before);
+    }
+
+    #[test]
+    fn parse_cron_extracts_minute_of_day_and_weekdays() {
+        // 9:00 AM on Mon/Wed/Fri
+        let (minute_of_day, weekdays) = parse_cron("0 9 * * 0,2,4").unwrap();
+        assert_eq!(minute_of_day, 540);
+        assert_eq!(weekdays, vec![0, 2, 4]);
+    }
+
+    #[test]
+    fn parse_cron_star_weekday_means_every_day() {
+        let (_, weekdays) = parse_cron("30 14 * * *").unwrap();
+        assert_eq!(weekdays, vec![0, 1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn parse_cron_rejects_off_grid_time() {
+        assert_eq!(parse_cron("7 9 * * *").unwrap_err(), WorldError::InvalidCron);
+    }
+
+    #[test]
+    fn parse_cron_rejects_wrong_field_count() {
+        assert_eq!(parse_cron("0 9 * *").unwrap_err(), WorldError::InvalidCron);
+    }
+
+    #[test]
+    fn parse_cron_rejects_out_of_range_weekday() {
+        assert_eq!(parse_cron("0 9 * * 7").unwrap_err(), WorldError::InvalidCron);
+    }
+
+    #[test]
+    fn create_recurring_task_is_staged_with_no_grid_slot() {
+        let mut w = test_world();
+        let event = w.apply(
+            Command::CreateRecurringTask {
+                title: "Water the plants".into(),
+                service_id: Uuid::nil(),
+                priority: Priority::Low,
+                cron: "0 9 * * *".into(),
+                start_time: 540,
+                duration: 15,
+                horizon_days: 14,
+            },
+            Uuid::nil(),
+        ).unwrap();
+
+        let id = match event {
+            Event::TaskCreated { task, .. } => task.id,
+            _ => panic!("expected TaskCreated"),
+        };
+
+        let task = &w.tasks[&id];
+        assert_eq!(task.status, TaskStatus::Staged);
+        assert_eq!(task.date, None);
+        assert!(task.recurrence.is_some());
+        assert_eq!(task.recurrence.as_ref().unwrap().weekdays, vec![0, 1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn materialize_recurring_creates_occurrences_within_horizon() {
+        let mut w = test_world();
+        w.apply(
+            Command::CreateRecurringTask {
+                title: "Standup".into(),
+                service_id: Uuid::nil(),
+                priority: Priority::Medium,
+                cron: "0 9 * * *".into(), // every day
+                start_time: 540,
+                duration: 15,
+                horizon_days: 3,
+            },
+            Uuid::nil(),
+        ).unwrap();
+
+        w.materialize_recurring(D);
+
+        let occurrences: Vec<&Task> = w.tasks.values().filter(|t| t.parent_id.is_some()).collect();
+        assert_eq!(occurrences.len(), 4); // D, D+1, D+2, D+3 inclusive
+        for t in &occurrences {
+            assert_eq!(t.status, TaskStatus::Scheduled);
+            assert_eq!(t.start_time, Some(540));
+            assert_eq!(t.duration, Some(15));
+        }
+    }
+
+    #[test]
+    fn materialize_recurring_is_idempotent() {
+        let mut w = test_world();
+        w.apply(
+            Command::CreateRecurringTask {
+                title: "Standup".into(),
+                service_id: Uuid::nil(),
+                priority: Priority::Medium,
+                cron: "0 9 * * *".into(),
+                start_time: 540,
+                duration: 15,
+                horizon_days: 3,
+            },
+            Uuid::nil(),
+        ).unwrap();
+
+        w.materialize_recurring(D);
+        let count_after_first = w.tasks.values().filter(|t| t.parent_id.is_some()).count();
+
+        w.materialize_recurring(D);
+        let count_after_second = w.tasks.values().filter(|t| t.parent_id.is_some()).count();
+
+        assert_eq!(count_after_first, count_after_second);
+    }
+
+    #[test]
+    fn materialize_recurring_skips_non_matching_weekdays() {
+        let mut w = test_world();
+        // D is a Wednesday (weekday 2); only materialize Mondays (weekday 0).
+        w.apply(
+            Command::CreateRecurringTask {
+                title: "Monday meeting".into(),
+                service_id: Uuid::nil(),
+                priority: Priority::Medium,
+                cron: "0 9 * * 0".into(),
+                start_time: 540,
+                duration: 30,
+                horizon_days: 6,
+            },
+            Uuid::nil(),
+        ).unwrap();
+
+        w.materialize_recurring(D);
+
+        let occurrences: Vec<&Task> = w.tasks.values().filter(|t| t.parent_id.is_some()).collect();
+        // Within [D, D+6] exactly one Monday falls (D+5, per the D=Wed fixture).
+        assert_eq!(occurrences.len(), 1);
+        assert_eq!(occurrences[0].date, Some(D + 5));
+    }
+
+    #[test]
+    fn schedule_task_blocked_by_incomplete_dependency() {
+        let mut w = test_world();
+        let blocker = create_task(&mut w);
+        let blocked = create_task(&mut w);
+        w.apply(Command::AddDependency { task_id: blocked, depends_on: blocker }, Uuid::nil()).unwrap();
+
+        let result = w.apply(
+            Command::ScheduleTask { task_id: blocked, date: D, start_time: 540, duration: 30 },
+            Uuid::nil(),
+        );
+        assert_eq!(result.unwrap_err(), WorldError::BlockedByDependency);
+    }
+
+    #[test]
+    fn schedule_task_unblocked_once_dependency_completes() {
+        let mut w = test_world();
+        let blocker = create_task(&mut w);
+        let blocked = create_task(&mut w);
+        w.apply(Command::AddDependency { task_id: blocked, depends_on: blocker }, Uuid::nil()).unwrap();
+
+        w.apply(
+            Command::ScheduleTask { task_id: blocker, date: D, start_time: 540, duration: 30 },
+            Uuid::nil(),
+        ).unwrap();
+        w.apply(Command::CompleteTask { task_id: blocker }, Uuid::nil()).unwrap();
+
+        let result = w.apply(
+            Command::ScheduleTask { task_id: blocked, date: D2, start_time: 540, duration: 30 },
+            Uuid::nil(),
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn move_task_blocked_by_incomplete_dependency() {
+        let mut w = test_world();
+        let blocker = create_task(&mut w);
+        let blocked = create_task(&mut w);
+        w.apply(
+            Command::ScheduleTask { task_id: blocked, date: D, start_time: 540, duration: 30 },
+            Uuid::nil(),
+        ).unwrap();
+        w.apply(Command::AddDependency { task_id: blocked, depends_on: blocker }, Uuid::nil()).unwrap();
+
+        let result = w.apply(
+            Command::MoveTask { task_id: blocked, date: D2, start_time: 600, duration: 30, allow_overlap: false },
+            Uuid::nil(),
+        );
+        assert_eq!(result.unwrap_err(), WorldError::BlockedByDependency);
+    }
+
+    #[test]
+    fn add_dependency_rejects_direct_cycle() {
+        let mut w = test_world();
+        let a = create_task(&mut w);
+        let b = create_task(&mut w);
+        w.apply(Command::AddDependency { task_id: a, depends_on: b }, Uuid::nil()).unwrap();
+
+        let result = w.apply(Command::AddDependency { task_id: b, depends_on: a }, Uuid::nil());
+        assert_eq!(result.unwrap_err(), WorldError::DependencyCycle);
+    }
+
+    #[test]
+    fn add_dependency_rejects_transitive_cycle() {
+        let mut w = test_world();
+        let a = create_task(&mut w);
+        let b = create_task(&mut w);
+        let c = create_task(&mut w);
+        w.apply(Command::AddDependency { task_id: a, depends_on: b }, Uuid::nil()).unwrap();
+        w.apply(Command::AddDependency { task_id: b, depends_on: c }, Uuid::nil()).unwrap();
+
+        // c -> a would close the cycle a -> b -> c -> a
+        let result = w.apply(Command::AddDependency { task_id: c, depends_on: a }, Uuid::nil());
+        assert_eq!(result.unwrap_err(), WorldError::DependencyCycle);
+    }
+
+    #[test]
+    fn remove_dependency_unblocks_scheduling() {
+        let mut w = test_world();
+        let blocker = create_task(&mut w);
+        let blocked = create_task(&mut w);
+        w.apply(Command::AddDependency { task_id: blocked, depends_on: blocker }, Uuid::nil()).unwrap();
+        w.apply(Command::RemoveDependency { task_id: blocked, depends_on: blocker }, Uuid::nil()).unwrap();
+
+        let result = w.apply(
+            Command::ScheduleTask { task_id: blocked, date: D, start_time: 540, duration: 30 },
+            Uuid::nil(),
+        );
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn ready_queue_excludes_blocked_staged_tasks() {
+        let mut w = test_world();
+        let blocker = create_task(&mut w);
+        let blocked = create_task(&mut w);
+        w.apply(Command::AddDependency { task_id: blocked, depends_on: blocker }, Uuid::nil()).unwrap();
+
+        let ready_ids: Vec<Uuid> = w.ready_queue().iter().map(|t| t.id).collect();
+        assert!(ready_ids.contains(&blocker));
+        assert!(!ready_ids.contains(&blocked));
+    }
+
+    #[test]
+    fn undo_create_task_removes_it() {
+        let mut w = test_world();
+        let id = create_task(&mut w);
+        assert!(w.tasks.contains_key(&id));
+
+        let event = w.apply(Command::Undo, Uuid::nil()).unwrap();
+        assert!(!w.tasks.contains_key(&id));
+        match event {
+            Event::TaskDeleted { task_id, .. } => assert_eq!(task_id, id),
+            other => panic!("expected TaskDeleted, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn undo_schedule_task_reverts_to_staged() {
+        let mut w = test_world();
+        let id = create_task(&mut w);
+        w.apply(
+            Command::ScheduleTask { task_id: id, date: D, start_time: 540, duration: 30 },
+            Uuid::nil(),
+        ).unwrap();
+        assert_eq!(w.tasks[&id].status, TaskStatus::Scheduled);
+
+        w.apply(Command::Undo, Uuid::nil()).unwrap();
+        let task = &w.tasks[&id];
+        assert_eq!(task.status, TaskStatus::Staged);
+        assert_eq!(task.date, None);
+    }
+
+    #[test]
+    fn undo_complete_task_reverts_status() {
+        let mut w = test_world();
+        let id = create_task(&mut w);
+        w.apply(
+            Command::ScheduleTask { task_id: id, date: D, start_time: 540, duration: 30 },
+            Uuid::nil(),
+        ).unwrap();
+        w.apply(Command::CompleteTask { task_id: id }, Uuid::nil()).unwrap();
+        assert_eq!(w.tasks[&id].status, TaskStatus::Completed);
+
+        w.apply(Command::Undo, Uuid::nil()).unwrap();
+        assert_eq!(w.tasks[&id].status, TaskStatus::Scheduled);
+    }
+
+    #[test]
+    fn redo_reapplies_undone_command() {
+        let mut w = test_world();
+        let id = create_task(&mut w);
+        w.apply(
+            Command::ScheduleTask { task_id: id, date: D, start_time: 540, duration: 30 },
+            Uuid::nil(),
+        ).unwrap();
+        w.apply(Command::Undo, Uuid::nil()).unwrap();
+        assert_eq!(w.tasks[&id].status, TaskStatus::Staged);
+
+        w.apply(Command::Redo, Uuid::nil()).unwrap();
+        let task = &w.tasks[&id];
+        assert_eq!(task.status, TaskStatus::Scheduled);
+        assert_eq!(task.date, Some(D));
+    }
+
+    #[test]
+    fn fresh_mutation_clears_redo_stack() {
+        let mut w = test_world();
+        let id = create_task(&mut w);
+        w.apply(
+            Command::ScheduleTask { task_id: id, date: D, start_time: 540, duration: 30 },
+            Uuid::nil(),
+        ).unwrap();
+        w.apply(Command::Undo, Uuid::nil()).unwrap();
+
+        // A fresh mutation from the same user should invalidate the redo stack.
+        create_task(&mut w);
+
+        let result = w.apply(Command::Redo, Uuid::nil());
+        assert_eq!(result.unwrap_err(), WorldError::NothingToRedo);
+    }
+
+    #[test]
+    fn undo_with_empty_stack_errors() {
+        let mut w = test_world();
+        let result = w.apply(Command::Undo, Uuid::nil());
+        assert_eq!(result.unwrap_err(), WorldError::NothingToUndo);
+    }
+
+    #[test]
+    fn redo_with_empty_stack_errors() {
+        let mut w = test_world();
+        let result = w.apply(Command::Redo, Uuid::nil());
+        assert_eq!(result.unwrap_err(), WorldError::NothingToRedo);
+    }
+
+    #[test]
+    fn undo_is_scoped_per_user() {
         let mut w = test_world();
+        let alice = Uuid::new_v4();
+        let bob = Uuid::new_v4();
+
         let event = w.apply(
             Command::CreateTask {
-                title: "New task".into(),
+                title: "Alice's task".into(),
                 service_id: Uuid::nil(),
                 priority: Priority::Medium,
                 assigned_to: None,
-                date: Some(D),
-                start_time: Some(540),
-                duration: Some(30),
+                date: None,
+                start_time: None,
+                duration: None,
+            },
+            alice,
+        ).unwrap();
+        let id = match event {
+            Event::TaskCreated { task, .. } => task.id,
+            _ => panic!("expected TaskCreated"),
+        };
+
+        // Bob has nothing of his own to undo, even though Alice just acted.
+        let result = w.apply(Command::Undo, bob);
+        assert_eq!(result.unwrap_err(), WorldError::NothingToUndo);
+        assert!(w.tasks.contains_key(&id));
+    }
+
+    #[test]
+    fn apply_batch_applies_every_command_under_consecutive_revisions() {
+        let mut w = test_world();
+        let a = create_task(&mut w);
+        let b = create_task(&mut w);
+        let revision_before = w.revision;
+
+        let events = w.apply_batch(
+            vec![
+                Command::ScheduleTask { task_id: a, date: D, start_time: 540, duration: 30 },
+                Command::ScheduleTask { task_id: b, date: D, start_time: 600, duration: 30 },
+            ],
+            Uuid::nil(),
+        ).unwrap();
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(w.revision, revision_before + 2);
+        assert_eq!(w.tasks[&a].status, TaskStatus::Scheduled);
+        assert_eq!(w.tasks[&b].status, TaskStatus::Scheduled);
+    }
+
+    #[test]
+    fn apply_batch_rolls_back_all_mutations_on_failure() {
+        let mut w = test_world();
+        let a = create_task(&mut w);
+        let revision_before = w.revision;
+        let log_len_before = w.log.len();
+
+        // Second command targets a task that doesn't exist — the whole
+        // batch must fail, leaving the first command's effect undone too.
+        let result = w.apply_batch(
+            vec![
+                Command::ScheduleTask { task_id: a, date: D, start_time: 540, duration: 30 },
+                Command::ScheduleTask { task_id: Uuid::new_v4(), date: D, start_time: 600, duration: 30 },
+            ],
+            Uuid::nil(),
+        );
+
+        assert_eq!(result.unwrap_err(), WorldError::TaskNotFound);
+        assert_eq!(w.revision, revision_before);
+        assert_eq!(w.log.len(), log_len_before);
+        assert_eq!(w.tasks[&a].status, TaskStatus::Staged);
+    }
+
+    #[test]
+    fn apply_batch_rollback_discards_tasks_created_mid_batch() {
+        let mut w = test_world();
+        let revision_before = w.revision;
+
+        let result = w.apply_batch(
+            vec![
+                Command::CreateTask {
+                    title: "Should be rolled back".into(),
+                    service_id: Uuid::nil(),
+                    priority: Priority::Medium,
+                    assigned_to: None,
+                    date: None,
+                    start_time: None,
+                    duration: None,
+                },
+                Command::ScheduleTask { task_id: Uuid::new_v4(), date: D, start_time: 540, duration: 30 },
+            ],
+            Uuid::nil(),
+        );
+
+        assert_eq!(result.unwrap_err(), WorldError::TaskNotFound);
+        assert_eq!(w.revision, revision_before);
+        assert!(w.tasks.is_empty());
+    }
+
+    #[test]
+    fn apply_batch_rollback_restores_undo_history() {
+        let mut w = test_world();
+        let a = create_task(&mut w);
+        let undo_depth_before = w.undo_stacks.get(&Uuid::nil()).map_or(0, Vec::len);
+
+        let result = w.apply_batch(
+            vec![
+                Command::ScheduleTask { task_id: a, date: D, start_time: 540, duration: 30 },
+                Command::ScheduleTask { task_id: Uuid::new_v4(), date: D, start_time: 600, duration: 30 },
+            ],
+            Uuid::nil(),
+        );
+
+        assert!(result.is_err());
+        let undo_depth_after = w.undo_stacks.get(&Uuid::nil()).map_or(0, Vec::len);
+        assert_eq!(undo_depth_after, undo_depth_before);
+    }
+
+    fn create_assigned_task(w: &mut World, assignee: Uuid) -> Uuid {
+        let event = w.apply(
+            Command::CreateTask {
+                title: "Assigned task".into(),
+                service_id: Uuid::nil(),
+                priority: Priority::Medium,
+                assigned_to: Some(assignee),
+                date: None,
+                start_time: None,
+                duration: None,
             },
             Uuid::nil(),
         ).unwrap();
 
-        let id = match event {
+        match event {
             Event::TaskCreated { task, .. } => task.id,
             _ => panic!("expected TaskCreated"),
-        };
-
-        let task = &w.tasks[&id];
-        assert_eq!(task.status, TaskStatus::Scheduled);
-        assert_eq!(task.date, Some(D));
-        assert_eq!(task.start_time, Some(540));
-        assert_eq!(task.duration, Some(30));
+        }
     }
 
     #[test]
-    fn create_task_with_staged_sentinel_rejected() {
-        // 0xFFFF is the staged sentinel — passing it as a date is invalid
+    fn schedule_task_rejects_overlap_for_same_assignee() {
         let mut w = test_world();
+        let assignee = Uuid::new_v4();
+        let a = create_assigned_task(&mut w, assignee);
+        let b = create_assigned_task(&mut w, assignee);
+        w.apply(
+            Command::ScheduleTask { task_id: a, date: D, start_time: 540, duration: 60 },
+            Uuid::nil(),
+        ).unwrap();
+
+        // b overlaps a's [540, 600) window at 570..630
         let result = w.apply(
-            Command::CreateTask {
-                title: "Bad".into(),
-                service_id: Uuid::nil(),
-                priority: Priority::Medium,
-                assigned_to: None,
-                date: Some(0xFFFF),
-                start_time: Some(540),
-                duration: Some(30),
-            },
+            Command::ScheduleTask { task_id: b, date: D, start_time: 570, duration: 60 },
             Uuid::nil(),
         );
-        assert_eq!(result.unwrap_err(), WorldError::InvalidDate);
+        assert_eq!(result.unwrap_err(), WorldError::SlotConflict { conflicting: a });
     }
 
     #[test]
-    fn create_task_requires_valid_service() {
-        let mut w = World::new(); // no services
+    fn schedule_task_allows_adjacent_non_overlapping_slot() {
+        let mut w = test_world();
+        let assignee = Uuid::new_v4();
+        let a = create_assigned_task(&mut w, assignee);
+        let b = create_assigned_task(&mut w, assignee);
+        w.apply(
+            Command::ScheduleTask { task_id: a, date: D, start_time: 540, duration: 60 },
+            Uuid::nil(),
+        ).unwrap();
+
+        // b starts exactly when a ends — half-open intervals, no overlap
         let result = w.apply(
-            Command::CreateTask {
-                title: "Orphan".into(),
-                service_id: Uuid::new_v4(),
-                priority: Priority::Low,
-                assigned_to: None,
-                date: None,
-                start_time: None,
-                duration: None,
-            },
+            Command::ScheduleTask { task_id: b, date: D, start_time: 600, duration: 30 },
             Uuid::nil(),
         );
-        assert_eq!(result.unwrap_err(), WorldError::ServiceNotFound);
-        assert_eq!(w.revision, 0); // nothing changed
+        assert!(result.is_ok());
     }
 
     #[test]
-    fn schedule_staged_task() {
+    fn schedule_task_ignores_conflicts_with_other_assignees() {
         let mut w = test_world();
-        let id = create_task(&mut w);
-
+        let a = create_assigned_task(&mut w, Uuid::new_v4());
+        let b = create_assigned_task(&mut w, Uuid::new_v4());
         w.apply(
-            Command::ScheduleTask { task_id: id, date: D, start_time: 540, duration: 60 },
+            Command::ScheduleTask { task_id: a, date: D, start_time: 540, duration: 60 },
             Uuid::nil(),
         ).unwrap();
 
-        let task = &w.tasks[&id];
-        assert_eq!(task.status, TaskStatus::Scheduled);
-        assert_eq!(task.date, Some(D));
-        assert_eq!(task.start_time, Some(540)); // 9:00 AM
-        assert_eq!(task.duration, Some(60));    // 1 hour
-        assert_eq!(w.revision, 2);
+        let result = w.apply(
+            Command::ScheduleTask { task_id: b, date: D, start_time: 540, duration: 60 },
+            Uuid::nil(),
+        );
+        assert!(result.is_ok());
     }
 
     #[test]
-    fn cannot_schedule_already_scheduled() {
+    fn move_task_rejects_overlap_unless_allow_overlap_set() {
         let mut w = test_world();
-        let id = create_task(&mut w);
-
+        let assignee = Uuid::new_v4();
+        let a = create_assigned_task(&mut w, assignee);
+        let b = create_assigned_task(&mut w, assignee);
         w.apply(
-            Command::ScheduleTask { task_id: id, date: D, start_time: 480, duration: 30 },
+            Command::ScheduleTask { task_id: a, date: D, start_time: 540, duration: 60 },
+            Uuid::nil(),
+        ).unwrap();
+        w.apply(
+            Command::ScheduleTask { task_id: b, date: D2, start_time: 540, duration: 60 },
             Uuid::nil(),
         ).unwrap();
 
-        let result = w.apply(
-            Command::ScheduleTask { task_id: id, date: D2, start_time: 600, duration: 30 },
+        let blocked = w.apply(
+            Command::MoveTask { task_id: b, date: D, start_time: 570, duration: 60, allow_overlap: false },
             Uuid::nil(),
         );
-        assert_eq!(result.unwrap_err(), WorldError::InvalidTransition);
+        assert_eq!(blocked.unwrap_err(), WorldError::SlotConflict { conflicting: a });
+
+        let allowed = w.apply(
+            Command::MoveTask { task_id: b, date: D, start_time: 570, duration: 60, allow_overlap: true },
+            Uuid::nil(),
+        );
+        assert!(allowed.is_ok());
     }
 
     #[test]
-    fn move_scheduled_task() {
+    fn day_agenda_returns_assignees_tasks_sorted_by_start_time() {
         let mut w = test_world();
-        let id = create_task(&mut w);
-
+        let assignee = Uuid::new_v4();
+        let a = create_assigned_task(&mut w, assignee);
+        let b = create_assigned_task(&mut w, assignee);
         w.apply(
-            Command::ScheduleTask { task_id: id, date: D, start_time: 480, duration: 60 },
+            Command::ScheduleTask { task_id: a, date: D, start_time: 600, duration: 30 },
             Uuid::nil(),
         ).unwrap();
-
         w.apply(
-            Command::MoveTask { task_id: id, date: D2, start_time: 840, duration: 90 },
+            Command::ScheduleTask { task_id: b, date: D, start_time: 480, duration: 30 },
             Uuid::nil(),
         ).unwrap();
 
-        let task = &w.tasks[&id];
-        assert_eq!(task.date, Some(D2));
-        assert_eq!(task.start_time, Some(840)); // 2:00 PM
-        assert_eq!(task.duration, Some(90));    // 1.5 hours
-        assert_eq!(w.revision, 3);
+        let agenda: Vec<Uuid> = w.day_agenda(D, assignee).iter().map(|t| t.id).collect();
+        assert_eq!(agenda, vec![b, a]);
     }
 
     #[test]
-    fn cannot_move_staged_task() {
+    fn query_filters_by_status() {
         let mut w = test_world();
-        let id = create_task(&mut w);
-
-        let result = w.apply(
-            Command::MoveTask { task_id: id, date: D, start_time: 480, duration: 60 },
+        let staged = create_task(&mut w);
+        let scheduled = create_task(&mut w);
+        w.apply(
+            Command::ScheduleTask { task_id: scheduled, date: D, start_time: 540, duration: 30 },
             Uuid::nil(),
-        );
-        assert_eq!(result.unwrap_err(), WorldError::InvalidTransition);
+        ).unwrap();
+
+        let staged_ids: Vec<Uuid> = w.query(TaskFilter { status: Some(TaskStatus::Staged), ..Default::default() })
+            .iter().map(|t| t.id).collect();
+        assert_eq!(staged_ids, vec![staged]);
+
+        let scheduled_ids: Vec<Uuid> = w.query(TaskFilter { status: Some(TaskStatus::Scheduled), ..Default::default() })
+            .iter().map(|t| t.id).collect();
+        assert_eq!(scheduled_ids, vec![scheduled]);
     }
 
     #[test]
-    fn unschedule_puts_task_back_in_staging() {
+    fn query_filters_by_assignee_and_intersects_with_status() {
         let mut w = test_world();
-        let id = create_task(&mut w);
-
+        let alice = Uuid::new_v4();
+        let bob = Uuid::new_v4();
+        let a1 = create_assigned_task(&mut w, alice);
+        let a2 = create_assigned_task(&mut w, alice);
+        let _b1 = create_assigned_task(&mut w, bob);
         w.apply(
-            Command::ScheduleTask { task_id: id, date: D, start_time: 600, duration: 30 },
+            Command::ScheduleTask { task_id: a2, date: D, start_time: 540, duration: 30 },
             Uuid::nil(),
         ).unwrap();
 
-        w.apply(Command::UnscheduleTask { task_id: id }, Uuid::nil()).unwrap();
-
-        let task = &w.tasks[&id];
-        assert_eq!(task.status, TaskStatus::Staged);
-        assert_eq!(task.date, None);
-        assert_eq!(task.start_time, None);
-        assert_eq!(task.duration, None);
+        let alice_staged: Vec<Uuid> = w.query(TaskFilter {
+            status: Some(TaskStatus::Staged),
+            assignee: Some(alice),
+            ..Default::default()
+        }).iter().map(|t| t.id).collect();
+        assert_eq!(alice_staged, vec![a1]);
     }
 
     #[test]
-    fn complete_scheduled_task() {
+    fn query_filters_by_date_range() {
         let mut w = test_world();
-        let id = create_task(&mut w);
-
+        let in_range = create_assigned_task(&mut w, Uuid::new_v4());
+        let out_of_range = create_assigned_task(&mut w, Uuid::new_v4());
         w.apply(
-            Command::ScheduleTask { task_id: id, date: D, start_time: 480, duration: 60 },
+            Command::ScheduleTask { task_id: in_range, date: D, start_time: 540, duration: 30 },
+            Uuid::nil(),
+        ).unwrap();
+        w.apply(
+            Command::ScheduleTask { task_id: out_of_range, date: D2, start_time: 540, duration: 30 },
             Uuid::nil(),
         ).unwrap();
 
-        w.apply(Command::CompleteTask { task_id: id }, Uuid::nil()).unwrap();
-
-        assert_eq!(w.tasks[&id].status, TaskStatus::Completed);
+        let ids: Vec<Uuid> = w.query(TaskFilter { date_range: Some((D, D)), ..Default::default() })
+            .iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![in_range]);
     }
 
     #[test]
-    fn cannot_complete_staged_task() {
+    fn rebuild_indexes_repopulates_from_tasks_after_direct_insertion() {
         let mut w = test_world();
-        let id = create_task(&mut w);
+        let task = Task {
+            id: Uuid::new_v4(),
+            title: "Loaded from disk".into(),
+            status: TaskStatus::Staged,
+            priority: Priority::Medium,
+            service_id: Uuid::nil(),
+            created_by: Uuid::nil(),
+            assigned_to: Some(Uuid::new_v4()),
+            date: None,
+            start_time: None,
+            duration: None,
+            recurrence: None,
+            parent_id: None,
+            depends_on: Vec::new(),
+            failure: None,
+            retry_count: 0,
+        };
+        let assignee = task.assigned_to.unwrap();
+        w.tasks.insert(task.id, task.clone());
 
-        let result = w.apply(Command::CompleteTask { task_id: id }, Uuid::nil());
-        assert_eq!(result.unwrap_err(), WorldError::InvalidTransition);
+        // Indexes are empty until rebuild_indexes() runs, matching what a
+        // boot-time loader does after populating `tasks` directly.
+        assert!(w.query(TaskFilter { status: Some(TaskStatus::Staged), ..Default::default() }).is_empty());
+
+        w.rebuild_indexes();
+
+        let found: Vec<Uuid> = w.query(TaskFilter { assignee: Some(assignee), ..Default::default() })
+            .iter().map(|t| t.id).collect();
+        assert_eq!(found, vec![task.id]);
     }
 
     #[test]
-    fn delete_task() {
+    fn indexes_stay_consistent_across_complete_delete_and_undo() {
         let mut w = test_world();
-        let id = create_task(&mut w);
+        let task = create_task(&mut w);
 
-        w.apply(Command::DeleteTask { task_id: id }, Uuid::nil()).unwrap();
+        w.apply(Command::ScheduleTask { task_id: task, date: D, start_time: 540, duration: 30 }, Uuid::nil()).unwrap();
+        w.apply(Command::CompleteTask { task_id: task }, Uuid::nil()).unwrap();
+        assert_eq!(
+            w.query(TaskFilter { status: Some(TaskStatus::Completed), ..Default::default() }).len(),
+            1
+        );
 
-        assert!(!w.tasks.contains_key(&id));
+        // Undo the completion — task should move back out of the Completed index.
+        w.apply(Command::Undo, Uuid::nil()).unwrap();
+        assert!(w.query(TaskFilter { status: Some(TaskStatus::Completed), ..Default::default() }).is_empty());
+        assert_eq!(
+            w.query(TaskFilter { status: Some(TaskStatus::Scheduled), ..Default::default() }).len(),
+            1
+        );
+
+        // Redo it, then delete — the Completed index entry must go with it.
+        w.apply(Command::Redo, Uuid::nil()).unwrap();
+        w.apply(Command::DeleteTask { task_id: task }, Uuid::nil()).unwrap();
+        assert!(w.query(TaskFilter::default()).is_empty());
     }
 
     #[test]
-    fn delete_nonexistent_task() {
+    fn fail_task_records_reason_and_moves_to_failed() {
         let mut w = test_world();
-        let result = w.apply(
-            Command::DeleteTask { task_id: Uuid::new_v4() },
+        let task = create_task(&mut w);
+        w.apply(Command::ScheduleTask { task_id: task, date: D, start_time: 540, duration: 30 }, Uuid::nil()).unwrap();
+
+        let event = w.apply(
+            Command::FailTask { task_id: task, reason: "connection refused".into() },
             Uuid::nil(),
-        );
-        assert_eq!(result.unwrap_err(), WorldError::TaskNotFound);
+        ).unwrap();
+        match event {
+            Event::TaskFailed { reason, .. } => assert_eq!(reason, "connection refused"),
+            other => panic!("expected TaskFailed, got {other:?}"),
+        }
+        assert_eq!(w.tasks[&task].status, TaskStatus::Failed);
+        assert_eq!(w.tasks[&task].failure.as_deref(), Some("connection refused"));
     }
 
     #[test]
-    fn staging_queue_sorted_by_priority() {
+    fn fail_task_rejects_staged_task() {
         let mut w = test_world();
-        let user = Uuid::nil();
-
-        w.apply(Command::CreateTask {
-            title: "Low".into(), service_id: Uuid::nil(),
-            priority: Priority::Low, assigned_to: None,
-            date: None, start_time: None, duration: None,
-        }, user).unwrap();
+        let task = create_task(&mut w);
 
-        w.apply(Command::CreateTask {
-            title: "Urgent".into(), service_id: Uuid::nil(),
-            priority: Priority::Urgent, assigned_to: None,
-            date: None, start_time: None, duration: None,
-        }, user).unwrap();
+        let result = w.apply(Command::FailTask { task_id: task, reason: "oops".into() }, Uuid::nil());
+        assert_eq!(result.unwrap_err(), WorldError::InvalidTransition);
+    }
 
-        w.apply(Command::CreateTask {
-            title: "High".into(), service_id: Uuid::nil(),
-            priority: Priority::High, assigned_to: None,
-            date: None, start_time: None, duration: None,
-        }, user).unwrap();
+    #[test]
+    fn retry_task_returns_to_staged_and_increments_retry_count() {
+        let mut w = test_world();
+        let task = create_task(&mut w);
+        w.apply(Command::ScheduleTask { task_id: task, date: D, start_time: 540, duration: 30 }, Uuid::nil()).unwrap();
+        w.apply(Command::FailTask { task_id: task, reason: "boom".into() }, Uuid::nil()).unwrap();
 
-        let queue = w.staging_queue();
-        assert_eq!(queue.len(), 3);
-        assert_eq!(queue[0].priority, Priority::Urgent);
-        assert_eq!(queue[1].priority, Priority::High);
-        assert_eq!(queue[2].priority, Priority::Low);
+        let event = w.apply(Command::RetryTask { task_id: task }, Uuid::nil()).unwrap();
+        match event {
+            Event::TaskRetried { retry_count, .. } => assert_eq!(retry_count, 1),
+            other => panic!("expected TaskRetried, got {other:?}"),
+        }
+        let retried = &w.tasks[&task];
+        assert_eq!(retried.status, TaskStatus::Staged);
+        assert_eq!(retried.date, None);
+        assert_eq!(retried.retry_count, 1);
+        // The last failure reason stays visible even after the retry.
+        assert_eq!(retried.failure.as_deref(), Some("boom"));
     }
 
     #[test]
-    fn scheduling_validation() {
+    fn retry_task_rejects_non_failed_task() {
         let mut w = test_world();
-        let id = create_task(&mut w);
+        let task = create_task(&mut w);
 
-        // Staged sentinel (0xFFFF) is not a valid date
-        let r = w.apply(
-            Command::ScheduleTask { task_id: id, date: 0xFFFF, start_time: 480, duration: 60 },
-            Uuid::nil(),
-        );
-        assert_eq!(r.unwrap_err(), WorldError::InvalidDate);
+        let result = w.apply(Command::RetryTask { task_id: task }, Uuid::nil());
+        assert_eq!(result.unwrap_err(), WorldError::InvalidTransition);
+    }
 
-        // Time not on 15-min grid
-        let r = w.apply(
-            Command::ScheduleTask { task_id: id, date: D, start_time: 487, duration: 60 },
-            Uuid::nil(),
-        );
-        assert_eq!(r.unwrap_err(), WorldError::InvalidTime);
+    #[test]
+    fn retry_task_rejects_once_max_retries_reached() {
+        let mut w = test_world();
+        w.set_max_retries(Some(1));
+        let task = create_task(&mut w);
+        w.apply(Command::ScheduleTask { task_id: task, date: D, start_time: 540, duration: 30 }, Uuid::nil()).unwrap();
+        w.apply(Command::FailTask { task_id: task, reason: "first".into() }, Uuid::nil()).unwrap();
+        w.apply(Command::RetryTask { task_id: task }, Uuid::nil()).unwrap();
 
-        // Duration zero
-        let r = w.apply(
-            Command::ScheduleTask { task_id: id, date: D, start_time: 480, duration: 0 },
-            Uuid::nil(),
-        );
-        assert_eq!(r.unwrap_err(), WorldError::InvalidDuration);
+        w.apply(Command::ScheduleTask { task_id: task, date: D, start_time: 540, duration: 30 }, Uuid::nil()).unwrap();
+        w.apply(Command::FailTask { task_id: task, reason: "second".into() }, Uuid::nil()).unwrap();
 
-        // Goes past midnight
-        let r = w.apply(
-            Command::ScheduleTask { task_id: id, date: D, start_time: 1380, duration: 120 },
-            Uuid::nil(),
-        );
-        assert_eq!(r.unwrap_err(), WorldError::InvalidDuration);
+        let result = w.apply(Command::RetryTask { task_id: task }, Uuid::nil());
+        assert_eq!(result.unwrap_err(), WorldError::RetryExhausted);
     }
 
     #[test]
-    fn revision_increments_on_every_mutation() {
+    fn undo_redo_round_trip_fail_and_retry() {
         let mut w = test_world();
-        assert_eq!(w.revision, 0);
+        let task = create_task(&mut w);
+        w.apply(Command::ScheduleTask { task_id: task, date: D, start_time: 540, duration: 30 }, Uuid::nil()).unwrap();
+        w.apply(Command::FailTask { task_id: task, reason: "timed out".into() }, Uuid::nil()).unwrap();
+        w.apply(Command::RetryTask { task_id: task }, Uuid::nil()).unwrap();
+
+        // Undo the retry — back to Failed with the same retry_count as before.
+        w.apply(Command::Undo, Uuid::nil()).unwrap();
+        assert_eq!(w.tasks[&task].status, TaskStatus::Failed);
+        assert_eq!(w.tasks[&task].retry_count, 0);
+
+        // Undo the failure — back to Scheduled, failure cleared.
+        w.apply(Command::Undo, Uuid::nil()).unwrap();
+        assert_eq!(w.tasks[&task].status, TaskStatus::Scheduled);
+        assert_eq!(w.tasks[&task].failure, None);
+
+        // Redo both — ends up exactly where we started undoing from.
+        w.apply(Command::Redo, Uuid::nil()).unwrap();
+        w.apply(Command::Redo, Uuid::nil()).unwrap();
+        assert_eq!(w.tasks[&task].status, TaskStatus::Staged);
+        assert_eq!(w.tasks[&task].retry_count, 1);
+    }
 
-        let id = create_task(&mut w);
-        assert_eq!(w.revision, 1);
+    #[test]
+    fn compact_folds_log_into_snapshot_and_trims_it() {
+        let mut w = test_world();
+        create_task(&mut w); // rev 1
+        create_task(&mut w); // rev 2
+        create_task(&mut w); // rev 3
 
-        w.apply(
-            Command::ScheduleTask { task_id: id, date: D, start_time: 480, duration: 60 },
-            Uuid::nil(),
-        ).unwrap();
-        assert_eq!(w.revision, 2);
+        w.compact(2);
 
-        w.apply(
-            Command::MoveTask { task_id: id, date: D2, start_time: 600, duration: 30 },
-            Uuid::nil(),
-        ).unwrap();
-        assert_eq!(w.revision, 3);
+        assert_eq!(w.log.len(), 1);
+        assert_eq!(w.log[0].0, 3);
+        assert_eq!(w.snapshot.as_ref().unwrap().base_revision, 2);
+        assert_eq!(w.snapshot.as_ref().unwrap().tasks.len(), 2);
+    }
 
-        w.apply(Command::CompleteTask { task_id: id }, Uuid::nil()).unwrap();
-        assert_eq!(w.revision, 4);
+    #[test]
+    fn events_since_serves_snapshot_and_tail_past_the_compaction_boundary() {
+        let mut w = test_world();
+        create_task(&mut w); // rev 1
+        create_task(&mut w); // rev 2
+        create_task(&mut w); // rev 3
+
+        w.compact(2);
+
+        let needs = w.events_since(0).unwrap_err();
+        assert_eq!(needs.snapshot.base_revision, 2);
+        assert_eq!(needs.snapshot.tasks.len(), 2);
+        assert_eq!(needs.tail.len(), 1);
+        assert_eq!(needs.tail[0].0, 3);
+
+        // A client already past the boundary still replays straight from log.
+        assert_eq!(w.events_since(2).unwrap().len(), 1);
     }
 
     #[test]
-    fn event_log_tracks_history() {
+    fn events_since_current_is_empty_even_after_compaction() {
         let mut w = test_world();
-        let id = create_task(&mut w);
+        create_task(&mut w);
+        create_task(&mut w);
 
-        w.apply(
-            Command::ScheduleTask { task_id: id, date: D, start_time: 480, duration: 60 },
-            Uuid::nil(),
-        ).unwrap();
+        w.compact(2);
 
-        assert_eq!(w.log.len(), 2);
-        assert_eq!(w.log[0].0, 1); // rev 1 = create
-        assert_eq!(w.log[1].0, 2); // rev 2 = schedule
+        assert_eq!(w.events_since(2).unwrap().len(), 0);
     }
 
     #[test]
-    fn events_since_for_reconnect() {
+    fn compact_is_incremental_across_repeated_calls() {
         let mut w = test_world();
         create_task(&mut w); // rev 1
         create_task(&mut w); // rev 2
         create_task(&mut w); // rev 3
+        create_task(&mut w); // rev 4
 
-        // Client last saw rev 1, needs events 2 and 3
-        let events = w.events_since(1).unwrap();
-        assert_eq!(events.len(), 2);
-        assert_eq!(events[0].0, 2);
-        assert_eq!(events[1].0, 3);
-
-        // Client is up to date
-        let events = w.events_since(3).unwrap();
-        assert_eq!(events.len(), 0);
+        w.compact(2);
+        w.compact(4);
 
-        // Client at rev 0, needs everything
-        let events = w.events_since(0).unwrap();
-        assert_eq!(events.len(), 3);
+        assert!(w.log.is_empty());
+        let snapshot = w.snapshot.as_ref().unwrap();
+        assert_eq!(snapshot.base_revision, 4);
+        assert_eq!(snapshot.tasks.len(), 4);
     }
 
     #[test]
-    fn failed_commands_dont_change_state() {
+    fn compact_up_to_an_already_compacted_revision_is_a_no_op() {
         let mut w = test_world();
-        let rev_before = w.revision;
-        let log_len_before = w.log.len();
+        create_task(&mut w);
+        create_task(&mut w);
 
-        // Try to delete a task that doesn't exist
-        let _ = w.apply(
-            Command::DeleteTask { task_id: Uuid::new_v4() },
-            Uuid::nil(),
-        );
+        w.compact(2);
+        let base_before = w.snapshot.as_ref().unwrap().base_revision;
+        w.compact(1); // already folded past this point
 
-        assert_eq!(w.revision, rev_before);
-        assert_eq!(w.log.len(), log_len_before);
+        assert_eq!(w.snapshot.as_ref().unwrap().base_revision, base_before);
     }
 }